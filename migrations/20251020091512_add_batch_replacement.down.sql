@@ -0,0 +1,5 @@
+ALTER TABLE bria_batch_wallet_summaries DROP COLUMN batch_replaced_ledger_tx_id;
+
+ALTER TABLE bria_batches
+DROP COLUMN superseded_by_batch_id,
+DROP COLUMN replaces_batch_id;
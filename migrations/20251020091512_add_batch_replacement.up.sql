@@ -0,0 +1,6 @@
+ALTER TABLE bria_batches
+ADD COLUMN replaces_batch_id UUID REFERENCES bria_batches(id) DEFAULT NULL,
+ADD COLUMN superseded_by_batch_id UUID REFERENCES bria_batches(id) DEFAULT NULL;
+
+ALTER TABLE bria_batch_wallet_summaries
+ADD COLUMN batch_replaced_ledger_tx_id UUID DEFAULT NULL;
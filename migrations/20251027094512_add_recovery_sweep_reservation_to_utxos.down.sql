@@ -0,0 +1 @@
+ALTER TABLE bria_utxos DROP COLUMN recovery_sweep_reserved_at;
@@ -0,0 +1,2 @@
+ALTER TABLE bria_utxos
+ADD COLUMN recovery_sweep_reserved_at TIMESTAMPTZ DEFAULT NULL;
@@ -0,0 +1,3 @@
+ALTER TABLE bria_batches
+DROP COLUMN last_broadcast_at,
+DROP COLUMN broadcast_results;
@@ -0,0 +1,3 @@
+ALTER TABLE bria_batches
+ADD COLUMN broadcast_results JSONB NOT NULL DEFAULT '[]',
+ADD COLUMN last_broadcast_at TIMESTAMPTZ DEFAULT NULL;
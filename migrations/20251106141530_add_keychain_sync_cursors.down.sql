@@ -0,0 +1,2 @@
+DROP INDEX idx_bdk_script_pubkeys_created_at;
+DROP TABLE bria_keychain_sync_cursors;
@@ -0,0 +1,11 @@
+CREATE TABLE bria_keychain_sync_cursors (
+  keychain_id UUID PRIMARY KEY,
+  account_id UUID REFERENCES bria_accounts(id) NOT NULL,
+  wallet_id UUID REFERENCES bria_wallets(id) NOT NULL,
+  height INTEGER NOT NULL,
+  dirty_since TIMESTAMPTZ DEFAULT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  modified_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_bria_keychain_sync_cursors_wallet_id ON bria_keychain_sync_cursors (wallet_id);
+CREATE INDEX idx_bdk_script_pubkeys_created_at ON bdk_script_pubkeys (created_at);
@@ -0,0 +1,2 @@
+ALTER TABLE bria_utxos DROP COLUMN spend_settled_block_height;
+DROP TABLE bria_block_hashes;
@@ -0,0 +1,15 @@
+CREATE TABLE bria_block_hashes (
+  keychain_id UUID NOT NULL,
+  height INTEGER NOT NULL,
+  block_hash VARCHAR NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE(keychain_id, height)
+);
+
+ALTER TABLE bria_utxos ADD COLUMN spend_settled_block_height INTEGER DEFAULT NULL;
+
+UPDATE bria_utxos u
+SET spend_settled_block_height = c.block_height
+FROM bria_utxos c
+WHERE u.spend_settled_ledger_tx_id IS NOT NULL
+  AND c.income_settled_ledger_tx_id = u.spend_settled_ledger_tx_id;
@@ -0,0 +1 @@
+DROP TABLE bria_keychain_rescans;
@@ -0,0 +1,8 @@
+CREATE TABLE bria_keychain_rescans (
+  keychain_id UUID PRIMARY KEY,
+  account_id UUID REFERENCES bria_accounts(id) NOT NULL,
+  wallet_id UUID REFERENCES bria_wallets(id) NOT NULL,
+  birthday_height INTEGER DEFAULT NULL,
+  gap_limit INTEGER DEFAULT NULL,
+  requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
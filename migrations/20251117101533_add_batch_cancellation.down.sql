@@ -0,0 +1,3 @@
+ALTER TABLE bria_batch_wallet_summaries DROP COLUMN batch_cancelled_ledger_tx_id;
+
+ALTER TABLE bria_batches DROP COLUMN cancelled_at;
@@ -0,0 +1,5 @@
+ALTER TABLE bria_batches
+ADD COLUMN cancelled_at TIMESTAMPTZ DEFAULT NULL;
+
+ALTER TABLE bria_batch_wallet_summaries
+ADD COLUMN batch_cancelled_ledger_tx_id UUID DEFAULT NULL;
@@ -7,12 +7,13 @@ use crate::{
     app::error::*,
     batch::*,
     batch_inclusion::PayoutWithInclusionEstimate,
+    fees::FeeEstimatorHealth,
     outbox::*,
     payout::*,
     payout_queue::*,
     primitives::{bitcoin::*, *},
     profile::*,
-    signing_session::*,
+    signing_session::{error::SigningSessionError, *},
     tracing::ToTraceLevel,
     utxo::*,
     wallet::balance::WalletBalanceSummary,
@@ -83,6 +84,12 @@ impl TryFrom<Option<proto::set_signer_config_request::Config>> for SignerConfig
                     rpc_password: config.rpc_password,
                 }))
             }
+            Some(proto::set_signer_config_request::Config::Webhook(config)) => {
+                Ok(SignerConfig::Webhook(WebhookSignerConfig {
+                    endpoint: config.endpoint,
+                    hmac_secret: config.hmac_secret,
+                }))
+            }
             None => Err(tonic::Status::new(
                 tonic::Code::InvalidArgument,
                 "missing signer config",
@@ -218,6 +225,7 @@ impl From<Wallet> for proto::Wallet {
             id,
             name,
             config: Some(config),
+            birthday_height: wallet.birthday_height,
         }
     }
 }
@@ -249,7 +257,13 @@ impl From<PayoutQueue> for proto::PayoutQueue {
             consolidate_deprecated_keychains: payout_queue.config.consolidate_deprecated_keychains,
             cpfp_payouts_after_mins: payout_queue.config.cpfp_payouts_after_mins,
             cpfp_payouts_after_blocks: payout_queue.config.cpfp_payouts_after_blocks,
+            rbf_after_mins: payout_queue.config.rbf_after_mins,
+            rbf_after_blocks: payout_queue.config.rbf_after_blocks,
+            add_payouts_to_unconfirmed_batch: payout_queue.config.add_payouts_to_unconfirmed_batch,
             force_min_change_sats: payout_queue.config.force_min_change_sats.map(u64::from),
+            min_fee_rate: payout_queue.config.min_fee_rate,
+            max_fee_rate: payout_queue.config.max_fee_rate,
+            defer_above_max_fee_rate: payout_queue.config.defer_above_max_fee_rate,
         });
         proto::PayoutQueue {
             id,
@@ -260,6 +274,18 @@ impl From<PayoutQueue> for proto::PayoutQueue {
     }
 }
 
+impl From<FeeEstimatorHealth> for proto::FeeEstimatorHealth {
+    fn from(health: FeeEstimatorHealth) -> Self {
+        Self {
+            estimator: health.estimator.to_string(),
+            successes: health.successes,
+            failures: health.failures,
+            implausible_estimates: health.implausible_estimates,
+            consecutive_failures: health.consecutive_failures,
+        }
+    }
+}
+
 impl From<TxPriority> for proto::TxPriority {
     fn from(priority: TxPriority) -> Self {
         match priority {
@@ -278,6 +304,47 @@ impl From<SigningSession> for proto::SigningSession {
             xpub_id: session.xpub_fingerprint.to_string(),
             failure_reason: session.failure_reason().map(|r| r.to_string()),
             state: format!("{:?}", session.state()),
+            unsigned_psbt: session.unsigned_psbt.to_string(),
+            attempt_count: session.attempt_count() as u32,
+            failures: session
+                .failures()
+                .map(|(failed_at, reason)| proto::SigningFailure {
+                    reason: reason.to_string(),
+                    failed_at: failed_at.timestamp() as u32,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<signing_session_cursor::SigningSessionsByCreatedAtCursor> for String {
+    fn from(cursor: signing_session_cursor::SigningSessionsByCreatedAtCursor) -> Self {
+        use base64::{engine::general_purpose, Engine};
+        general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&cursor).expect("cursor should always serialize"))
+    }
+}
+
+impl TryFrom<String> for signing_session_cursor::SigningSessionsByCreatedAtCursor {
+    type Error = ApplicationError;
+
+    fn try_from(cursor: String) -> Result<Self, Self::Error> {
+        use base64::{engine::general_purpose, Engine};
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|err| ApplicationError::CouldNotParseIncomingCursor(err.to_string()))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|err| ApplicationError::CouldNotParseIncomingCursor(err.to_string()))
+    }
+}
+
+impl From<SigningSession> for proto::UnsignedPsbt {
+    fn from(session: SigningSession) -> Self {
+        proto::UnsignedPsbt {
+            batch_id: session.batch_id.to_string(),
+            signing_session_id: session.id.to_string(),
+            tx_id: session.unsigned_psbt.unsigned_tx.txid().to_string(),
+            unsigned_psbt: session.unsigned_psbt.to_string(),
         }
     }
 }
@@ -303,7 +370,13 @@ impl From<proto::PayoutQueueConfig> for PayoutQueueConfig {
             consolidate_deprecated_keychains: proto_config.consolidate_deprecated_keychains,
             cpfp_payouts_after_mins: proto_config.cpfp_payouts_after_mins,
             cpfp_payouts_after_blocks: proto_config.cpfp_payouts_after_blocks,
+            rbf_after_mins: proto_config.rbf_after_mins,
+            rbf_after_blocks: proto_config.rbf_after_blocks,
+            add_payouts_to_unconfirmed_batch: proto_config.add_payouts_to_unconfirmed_batch,
             force_min_change_sats: proto_config.force_min_change_sats.map(Satoshis::from),
+            min_fee_rate: proto_config.min_fee_rate,
+            max_fee_rate: proto_config.max_fee_rate,
+            defer_above_max_fee_rate: proto_config.defer_above_max_fee_rate,
             ..Self::default()
         };
 
@@ -327,6 +400,15 @@ impl From<proto::TxPriority> for TxPriority {
     }
 }
 
+impl From<BroadcastResult> for proto::BroadcastResult {
+    fn from(result: BroadcastResult) -> Self {
+        Self {
+            chain_source: result.chain_source,
+            error: result.error,
+        }
+    }
+}
+
 impl From<(WalletSummary, Vec<Payout>)> for proto::BatchWalletSummary {
     fn from((summary, payouts): (WalletSummary, Vec<Payout>)) -> Self {
         Self {
@@ -477,6 +559,57 @@ impl From<OutboxEvent<Augmentation>> for proto::BriaEvent {
                 satoshis: u64::from(satoshis),
                 address: address.to_string(),
             }),
+            OutboxEventPayload::UtxoReorged {
+                tx_id,
+                vout,
+                satoshis,
+                address,
+                wallet_id,
+                reorged_confirmation_time,
+                ..
+            } => proto::bria_event::Payload::UtxoReorged(proto::UtxoReorged {
+                wallet_id: wallet_id.to_string(),
+                tx_id: tx_id.to_string(),
+                vout,
+                satoshis: u64::from(satoshis),
+                address: address.to_string(),
+                reorged_block_height: reorged_confirmation_time.height,
+            }),
+            OutboxEventPayload::SpendReorged {
+                tx_id,
+                wallet_id,
+                batch_id,
+                total_utxo_in_sats,
+                fee_sats,
+                reorged_confirmation_time,
+                ..
+            } => proto::bria_event::Payload::SpendReorged(proto::SpendReorged {
+                wallet_id: wallet_id.to_string(),
+                tx_id: tx_id.to_string(),
+                batch_id: batch_id.map(|id| id.to_string()),
+                total_utxo_in_sats: u64::from(total_utxo_in_sats),
+                fee_sats: u64::from(fee_sats),
+                reorged_block_height: reorged_confirmation_time.height,
+            }),
+            OutboxEventPayload::UtxoOpeningBalance {
+                tx_id,
+                vout,
+                satoshis,
+                address,
+                wallet_id,
+                confirmation_time,
+                birthday_height,
+                ..
+            } => proto::bria_event::Payload::UtxoOpeningBalance(proto::UtxoOpeningBalance {
+                wallet_id: wallet_id.to_string(),
+                tx_id: tx_id.to_string(),
+                vout,
+                satoshis: u64::from(satoshis),
+                address: address.to_string(),
+                block_height: confirmation_time.height,
+                block_time: confirmation_time.timestamp,
+                birthday_height,
+            }),
             OutboxEventPayload::PayoutSubmitted {
                 id,
                 wallet_id,
@@ -593,6 +726,40 @@ impl From<OutboxEvent<Augmentation>> for proto::BriaEvent {
                 }),
                 proportional_fee_sats: u64::from(proportional_fee),
             }),
+            OutboxEventPayload::PayoutReplaced {
+                id,
+                tx_id,
+                vout,
+                wallet_id,
+                payout_queue_id,
+                batch_id,
+                replaced_batch_id,
+                replaced_tx_id,
+                satoshis,
+                destination,
+                ..
+            } => proto::bria_event::Payload::PayoutReplaced(proto::PayoutReplaced {
+                id: id.to_string(),
+                tx_id: tx_id.to_string(),
+                vout,
+                wallet_id: wallet_id.to_string(),
+                payout_queue_id: payout_queue_id.to_string(),
+                satoshis: u64::from(satoshis),
+                destination: Some(match destination {
+                    PayoutDestination::OnchainAddress { value: destination } => {
+                        proto::payout_replaced::Destination::OnchainAddress(destination.to_string())
+                    }
+                    PayoutDestination::Wallet { id, address } => {
+                        proto::payout_replaced::Destination::Wallet(proto::BriaWalletDestination {
+                            wallet_id: id.to_string(),
+                            address: address.to_string(),
+                        })
+                    }
+                }),
+                replaced_tx_id: replaced_tx_id.to_string(),
+                batch_id: batch_id.to_string(),
+                replaced_batch_id: replaced_batch_id.to_string(),
+            }),
             OutboxEventPayload::PayoutSettled {
                 id,
                 tx_id,
@@ -652,7 +819,10 @@ impl From<AddressAugmentation> for proto::WalletAddress {
 
 impl From<ApplicationError> for tonic::Status {
     fn from(err: ApplicationError) -> Self {
-        use crate::{address::error::*, payout::error::*, profile::error::*, wallet::error::*};
+        use crate::{
+            address::error::*, batch::error::*, payout::error::*, payout_queue::error::*,
+            profile::error::*, utxo::error::*, wallet::error::*,
+        };
 
         match err {
             ApplicationError::ProfileError(ProfileError::ProfileKeyNotFound) => {
@@ -670,6 +840,9 @@ impl From<ApplicationError> for tonic::Status {
             ApplicationError::PayoutQueueError(err) if err.was_not_found() => {
                 tonic::Status::not_found(err.to_string())
             }
+            ApplicationError::PayoutQueueError(
+                PayoutQueueError::InvalidFeeRate(_) | PayoutQueueError::MinFeeRateAboveMax(_, _),
+            ) => tonic::Status::invalid_argument(err.to_string()),
             ApplicationError::ProfileError(err) if err.was_not_found() => {
                 tonic::Status::not_found(err.to_string())
             }
@@ -685,6 +858,9 @@ impl From<ApplicationError> for tonic::Status {
             ApplicationError::CouldNotParseIncomingUuid(_) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
+            ApplicationError::CouldNotParseIncomingCursor(_) => {
+                tonic::Status::invalid_argument(err.to_string())
+            }
             ApplicationError::DestinationBlocked(_) => {
                 tonic::Status::permission_denied(err.to_string())
             }
@@ -700,12 +876,27 @@ impl From<ApplicationError> for tonic::Status {
             ApplicationError::SigningSessionNotFoundForXPubFingerprint(_) => {
                 tonic::Status::not_found(err.to_string())
             }
+            ApplicationError::SigningSessionError(err) if err.was_not_found() => {
+                tonic::Status::not_found(err.to_string())
+            }
+            ApplicationError::SigningSessionError(
+                SigningSessionError::UnknownSigningSessionState(_),
+            ) => tonic::Status::invalid_argument(err.to_string()),
             ApplicationError::WalletError(WalletError::PsbtDoesNotHaveValidSignatures) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
             ApplicationError::WalletError(WalletError::UnsignedTxnMismatch) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
+            ApplicationError::WalletError(WalletError::NoRecoveryPath) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::WalletError(WalletError::NoMaturedRecoveryUtxos) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::NoPendingSigningSessionForTx(_) => {
+                tonic::Status::not_found(err.to_string())
+            }
             ApplicationError::CouldNotParseIncomingPsbt(_) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
@@ -715,9 +906,36 @@ impl From<ApplicationError> for tonic::Status {
             ApplicationError::PayoutError(PayoutError::PayoutAlreadyCancelled) => {
                 tonic::Status::failed_precondition(err.to_string())
             }
+            ApplicationError::BatchError(BatchError::BatchCannotBeCancelled(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::BatchError(BatchError::BatchCancelled(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
             ApplicationError::CouldNotParseAddress(_) => {
                 tonic::Status::invalid_argument(err.to_string())
             }
+            ApplicationError::BatchError(BatchError::BatchIdNotFound(_)) => {
+                tonic::Status::not_found(err.to_string())
+            }
+            ApplicationError::BatchError(BatchError::BatchAlreadySuperseded(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::BatchError(BatchError::BatchNotBroadcast(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::BatchError(BatchError::BatchDoesNotSignalRbf(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::BatchError(BatchError::InsufficientChangeForFeeBump(_)) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::UtxoError(UtxoError::ChangeAlreadySpent) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
+            ApplicationError::UtxoError(UtxoError::UtxoAlreadySettledError) => {
+                tonic::Status::failed_precondition(err.to_string())
+            }
             _ => tonic::Status::internal(err.to_string()),
         }
     }
@@ -232,6 +232,61 @@ impl BriaService for Bria {
         .await
     }
 
+    #[instrument(name = "bria.export_unsigned_psbts", skip_all, fields(error, error.level, error.message), err)]
+    async fn export_unsigned_psbts(
+        &self,
+        request: Request<ExportUnsignedPsbtsRequest>,
+    ) -> Result<Response<ExportUnsignedPsbtsResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let ExportUnsignedPsbtsRequest { xpub_ref } = request.into_inner();
+            let sessions = self
+                .app
+                .list_pending_signing_sessions(&profile, xpub_ref)
+                .await?;
+            Ok(Response::new(ExportUnsignedPsbtsResponse {
+                psbts: sessions
+                    .into_iter()
+                    .map(proto::UnsignedPsbt::from)
+                    .collect(),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "bria.submit_signed_psbts", skip_all, fields(error, error.level, error.message), err)]
+    async fn submit_signed_psbts(
+        &self,
+        request: Request<SubmitSignedPsbtsRequest>,
+    ) -> Result<Response<SubmitSignedPsbtsResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let SubmitSignedPsbtsRequest {
+                xpub_ref,
+                signed_psbts,
+            } = request.into_inner();
+            let signed_psbts = signed_psbts
+                .into_iter()
+                .map(|psbt| {
+                    psbt.parse::<bitcoin::psbt::PartiallySignedTransaction>()
+                        .map_err(ApplicationError::CouldNotParseIncomingPsbt)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let batch_ids = self
+                .app
+                .submit_signed_psbts(&profile, xpub_ref, signed_psbts)
+                .await?;
+            Ok(Response::new(SubmitSignedPsbtsResponse {
+                batch_ids: batch_ids.into_iter().map(|id| id.to_string()).collect(),
+            }))
+        })
+        .await
+    }
+
     #[instrument(name = "bria.create_wallet", skip_all, fields(error, error.level, error.message), err)]
     async fn create_wallet(
         &self,
@@ -245,6 +300,7 @@ impl BriaService for Bria {
             let CreateWalletRequest {
                 name,
                 keychain_config,
+                birthday_height,
             } = request.into_inner();
             let (id, xpub_fingerprints) = match keychain_config {
                 Some(KeychainConfig {
@@ -255,7 +311,7 @@ impl BriaService for Bria {
                         })),
                 }) => {
                     self.app
-                        .create_wpkh_wallet(&profile, name, xpub, derivation_path)
+                        .create_wpkh_wallet(&profile, name, xpub, derivation_path, birthday_height)
                         .await?
                 }
                 Some(KeychainConfig {
@@ -266,7 +322,7 @@ impl BriaService for Bria {
                         })),
                 }) => {
                     self.app
-                        .create_descriptors_wallet(&profile, name, external, internal)
+                        .create_descriptors_wallet(&profile, name, external, internal, birthday_height)
                         .await?
                 }
                 Some(KeychainConfig {
@@ -277,7 +333,50 @@ impl BriaService for Bria {
                                 threshold,
                             })),
                 }) => {
-                    self.app.create_sorted_multisig_wallet(&profile, name, xpubs, threshold).await?
+                    self.app.create_sorted_multisig_wallet(&profile, name, xpubs, threshold, birthday_height).await?
+                }
+                Some(KeychainConfig {
+                    config:
+                        Some(keychain_config::Config::TaprootMultisig(
+                            keychain_config::TaprootMultisig {
+                                xpubs,
+                                threshold,
+                            })),
+                }) => {
+                    self.app.create_taproot_multisig_wallet(&profile, name, xpubs, threshold, birthday_height).await?
+                }
+                Some(KeychainConfig {
+                    config:
+                        Some(keychain_config::Config::SortedMultisigWithRecovery(
+                            keychain_config::SortedMultisigWithRecovery {
+                                xpubs,
+                                threshold,
+                                recovery_xpub,
+                                recovery_after_blocks,
+                            })),
+                }) => {
+                    self.app
+                        .create_sorted_multisig_with_recovery_wallet(
+                            &profile,
+                            name,
+                            xpubs,
+                            threshold,
+                            recovery_xpub,
+                            recovery_after_blocks,
+                            birthday_height,
+                        )
+                        .await?
+                }
+                Some(KeychainConfig {
+                    config:
+                        Some(keychain_config::Config::Taproot(keychain_config::Taproot {
+                            xpub,
+                            derivation_path,
+                        })),
+                }) => {
+                    self.app
+                        .create_taproot_wallet(&profile, name, xpub, derivation_path, birthday_height)
+                        .await?
                 }
                 _ => {
                     return Err(Status::invalid_argument("invalid keychain config"));
@@ -332,6 +431,27 @@ impl BriaService for Bria {
         .await
     }
 
+    #[instrument(name = "bria.get_fee_estimator_health", skip_all, fields(error, error.level, error.message), err)]
+    async fn get_fee_estimator_health(
+        &self,
+        request: Request<GetFeeEstimatorHealthRequest>,
+    ) -> Result<Response<GetFeeEstimatorHealthResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            self.app.authenticate(key).await?;
+            let estimators = self.app.fee_estimator_health();
+            Ok(Response::new(GetFeeEstimatorHealthResponse {
+                estimators: estimators
+                    .into_iter()
+                    .map(proto::FeeEstimatorHealth::from)
+                    .collect(),
+            }))
+        })
+        .await
+    }
+
     #[instrument(name = "bria.new_address", skip_all, fields(error, error.level, error.message), err)]
     async fn new_address(
         &self,
@@ -772,6 +892,29 @@ impl BriaService for Bria {
         .await
     }
 
+    #[instrument(name = "bria.rescan_wallet", skip_all, fields(error, error.level, error.message), err)]
+    async fn rescan_wallet(
+        &self,
+        request: Request<RescanWalletRequest>,
+    ) -> Result<Response<RescanWalletResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let RescanWalletRequest {
+                wallet_name,
+                birthday_height,
+                gap_limit,
+            } = request.into_inner();
+            self.app
+                .rescan_wallet(&profile, wallet_name, birthday_height, gap_limit)
+                .await?;
+            Ok(Response::new(RescanWalletResponse {}))
+        })
+        .await
+    }
+
     #[instrument(name = "bria.list_payout_queues", skip_all, fields(error, error.level, error.message), err)]
     async fn list_payout_queues(
         &self,
@@ -862,6 +1005,13 @@ impl BriaService for Bria {
                 payout_queue_id: batch.payout_queue_id.to_string(),
                 tx_id: batch.bitcoin_tx_id.to_string(),
                 unsigned_psbt: batch.unsigned_psbt.to_string(),
+                replaces_batch_id: batch.replaces_batch_id.map(|id| id.to_string()),
+                superseded_by_batch_id: batch.superseded_by_batch_id.map(|id| id.to_string()),
+                broadcast_results: batch
+                    .broadcast_results
+                    .into_iter()
+                    .map(proto::BroadcastResult::from)
+                    .collect(),
                 wallet_summaries,
                 signing_sessions: sessions
                     .map(|sessions| {
@@ -877,6 +1027,175 @@ impl BriaService for Bria {
         .await
     }
 
+    #[instrument(name = "bria.bump_batch_fee", skip_all, fields(error, error.level, error.message), err)]
+    async fn bump_batch_fee(
+        &self,
+        request: Request<BumpBatchFeeRequest>,
+    ) -> Result<Response<BumpBatchFeeResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let BumpBatchFeeRequest {
+                batch_id,
+                sats_per_vbyte,
+            } = request.into_inner();
+
+            let (batch_id, tx_id) = self
+                .app
+                .bump_batch_fee(
+                    &profile,
+                    batch_id
+                        .parse()
+                        .map_err(ApplicationError::CouldNotParseIncomingUuid)?,
+                    sats_per_vbyte,
+                )
+                .await?;
+            Ok(Response::new(BumpBatchFeeResponse {
+                batch_id: batch_id.to_string(),
+                tx_id: tx_id.to_string(),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "bria.sweep_with_recovery_key", skip_all, fields(error, error.level, error.message), err)]
+    async fn sweep_with_recovery_key(
+        &self,
+        request: Request<SweepWithRecoveryKeyRequest>,
+    ) -> Result<Response<SweepWithRecoveryKeyResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let SweepWithRecoveryKeyRequest {
+                wallet_name,
+                destination,
+                sats_per_vbyte,
+            } = request.into_inner();
+
+            let psbts = self
+                .app
+                .sweep_with_recovery_key(&profile, wallet_name, destination, sats_per_vbyte)
+                .await?;
+            Ok(Response::new(SweepWithRecoveryKeyResponse {
+                sweeps: psbts
+                    .into_iter()
+                    .map(|psbt| proto::RecoverySweep {
+                        tx_id: psbt.unsigned_tx.txid().to_string(),
+                        unsigned_psbt: psbt.to_string(),
+                    })
+                    .collect(),
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "bria.release_recovery_sweep", skip_all, fields(error, error.level, error.message), err)]
+    async fn release_recovery_sweep(
+        &self,
+        request: Request<ReleaseRecoverySweepRequest>,
+    ) -> Result<Response<ReleaseRecoverySweepResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let ReleaseRecoverySweepRequest { wallet_name } = request.into_inner();
+
+            let released_utxos = self
+                .app
+                .release_recovery_sweep(&profile, wallet_name)
+                .await?;
+            Ok(Response::new(ReleaseRecoverySweepResponse {
+                released_utxos,
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "bria.list_signing_sessions", skip_all, fields(error, error.level, error.message), err)]
+    async fn list_signing_sessions(
+        &self,
+        request: Request<ListSigningSessionsRequest>,
+    ) -> Result<Response<ListSigningSessionsResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let ListSigningSessionsRequest {
+                batch_id,
+                xpub_ref,
+                state,
+                after,
+                limit,
+            } = request.into_inner();
+
+            let query = es_entity::PaginatedQueryArgs {
+                first: limit.map(|limit| limit as usize).unwrap_or(100),
+                after: after
+                    .map(crate::signing_session::signing_session_cursor::SigningSessionsByCreatedAtCursor::try_from)
+                    .transpose()?,
+            };
+            let ret = self
+                .app
+                .list_signing_sessions(
+                    &profile,
+                    batch_id
+                        .map(|id| id.parse())
+                        .transpose()
+                        .map_err(ApplicationError::CouldNotParseIncomingUuid)?,
+                    xpub_ref,
+                    state
+                        .map(|state| state.parse::<crate::signing_session::SigningSessionState>())
+                        .transpose()
+                        .map_err(ApplicationError::from)?,
+                    query,
+                )
+                .await?;
+            Ok(Response::new(ListSigningSessionsResponse {
+                sessions: ret
+                    .entities
+                    .into_iter()
+                    .map(proto::SigningSession::from)
+                    .collect(),
+                end_cursor: ret.end_cursor.map(String::from),
+                has_next_page: ret.has_next_page,
+            }))
+        })
+        .await
+    }
+
+    #[instrument(name = "bria.get_signing_session", skip_all, fields(error, error.level, error.message), err)]
+    async fn get_signing_session(
+        &self,
+        request: Request<GetSigningSessionRequest>,
+    ) -> Result<Response<GetSigningSessionResponse>, Status> {
+        crate::tracing::record_error(|| async move {
+            extract_tracing(&request);
+
+            let key = extract_api_token(&request)?;
+            let profile = self.app.authenticate(key).await?;
+            let GetSigningSessionRequest { id } = request.into_inner();
+
+            let session = self
+                .app
+                .get_signing_session(
+                    &profile,
+                    id.parse()
+                        .map_err(ApplicationError::CouldNotParseIncomingUuid)?,
+                )
+                .await?;
+            Ok(Response::new(GetSigningSessionResponse {
+                session: Some(proto::SigningSession::from(session)),
+            }))
+        })
+        .await
+    }
+
     type SubscribeAllStream = std::pin::Pin<
         Box<dyn futures::Stream<Item = Result<BriaEvent, Status>> + Send + Sync + 'static>,
     >;
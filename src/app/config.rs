@@ -2,6 +2,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashSet;
 
 use crate::{
+    bdk::chain_source::ChainSourceConfig,
     fees::FeesConfig,
     job::JobsConfig,
     primitives::{bitcoin::Network, Address, PayoutDestination},
@@ -35,6 +36,15 @@ pub struct BlockchainConfig {
     pub network: Network,
     #[serde(default = "default_electrum_url")]
     pub electrum_url: String,
+    #[serde(default)]
+    pub chain_source: ChainSourceConfig,
+    /// Only sync keychains the electrum chain source reported activity for
+    /// instead of polling every wallet
+    #[serde(default)]
+    pub incremental_sync: bool,
+    /// Additional backends that signed batches are broadcast through
+    #[serde(default)]
+    pub broadcast_sources: Vec<ChainSourceConfig>,
 }
 
 impl Default for BlockchainConfig {
@@ -42,6 +52,9 @@ impl Default for BlockchainConfig {
         Self {
             network: default_network(),
             electrum_url: default_electrum_url(),
+            chain_source: ChainSourceConfig::default(),
+            incremental_sync: false,
+            broadcast_sources: Vec::new(),
         }
     }
 }
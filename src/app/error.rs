@@ -63,6 +63,8 @@ pub enum ApplicationError {
     CouldNotParseIncomingMetadata(serde_json::Error),
     #[error("CouldNotParseIncomingUuid: {0}")]
     CouldNotParseIncomingUuid(uuid::Error),
+    #[error("CouldNotParseIncomingCursor: {0}")]
+    CouldNotParseIncomingCursor(String),
     #[error("DestinationBlocked - sending to '{0}' is prohibited")]
     DestinationBlocked(PayoutDestination),
     #[error("DestinationNotAllowed - profile is not allowed to send to '{0}'")]
@@ -73,6 +75,8 @@ pub enum ApplicationError {
     SigningSessionNotFoundForBatchId(crate::primitives::BatchId),
     #[error("Signing Session not found for xpub fingerprint: {0}")]
     SigningSessionNotFoundForXPubFingerprint(crate::primitives::XPubFingerprint),
+    #[error("No pending signing session found for tx: {0}")]
+    NoPendingSigningSessionForTx(bitcoin::Txid),
     #[error("Could not parse incoming psbt: {0}")]
     CouldNotParseIncomingPsbt(bitcoin::psbt::PsbtParseError),
     #[error("Hex decode error: {0}")]
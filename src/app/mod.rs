@@ -12,8 +12,9 @@ use error::*;
 use crate::{
     account::balance::AccountBalanceSummary,
     address::*,
-    batch::*,
+    batch::{error::BatchError, *},
     batch_inclusion::*,
+    bdk::pg::KeychainRescans,
     descriptor::*,
     fees::{self, *},
     job,
@@ -25,7 +26,7 @@ use crate::{
     profile::*,
     signing_session::*,
     utxo::*,
-    wallet::{balance::*, *},
+    wallet::{balance::*, error::WalletError, *},
     xpub::*,
 };
 
@@ -87,6 +88,7 @@ impl App {
         )
         .await?;
         Self::spawn_sync_all_wallets(pool.clone(), config.jobs.sync_all_wallets_delay).await?;
+        Self::spawn_sync_on_chain_events(pool.clone(), wallets.clone(), &config.blockchain).await?;
         Self::spawn_process_all_payout_queues(
             pool.clone(),
             config.jobs.process_all_payout_queues_delay,
@@ -97,6 +99,8 @@ impl App {
             config.jobs.respawn_all_outbox_handlers_delay,
         )
         .await?;
+        Self::spawn_rebroadcast_batches(pool.clone(), config.jobs.rebroadcast_batches_delay)
+            .await?;
         let app = Self {
             outbox,
             profiles: Profiles::new(&pool),
@@ -123,6 +127,11 @@ impl App {
         Ok(app)
     }
 
+    /// Success and failure counts of the configured fee estimators since startup.
+    pub fn fee_estimator_health(&self) -> Vec<FeeEstimatorHealth> {
+        self.fees_client.estimator_health()
+    }
+
     pub fn network(&self) -> bitcoin::Network {
         self.config.blockchain.network
     }
@@ -288,14 +297,94 @@ impl App {
                     .expect("ref should always parse"),
             )
             .await?;
+        let sessions = self
+            .sign_session_with_psbt(profile, batch_id, &xpub, signed_psbt)
+            .await?;
+
+        let mut op = self.signing_sessions.begin_op().await?;
+        self.signing_sessions
+            .update_sessions(&mut op, &sessions)
+            .await?;
+        job::spawn_all_batch_signings(op.into(), std::iter::once((profile.account_id, batch_id)))
+            .await?;
+        Ok(())
+    }
+
+    /// Validates every psbt before persisting any of them so that either all
+    /// signing sessions are updated or none are. A batch that is covered by more
+    /// than one psbt is only submitted once.
+    #[instrument(name = "app.submit_signed_psbts", skip(self, signed_psbts), err)]
+    pub async fn submit_signed_psbts(
+        &self,
+        profile: &Profile,
+        xpub_ref: String,
+        signed_psbts: Vec<bitcoin::psbt::PartiallySignedTransaction>,
+    ) -> Result<Vec<BatchId>, ApplicationError> {
+        let xpub = self
+            .xpubs
+            .find_from_ref(
+                profile.account_id,
+                xpub_ref
+                    .parse::<XPubRef>()
+                    .expect("ref should always parse"),
+            )
+            .await?;
+        let pending_batches: HashMap<_, _> = self
+            .list_pending_signing_sessions(profile, xpub_ref)
+            .await?
+            .into_iter()
+            .map(|session| (session.unsigned_psbt.unsigned_tx.txid(), session.batch_id))
+            .collect();
+        let mut batch_ids = Vec::new();
+        let mut batch_sessions = Vec::new();
+        for signed_psbt in signed_psbts {
+            let tx_id = signed_psbt.unsigned_tx.txid();
+            let batch_id = *pending_batches
+                .get(&tx_id)
+                .ok_or(ApplicationError::NoPendingSigningSessionForTx(tx_id))?;
+            if batch_ids.contains(&batch_id) {
+                continue;
+            }
+            batch_sessions.push(
+                self.sign_session_with_psbt(profile, batch_id, &xpub, signed_psbt)
+                    .await?,
+            );
+            batch_ids.push(batch_id);
+        }
+
+        let mut op = self.signing_sessions.begin_op().await?;
+        for sessions in batch_sessions.iter() {
+            self.signing_sessions
+                .update_sessions(&mut op, sessions)
+                .await?;
+        }
+        job::spawn_all_batch_signings(
+            op.into(),
+            batch_ids
+                .iter()
+                .map(|batch_id| (profile.account_id, *batch_id)),
+        )
+        .await?;
+        Ok(batch_ids)
+    }
+
+    async fn sign_session_with_psbt(
+        &self,
+        profile: &Profile,
+        batch_id: BatchId,
+        xpub: &AccountXPub,
+        signed_psbt: bitcoin::psbt::PartiallySignedTransaction,
+    ) -> Result<HashMap<XPubFingerprint, SigningSession>, ApplicationError> {
         let xpub_fingerprint = xpub.fingerprint();
-        let xpub = xpub.value;
-        let unsigned_psbt = self
+        let batch = self
             .batches
             .find_by_id(profile.account_id, batch_id)
-            .await?
-            .unsigned_psbt;
-        psbt_validator::validate_psbt(&signed_psbt, xpub, &unsigned_psbt)?;
+            .await?;
+        if batch.is_cancelled() {
+            return Err(BatchError::BatchCancelled(batch_id).into());
+        }
+        let unsigned_psbt = batch.unsigned_psbt;
+        psbt_validator::validate_psbt(&signed_psbt, xpub.value.clone(), &unsigned_psbt)?;
         let mut sessions = self
             .signing_sessions
             .list_for_batch(profile.account_id, batch_id)
@@ -305,15 +394,8 @@ impl App {
         let session = sessions.get_mut(&xpub_fingerprint).ok_or_else(|| {
             ApplicationError::SigningSessionNotFoundForXPubFingerprint(xpub_fingerprint)
         })?;
-
-        let mut op = self.signing_sessions.begin_op().await?;
         session.submit_externally_signed_psbt(signed_psbt);
-        self.signing_sessions
-            .update_sessions(&mut op, &sessions)
-            .await?;
-        job::spawn_all_batch_signings(op.into(), std::iter::once((profile.account_id, batch_id)))
-            .await?;
-        Ok(())
+        Ok(sessions)
     }
 
     #[instrument(name = "app.create_wpkh_wallet", skip(self), err)]
@@ -323,6 +405,7 @@ impl App {
         wallet_name: String,
         xpub: String,
         derivation: Option<String>,
+        birthday_height: Option<u32>,
     ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
         let keychain = if let Ok(xpub) = XPub::try_from((&xpub, derivation)) {
             KeychainConfig::wpkh(xpub)
@@ -338,7 +421,35 @@ impl App {
                     .value,
             )
         };
-        self.create_wallet(profile, wallet_name, keychain).await
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
+    }
+
+    #[instrument(name = "app.create_taproot_wallet", skip(self), err)]
+    pub async fn create_taproot_wallet(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+        xpub: String,
+        derivation: Option<String>,
+        birthday_height: Option<u32>,
+    ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
+        let keychain = if let Ok(xpub) = XPub::try_from((&xpub, derivation)) {
+            KeychainConfig::tr(xpub)
+        } else {
+            KeychainConfig::tr(
+                self.xpubs
+                    .find_from_ref(
+                        profile.account_id,
+                        xpub.parse::<XPubRef>()
+                            .expect("xpub_ref should always parse"),
+                    )
+                    .await?
+                    .value,
+            )
+        };
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
     }
 
     #[instrument(name = "app.create_descriptors_wallet", skip(self), err)]
@@ -348,9 +459,11 @@ impl App {
         wallet_name: String,
         external: String,
         internal: String,
+        birthday_height: Option<u32>,
     ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
         let keychain = KeychainConfig::try_from((external.as_ref(), internal.as_ref()))?;
-        self.create_wallet(profile, wallet_name, keychain).await
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
     }
 
     #[instrument(name = "app.create_sorted_multisig_wallet", skip(self), err)]
@@ -360,6 +473,7 @@ impl App {
         wallet_name: String,
         xpubs: Vec<String>,
         threshold: u32,
+        birthday_height: Option<u32>,
     ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
         let xpub_values: Vec<XPub> = futures::future::try_join_all(
             xpubs
@@ -376,7 +490,86 @@ impl App {
         .collect();
 
         let keychain = KeychainConfig::sorted_multisig(xpub_values, threshold);
-        self.create_wallet(profile, wallet_name, keychain).await
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
+    }
+
+    #[instrument(name = "app.create_taproot_multisig_wallet", skip(self), err)]
+    pub async fn create_taproot_multisig_wallet(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+        xpubs: Vec<String>,
+        threshold: u32,
+        birthday_height: Option<u32>,
+    ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
+        let xpub_values: Vec<XPub> = futures::future::try_join_all(
+            xpubs
+                .iter()
+                .map(|xpub| {
+                    xpub.parse::<XPubRef>()
+                        .expect("xpub_ref should always parse")
+                })
+                .map(|xpub_ref| self.xpubs.find_from_ref(profile.account_id, xpub_ref)),
+        )
+        .await?
+        .into_iter()
+        .map(|xpub| xpub.value)
+        .collect();
+
+        let keychain = KeychainConfig::taproot_multisig(xpub_values, threshold);
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
+    }
+
+    #[instrument(
+        name = "app.create_sorted_multisig_with_recovery_wallet",
+        skip(self),
+        err
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_sorted_multisig_with_recovery_wallet(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+        xpubs: Vec<String>,
+        threshold: u32,
+        recovery_xpub: String,
+        recovery_after_blocks: u32,
+        birthday_height: Option<u32>,
+    ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
+        let xpub_values: Vec<XPub> = futures::future::try_join_all(
+            xpubs
+                .iter()
+                .map(|xpub| {
+                    xpub.parse::<XPubRef>()
+                        .expect("xpub_ref should always parse")
+                })
+                .map(|xpub_ref| self.xpubs.find_from_ref(profile.account_id, xpub_ref)),
+        )
+        .await?
+        .into_iter()
+        .map(|xpub| xpub.value)
+        .collect();
+        let recovery_xpub = self
+            .xpubs
+            .find_from_ref(
+                profile.account_id,
+                recovery_xpub
+                    .parse::<XPubRef>()
+                    .expect("xpub_ref should always parse"),
+            )
+            .await?
+            .value;
+
+        let keychain = KeychainConfig::sorted_multisig_with_recovery(
+            xpub_values,
+            threshold,
+            recovery_xpub,
+            recovery_after_blocks,
+        );
+        self.create_wallet(profile, wallet_name, keychain, birthday_height)
+            .await
     }
 
     async fn create_wallet(
@@ -384,6 +577,7 @@ impl App {
         profile: &Profile,
         wallet_name: String,
         keychain: KeychainConfig,
+        birthday_height: Option<u32>,
     ) -> Result<(WalletId, Vec<XPubFingerprint>), ApplicationError> {
         let mut op = self.wallets.begin_op().await?;
         let xpubs = keychain.xpubs();
@@ -424,6 +618,7 @@ impl App {
             .name(wallet_name)
             .keychain(keychain.clone())
             .ledger_account_ids(wallet_ledger_accounts)
+            .birthday_height(birthday_height)
             .build()
             .expect("Couldn't build NewWallet");
         let wallet = self.wallets.create_in_op(&mut op, new_wallet).await?;
@@ -626,6 +821,7 @@ impl App {
             .name(payout_queue_name)
             .description(description);
         if let Some(config) = config {
+            config.validate()?;
             builder.config(config);
         }
         let payout_queue = builder.build().expect("Couldn't build NewPayoutQueue");
@@ -704,7 +900,9 @@ impl App {
 
         let queue_id = payout_queue.id;
         let tx_priority = payout_queue.config.tx_priority;
-        let fee_rate = self.fees_client.fee_rate(tx_priority).await?;
+        let fee_rate = payout_queue
+            .config
+            .bounded_fee_rate(self.fees_client.fee_rate(tx_priority).await?);
 
         let psbt = {
             job::process_payout_queue::construct_psbt(
@@ -713,8 +911,9 @@ impl App {
                 &unbatched_payouts,
                 &self.utxos,
                 &self.wallets,
-                payout_queue,
+                &payout_queue,
                 fee_rate,
+                HashMap::new(),
                 true,
             )
             .await?
@@ -897,6 +1096,11 @@ impl App {
             .payouts
             .find_by_id_for_cancellation(&mut op, profile.account_id, id)
             .await?;
+        if let Some(batch_id) = payout.batch_id {
+            self.cancel_batch_for_payout(&mut op, profile.account_id, batch_id, id)
+                .await?;
+            payout.batch_cancelled(batch_id);
+        }
         payout.cancel_payout(profile.id)?;
         self.payouts.update_in_op(&mut op, &mut payout).await?;
         self.ledger
@@ -905,6 +1109,49 @@ impl App {
         Ok(())
     }
 
+    async fn cancel_batch_for_payout(
+        &self,
+        op: &mut es_entity::DbOp<'_>,
+        account_id: AccountId,
+        batch_id: BatchId,
+        cancelled_payout_id: PayoutId,
+    ) -> Result<(), ApplicationError> {
+        let batch = self.batches.find_by_id(account_id, batch_id).await?;
+        self.batches.cancel_in_op(op, account_id, batch_id).await?;
+        if let Some(mut sessions) = self
+            .signing_sessions
+            .list_for_batch(account_id, batch_id)
+            .await?
+            .map(|s| s.xpub_sessions)
+        {
+            sessions
+                .values_mut()
+                .for_each(|session| session.batch_cancelled());
+            self.signing_sessions.update_sessions(op, &sessions).await?;
+        }
+        self.utxos
+            .release_batch_utxos(op.tx_mut(), batch_id)
+            .await?;
+        let remaining_payouts = self
+            .payouts
+            .list_for_batch(account_id, batch_id)
+            .await?
+            .into_values()
+            .flatten()
+            .filter(|p| p.id != cancelled_payout_id);
+        self.payouts
+            .return_to_queue(op, remaining_payouts, batch_id)
+            .await?;
+        job::spawn_batch_cancelled_accounting(
+            op.tx_mut(),
+            account_id,
+            batch_id,
+            batch.wallet_summaries.into_keys(),
+        )
+        .await?;
+        Ok(())
+    }
+
     #[instrument(name = "app.list_wallets", skip_all, err)]
     pub async fn list_wallets(&self, profile: &Profile) -> Result<Vec<Wallet>, ApplicationError> {
         Ok(self.wallets.list_for_account(profile.account_id).await?)
@@ -993,6 +1240,7 @@ impl App {
             payout_queue.update_description(desc)
         }
         if let Some(config) = new_config {
+            config.validate()?;
             payout_queue.update_config(config)
         }
         self.payout_queues.update(&mut payout_queue).await?;
@@ -1028,6 +1276,241 @@ impl App {
         Ok((batch, payouts, signing_sessions))
     }
 
+    #[instrument(name = "app.list_signing_sessions", skip(self), err)]
+    pub async fn list_signing_sessions(
+        &self,
+        profile: &Profile,
+        batch_id: Option<BatchId>,
+        xpub_ref: Option<String>,
+        state: Option<SigningSessionState>,
+        query: es_entity::PaginatedQueryArgs<
+            signing_session_cursor::SigningSessionsByCreatedAtCursor,
+        >,
+    ) -> Result<
+        es_entity::PaginatedQueryRet<
+            SigningSession,
+            signing_session_cursor::SigningSessionsByCreatedAtCursor,
+        >,
+        ApplicationError,
+    > {
+        let xpub_fingerprint = if let Some(xpub_ref) = xpub_ref {
+            Some(
+                self.xpubs
+                    .find_from_ref(
+                        profile.account_id,
+                        xpub_ref
+                            .parse::<XPubRef>()
+                            .expect("ref should always parse"),
+                    )
+                    .await?
+                    .fingerprint(),
+            )
+        } else {
+            None
+        };
+        let filter = SigningSessionsListFilter {
+            batch_id,
+            xpub_fingerprint,
+            state,
+        };
+        Ok(self
+            .signing_sessions
+            .list_for_account(profile.account_id, filter, query)
+            .await?)
+    }
+
+    #[instrument(name = "app.list_pending_signing_sessions", skip(self), err)]
+    pub async fn list_pending_signing_sessions(
+        &self,
+        profile: &Profile,
+        xpub_ref: String,
+    ) -> Result<Vec<SigningSession>, ApplicationError> {
+        let mut sessions = Vec::new();
+        let mut query = es_entity::PaginatedQueryArgs::default();
+        loop {
+            let ret = self
+                .list_signing_sessions(profile, None, Some(xpub_ref.clone()), None, query)
+                .await?;
+            sessions.extend(ret.entities.into_iter().filter(|session| {
+                !session.is_completed() && !session.is_no_longer_needed() && !session.is_cancelled()
+            }));
+            if !ret.has_next_page {
+                break;
+            }
+            query = es_entity::PaginatedQueryArgs {
+                after: ret.end_cursor,
+                ..Default::default()
+            };
+        }
+        Ok(sessions)
+    }
+
+    #[instrument(name = "app.get_signing_session", skip(self), err)]
+    pub async fn get_signing_session(
+        &self,
+        profile: &Profile,
+        id: SigningSessionId,
+    ) -> Result<SigningSession, ApplicationError> {
+        let session = self
+            .signing_sessions
+            .find_by_account_id_and_id(profile.account_id, id)
+            .await?;
+        Ok(session)
+    }
+
+    #[instrument(name = "app.bump_batch_fee", skip(self), err)]
+    pub async fn bump_batch_fee(
+        &self,
+        profile: &Profile,
+        batch_id: BatchId,
+        sats_per_vbyte: Option<f32>,
+    ) -> Result<(BatchId, bitcoin::Txid), ApplicationError> {
+        let batch = self
+            .batches
+            .find_by_id(profile.account_id, batch_id)
+            .await?;
+        let fee_rate = if let Some(sats_per_vbyte) = sats_per_vbyte {
+            bitcoin::FeeRate::from_sat_per_vb(sats_per_vbyte)
+        } else {
+            let payout_queue = self
+                .payout_queues
+                .find_by_account_id_and_id(profile.account_id, batch.payout_queue_id)
+                .await?;
+            payout_queue.config.bounded_fee_rate(
+                self.fees_client
+                    .fee_rate(payout_queue.config.tx_priority)
+                    .await?,
+            )
+        };
+        let new_batch = batch.replace_by_fee(fee_rate)?;
+        let new_batch_id = new_batch.id;
+        let tx_id = new_batch.tx_id;
+        job::replace_batch(
+            &self.pool,
+            &self.batches,
+            &self.payouts,
+            &self.utxos,
+            profile.account_id,
+            new_batch,
+            fee_rate,
+        )
+        .await?;
+        Ok((new_batch_id, tx_id))
+    }
+
+    #[instrument(name = "app.rescan_wallet", skip(self), err)]
+    pub async fn rescan_wallet(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+        birthday_height: Option<u32>,
+        gap_limit: Option<u32>,
+    ) -> Result<(), ApplicationError> {
+        let wallet = self
+            .wallets
+            .find_by_account_id_and_name(profile.account_id, wallet_name)
+            .await?;
+        let rescans = KeychainRescans::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        for keychain_id in wallet.keychain_ids() {
+            rescans
+                .request(
+                    &mut tx,
+                    profile.account_id,
+                    wallet.id,
+                    keychain_id,
+                    birthday_height,
+                    gap_limit,
+                )
+                .await?;
+        }
+        tx.commit().await?;
+        job::spawn_sync_wallet(
+            &self.pool,
+            job::SyncWalletData::new(profile.account_id, wallet.id),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "app.sweep_with_recovery_key", skip(self), err)]
+    pub async fn sweep_with_recovery_key(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+        destination: String,
+        sats_per_vbyte: Option<f32>,
+    ) -> Result<Vec<bitcoin::psbt::PartiallySignedTransaction>, ApplicationError> {
+        let wallet = self
+            .wallets
+            .find_by_account_id_and_name(profile.account_id, wallet_name)
+            .await?;
+        let keychain_wallets: HashMap<_, _> = wallet
+            .keychain_wallets(self.pool.clone())
+            .filter(|keychain_wallet| keychain_wallet.recovery_after_blocks().is_some())
+            .map(|keychain_wallet| (keychain_wallet.keychain_id, keychain_wallet))
+            .collect();
+        if keychain_wallets.is_empty() {
+            return Err(WalletError::NoRecoveryPath.into());
+        }
+        let destination = Address::try_from((destination, self.config.blockchain.network))?;
+        let current_height = crate::bdk::last_sync_time(&self.pool).await?;
+        let fee_rate = if let Some(sats_per_vbyte) = sats_per_vbyte {
+            bitcoin::FeeRate::from_sat_per_vb(sats_per_vbyte)
+        } else {
+            self.fees_client.fee_rate(TxPriority::NextBlock).await?
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let outpoints = self
+            .utxos
+            .reserve_matured_for_recovery_sweep(
+                &mut tx,
+                keychain_wallets
+                    .iter()
+                    .filter_map(|(id, keychain_wallet)| {
+                        Some((*id, keychain_wallet.recovery_after_blocks()?))
+                    })
+                    .collect(),
+                current_height,
+            )
+            .await?;
+        if outpoints.is_empty() {
+            return Err(WalletError::NoMaturedRecoveryUtxos.into());
+        }
+        // Every keychain has its own bdk wallet so each one is swept by a separate psbt
+        let mut psbts = Vec::new();
+        for (keychain_id, outpoints) in outpoints {
+            let psbt = keychain_wallets[&keychain_id]
+                .build_recovery_sweep(outpoints, destination.clone(), fee_rate)
+                .await?;
+            psbts.push(psbt);
+        }
+        tx.commit().await?;
+        Ok(psbts)
+    }
+
+    /// Makes the utxos reserved by [`Self::sweep_with_recovery_key`] selectable by batches
+    /// again, e.g. when the sweep was never signed or broadcast.
+    #[instrument(name = "app.release_recovery_sweep", skip(self), err)]
+    pub async fn release_recovery_sweep(
+        &self,
+        profile: &Profile,
+        wallet_name: String,
+    ) -> Result<u64, ApplicationError> {
+        let wallet = self
+            .wallets
+            .find_by_account_id_and_name(profile.account_id, wallet_name)
+            .await?;
+        let mut tx = self.pool.begin().await?;
+        let released = self
+            .utxos
+            .release_recovery_sweep(&mut tx, wallet.keychain_ids())
+            .await?;
+        tx.commit().await?;
+        Ok(released)
+    }
+
     #[instrument(name = "app.subscribe_all", skip(self), err)]
     pub async fn subscribe_all(
         &self,
@@ -1046,6 +1529,39 @@ impl App {
         Ok(res)
     }
 
+    #[instrument(
+        name = "app.spawn_sync_on_chain_events",
+        level = "trace",
+        skip_all,
+        err
+    )]
+    async fn spawn_sync_on_chain_events(
+        pool: sqlx::PgPool,
+        wallets: Wallets,
+        blockchain_cfg: &BlockchainConfig,
+    ) -> Result<(), ApplicationError> {
+        if let Some(notify) =
+            crate::bdk::electrum_watcher::spawn_electrum_watcher(pool.clone(), blockchain_cfg)
+        {
+            tokio::spawn(async move {
+                loop {
+                    notify.notified().await;
+                    let _ = job::spawn_sync_dirty_wallets(&pool).await;
+                }
+            });
+        } else if let Some(mut events) =
+            crate::bdk::zmq::spawn_chain_event_listeners(&blockchain_cfg.chain_source)
+        {
+            tokio::spawn(async move {
+                loop {
+                    events.notified().await;
+                    let _ = job::spawn_sync_wallets(&pool, &wallets).await;
+                }
+            });
+        }
+        Ok(())
+    }
+
     #[instrument(name = "app.spawn_sync_all_wallets", level = "trace", skip_all, err)]
     async fn spawn_sync_all_wallets(
         pool: sqlx::PgPool,
@@ -1103,4 +1619,19 @@ impl App {
         });
         Ok(())
     }
+
+    #[instrument(name = "app.spawn_rebroadcast_batches", level = "trace", skip_all, err)]
+    async fn spawn_rebroadcast_batches(
+        pool: sqlx::PgPool,
+        delay: std::time::Duration,
+    ) -> Result<(), ApplicationError> {
+        tokio::spawn(async move {
+            loop {
+                let _ =
+                    job::spawn_rebroadcast_batches(&pool, std::time::Duration::from_secs(1)).await;
+                tokio::time::sleep(delay).await;
+            }
+        });
+        Ok(())
+    }
 }
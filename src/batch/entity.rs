@@ -2,8 +2,13 @@ use derive_builder::Builder;
 
 use std::collections::HashMap;
 
+use super::error::BatchError;
 use crate::primitives::*;
 
+// BIP125 requires a replacement to pay for its own relay on top of the fees of the original
+const INCREMENTAL_RELAY_FEE_SATS_PER_VBYTE: u64 = 1;
+const DUST_LIMIT_SATS: u64 = 546;
+
 pub struct Batch {
     pub id: BatchId,
     pub account_id: AccountId,
@@ -12,6 +17,10 @@ pub struct Batch {
     pub wallet_summaries: HashMap<WalletId, WalletSummary>,
     pub unsigned_psbt: bitcoin::psbt::PartiallySignedTransaction,
     pub signed_tx: Option<bitcoin::Transaction>,
+    pub replaces_batch_id: Option<BatchId>,
+    pub superseded_by_batch_id: Option<BatchId>,
+    pub broadcast_results: Vec<BroadcastResult>,
+    pub cancelled_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl Batch {
@@ -20,6 +29,142 @@ impl Batch {
             .values()
             .all(|s| s.batch_created_ledger_tx_id.is_some())
     }
+
+    pub fn is_superseded(&self) -> bool {
+        self.superseded_by_batch_id.is_some()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled_at.is_some()
+    }
+
+    pub fn total_fee_sats(&self) -> Satoshis {
+        self.wallet_summaries
+            .values()
+            .map(|s| s.total_fee_sats)
+            .sum()
+    }
+
+    pub fn pays_less_than(&self, fee_rate: bitcoin::FeeRate) -> bool {
+        self.signed_tx
+            .as_ref()
+            .map(|tx| Satoshis::from(fee_rate.fee_vb(tx.vsize())) > self.total_fee_sats())
+            .unwrap_or(false)
+    }
+
+    pub fn replacement_fee_rate(&self, fee_rate: bitcoin::FeeRate) -> bitcoin::FeeRate {
+        let vbytes = match self.signed_tx.as_ref() {
+            Some(tx) => tx.vsize(),
+            None => return fee_rate,
+        };
+        let min_sats_per_vbyte = u64::from(self.total_fee_sats()) as f32 / vbytes as f32
+            + INCREMENTAL_RELAY_FEE_SATS_PER_VBYTE as f32;
+        if fee_rate.as_sat_per_vb() < min_sats_per_vbyte {
+            bitcoin::FeeRate::from_sat_per_vb(min_sats_per_vbyte)
+        } else {
+            fee_rate
+        }
+    }
+
+    pub fn is_replaceable_by(&self, tx: &bitcoin::Transaction) -> bool {
+        self.unsigned_psbt.unsigned_tx.input.iter().all(|input| {
+            tx.input
+                .iter()
+                .any(|new_input| new_input.previous_output == input.previous_output)
+        })
+    }
+
+    /// Batches broadcast before replacement support was added did not opt in to BIP125
+    /// and can only be replaced on nodes running with full-rbf.
+    pub fn signals_rbf(&self) -> bool {
+        self.signed_tx
+            .as_ref()
+            .map(|tx| tx.input.iter().any(|input| input.sequence.is_rbf()))
+            .unwrap_or(false)
+    }
+
+    pub fn replace_by_fee(&self, fee_rate: bitcoin::FeeRate) -> Result<NewBatch, BatchError> {
+        if self.is_superseded() {
+            return Err(BatchError::BatchAlreadySuperseded(self.id));
+        }
+        let signed_tx = self
+            .signed_tx
+            .as_ref()
+            .filter(|_| self.accounting_complete())
+            .ok_or(BatchError::BatchNotBroadcast(self.id))?;
+        if !self.signals_rbf() {
+            return Err(BatchError::BatchDoesNotSignalRbf(self.id));
+        }
+        let vbytes = signed_tx.vsize();
+        let current_fee = self.total_fee_sats();
+        let min_fee =
+            current_fee + Satoshis::from(INCREMENTAL_RELAY_FEE_SATS_PER_VBYTE * vbytes as u64);
+        let additional_fee = Satoshis::from(fee_rate.fee_vb(vbytes)).max(min_fee) - current_fee;
+
+        let mut psbt = self.unsigned_psbt.clone();
+        for input in psbt.unsigned_tx.input.iter_mut() {
+            input.sequence = bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME;
+        }
+
+        let mut wallet_ids: Vec<_> = self.wallet_summaries.keys().copied().collect();
+        wallet_ids.sort_by_key(|id| uuid::Uuid::from(*id));
+        let n_wallets = wallet_ids.len();
+        let mut fee_still_to_allocate = additional_fee;
+        let mut wallet_summaries = HashMap::new();
+        for (idx, wallet_id) in wallet_ids.into_iter().enumerate() {
+            let summary = &self.wallet_summaries[&wallet_id];
+            let wallet_share = if idx == n_wallets - 1 {
+                fee_still_to_allocate
+            } else if current_fee == Satoshis::ZERO {
+                Satoshis::ZERO
+            } else {
+                Satoshis::from(
+                    (additional_fee.into_inner() * summary.total_fee_sats.into_inner()
+                        / current_fee.into_inner())
+                    .round(),
+                )
+            };
+            fee_still_to_allocate -= wallet_share;
+
+            let change_outpoint = summary
+                .change_outpoint
+                .ok_or(BatchError::InsufficientChangeForFeeBump(wallet_id))?;
+            let change_sats = summary.change_sats - wallet_share;
+            if change_sats < Satoshis::from(DUST_LIMIT_SATS) {
+                return Err(BatchError::InsufficientChangeForFeeBump(wallet_id));
+            }
+            psbt.unsigned_tx.output[change_outpoint.vout as usize].value = u64::from(change_sats);
+
+            wallet_summaries.insert(
+                wallet_id,
+                WalletSummary {
+                    total_fee_sats: summary.total_fee_sats + wallet_share,
+                    change_sats,
+                    batch_created_ledger_tx_id: None,
+                    batch_broadcast_ledger_tx_id: None,
+                    ..summary.clone()
+                },
+            );
+        }
+
+        let tx_id = psbt.unsigned_tx.txid();
+        for summary in wallet_summaries.values_mut() {
+            if let Some(outpoint) = summary.change_outpoint.as_mut() {
+                outpoint.txid = tx_id;
+            }
+        }
+
+        Ok(NewBatch {
+            id: BatchId::new(),
+            account_id: self.account_id,
+            payout_queue_id: self.payout_queue_id,
+            tx_id,
+            total_fee_sats: current_fee + additional_fee,
+            unsigned_psbt: psbt,
+            wallet_summaries,
+            replaces_batch_id: Some(self.id),
+        })
+    }
 }
 
 #[derive(Builder, Clone)]
@@ -27,16 +172,35 @@ pub struct NewBatch {
     pub id: BatchId,
     pub(super) account_id: AccountId,
     pub(super) payout_queue_id: PayoutQueueId,
-    pub(super) tx_id: bitcoin::Txid,
+    pub tx_id: bitcoin::Txid,
     pub(super) total_fee_sats: Satoshis,
     pub(super) unsigned_psbt: bitcoin::psbt::PartiallySignedTransaction,
     pub(super) wallet_summaries: HashMap<WalletId, WalletSummary>,
+    #[builder(default)]
+    pub replaces_batch_id: Option<BatchId>,
 }
 
 impl NewBatch {
     pub fn builder() -> NewBatchBuilder {
         NewBatchBuilder::default()
     }
+
+    pub fn wallet_ids(&self) -> Vec<WalletId> {
+        self.wallet_summaries.keys().copied().collect()
+    }
+}
+
+/// Outcome of the latest broadcast of the signed tx through one chain source.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BroadcastResult {
+    pub chain_source: String,
+    pub error: Option<String>,
+}
+
+impl BroadcastResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -62,3 +226,175 @@ pub struct WalletSummary {
     pub batch_created_ledger_tx_id: Option<LedgerTransactionId>,
     pub batch_broadcast_ledger_tx_id: Option<LedgerTransactionId>,
 }
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::{absolute::LockTime, Witness};
+
+    use super::*;
+
+    fn batch_with_change(change_sats: u64, fee_sats: u64) -> Batch {
+        let wallet_id = WalletId::new();
+        let input = bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+                    .parse()
+                    .unwrap(),
+                vout: 0,
+            },
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        };
+        let unsigned_tx = bitcoin::Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![input],
+            output: vec![
+                bitcoin::TxOut {
+                    value: 50_000,
+                    script_pubkey: bitcoin::ScriptBuf::new(),
+                },
+                bitcoin::TxOut {
+                    value: change_sats,
+                    script_pubkey: bitcoin::ScriptBuf::new(),
+                },
+            ],
+        };
+        let tx_id = unsigned_tx.txid();
+        let summary = WalletSummary {
+            wallet_id,
+            current_keychain_id: KeychainId::new(),
+            signing_keychains: vec![],
+            total_in_sats: Satoshis::from(50_000 + change_sats + fee_sats),
+            total_spent_sats: Satoshis::from(50_000u64),
+            total_fee_sats: Satoshis::from(fee_sats),
+            cpfp_fee_sats: Satoshis::ZERO,
+            cpfp_details: HashMap::new(),
+            change_sats: Satoshis::from(change_sats),
+            change_address: None,
+            change_outpoint: Some(bitcoin::OutPoint {
+                txid: tx_id,
+                vout: 1,
+            }),
+            batch_created_ledger_tx_id: Some(LedgerTransactionId::new()),
+            batch_broadcast_ledger_tx_id: None,
+        };
+        Batch {
+            id: BatchId::new(),
+            account_id: AccountId::new(),
+            payout_queue_id: PayoutQueueId::new(),
+            bitcoin_tx_id: tx_id,
+            wallet_summaries: [(wallet_id, summary)].into_iter().collect(),
+            unsigned_psbt: bitcoin::psbt::PartiallySignedTransaction::from_unsigned_tx(
+                unsigned_tx.clone(),
+            )
+            .unwrap(),
+            signed_tx: Some(unsigned_tx),
+            replaces_batch_id: None,
+            superseded_by_batch_id: None,
+            broadcast_results: vec![],
+            cancelled_at: None,
+        }
+    }
+
+    #[test]
+    fn replace_by_fee_deducts_from_change() {
+        let batch = batch_with_change(100_000, 100);
+        let vbytes = batch.signed_tx.as_ref().unwrap().vsize() as u64;
+        let new_batch = batch
+            .replace_by_fee(bitcoin::FeeRate::from_sat_per_vb(10.0))
+            .unwrap();
+
+        assert_eq!(new_batch.replaces_batch_id, Some(batch.id));
+        assert_eq!(new_batch.total_fee_sats, Satoshis::from(10 * vbytes));
+        let tx = &new_batch.unsigned_psbt.unsigned_tx;
+        assert_eq!(tx.output[0].value, 50_000);
+        assert_eq!(tx.output[1].value, 100_000 - (10 * vbytes - 100));
+        assert_eq!(
+            tx.input[0].sequence,
+            bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME
+        );
+        assert_eq!(new_batch.tx_id, tx.txid());
+        let summary = new_batch.wallet_summaries.values().next().unwrap();
+        assert_eq!(summary.change_outpoint.unwrap().txid, new_batch.tx_id);
+        assert!(summary.batch_created_ledger_tx_id.is_none());
+    }
+
+    #[test]
+    fn replace_by_fee_pays_at_least_incremental_relay_fee() {
+        let batch = batch_with_change(100_000, 1_000);
+        let vbytes = batch.signed_tx.as_ref().unwrap().vsize() as u64;
+        let new_batch = batch
+            .replace_by_fee(bitcoin::FeeRate::from_sat_per_vb(1.0))
+            .unwrap();
+
+        assert_eq!(new_batch.total_fee_sats, Satoshis::from(1_000 + vbytes));
+    }
+
+    #[test]
+    fn replace_by_fee_errors_when_change_would_be_dust() {
+        let batch = batch_with_change(1_000, 100);
+        let result = batch.replace_by_fee(bitcoin::FeeRate::from_sat_per_vb(100.0));
+
+        assert!(matches!(
+            result,
+            Err(BatchError::InsufficientChangeForFeeBump(_))
+        ));
+    }
+
+    #[test]
+    fn pays_less_than() {
+        let batch = batch_with_change(100_000, 1_000);
+
+        assert!(batch.pays_less_than(bitcoin::FeeRate::from_sat_per_vb(100.0)));
+        assert!(!batch.pays_less_than(bitcoin::FeeRate::from_sat_per_vb(1.0)));
+    }
+
+    #[test]
+    fn replace_by_fee_errors_when_not_signaling_rbf() {
+        let mut batch = batch_with_change(100_000, 100);
+        for input in batch.signed_tx.as_mut().unwrap().input.iter_mut() {
+            input.sequence = bitcoin::Sequence::MAX;
+        }
+        let result = batch.replace_by_fee(bitcoin::FeeRate::from_sat_per_vb(10.0));
+
+        assert!(matches!(result, Err(BatchError::BatchDoesNotSignalRbf(_))));
+    }
+
+    #[test]
+    fn replace_by_fee_errors_when_superseded() {
+        let mut batch = batch_with_change(100_000, 100);
+        batch.superseded_by_batch_id = Some(BatchId::new());
+        let result = batch.replace_by_fee(bitcoin::FeeRate::from_sat_per_vb(10.0));
+
+        assert!(matches!(result, Err(BatchError::BatchAlreadySuperseded(_))));
+    }
+
+    #[test]
+    fn replacement_fee_rate_outbids_current_rate() {
+        let batch = batch_with_change(100_000, 1_000);
+        let vbytes = batch.signed_tx.as_ref().unwrap().vsize() as f32;
+        let current_rate = 1_000.0 / vbytes;
+
+        let fee_rate = batch.replacement_fee_rate(bitcoin::FeeRate::from_sat_per_vb(1.0));
+        assert_eq!(fee_rate.as_sat_per_vb(), current_rate + 1.0);
+
+        let fee_rate = batch.replacement_fee_rate(bitcoin::FeeRate::from_sat_per_vb(100.0));
+        assert_eq!(fee_rate.as_sat_per_vb(), 100.0);
+    }
+
+    #[test]
+    fn is_replaceable_by_requires_all_inputs() {
+        let batch = batch_with_change(100_000, 100);
+        let mut tx = batch.unsigned_psbt.unsigned_tx.clone();
+        tx.output.push(bitcoin::TxOut {
+            value: 10_000,
+            script_pubkey: bitcoin::ScriptBuf::new(),
+        });
+        assert!(batch.is_replaceable_by(&tx));
+
+        tx.input.clear();
+        assert!(!batch.is_replaceable_by(&tx));
+    }
+}
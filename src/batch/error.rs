@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::primitives::{BatchId, WalletId};
+
 #[derive(Debug, Error)]
 pub enum BatchError {
     #[error("BatchError - Could not find batch with id: {0}")]
@@ -10,6 +12,18 @@ pub enum BatchError {
     BitcoinConsensusEncodeError(#[from] crate::primitives::bitcoin::consensus::encode::Error),
     #[error("BatchError - Could not deserialize PSBT: {0}")]
     PsbtDeserializationError(#[from] crate::primitives::bitcoin::psbt::Error),
+    #[error("BatchError - Batch {0} has already been superseded")]
+    BatchAlreadySuperseded(BatchId),
+    #[error("BatchError - Batch {0} has not been broadcast yet")]
+    BatchNotBroadcast(BatchId),
+    #[error("BatchError - Batch {0} does not signal BIP125 replaceability")]
+    BatchDoesNotSignalRbf(BatchId),
+    #[error("BatchError - Batch {0} can no longer be cancelled")]
+    BatchCannotBeCancelled(BatchId),
+    #[error("BatchError - Batch {0} has been cancelled")]
+    BatchCancelled(BatchId),
+    #[error("BatchError - Change of wallet {0} is insufficient to cover the fee bump")]
+    InsufficientChangeForFeeBump(WalletId),
     #[error("BatchError - EsEntityError: {0}")]
     EsEntityError(es_entity::EsEntityError),
 }
@@ -13,6 +13,18 @@ pub struct BatchInfo {
     pub created_ledger_tx_id: LedgerTxId,
 }
 
+pub struct CancelledBatchInfo {
+    pub id: BatchId,
+    pub created_ledger_tx_id: LedgerTxId,
+}
+
+pub struct ReplacedBatchInfo {
+    pub id: BatchId,
+    pub payout_queue_id: PayoutQueueId,
+    pub created_ledger_tx_id: LedgerTxId,
+    pub broadcast_ledger_tx_id: Option<LedgerTxId>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Batches {
     pool: PgPool,
@@ -31,14 +43,15 @@ impl Batches {
     ) -> Result<BatchId, BatchError> {
         let serializied_psbt = batch.unsigned_psbt.serialize();
         sqlx::query!(
-            r#"INSERT INTO bria_batches (id, account_id, payout_queue_id, total_fee_sats, bitcoin_tx_id, unsigned_psbt)
-            VALUES ($1, $2, $3, $4, $5, $6)"#,
+            r#"INSERT INTO bria_batches (id, account_id, payout_queue_id, total_fee_sats, bitcoin_tx_id, unsigned_psbt, replaces_batch_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
             batch.id as BatchId,
             batch.account_id as AccountId,
             batch.payout_queue_id as PayoutQueueId,
             i64::from(batch.total_fee_sats),
             batch.tx_id.as_ref() as &[u8],
             serializied_psbt.as_slice() as &[u8],
+            batch.replaces_batch_id as Option<BatchId>,
         ).execute(op.as_executor()).await?;
 
         let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
@@ -88,6 +101,7 @@ impl Batches {
         let rows = sqlx::query!(
             r#"SELECT
                     payout_queue_id, unsigned_psbt, signed_tx, bitcoin_tx_id, s.batch_id,
+                    replaces_batch_id, superseded_by_batch_id, broadcast_results, cancelled_at,
                     s.wallet_id, s.current_keychain_id, s.signing_keychains, total_in_sats,
                     total_spent_sats, change_sats, change_address, change_vout, s.total_fee_sats,
                     cpfp_fee_sats, cpfp_details, batch_created_ledger_tx_id, batch_broadcast_ledger_tx_id
@@ -114,6 +128,11 @@ impl Batches {
             .map(|tx| bitcoin::consensus::deserialize(tx))
             .transpose()?;
         let payout_queue_id = PayoutQueueId::from(rows[0].payout_queue_id);
+        let replaces_batch_id = rows[0].replaces_batch_id.map(BatchId::from);
+        let superseded_by_batch_id = rows[0].superseded_by_batch_id.map(BatchId::from);
+        let broadcast_results = serde_json::from_value(rows[0].broadcast_results.clone())
+            .expect("parse broadcast results");
+        let cancelled_at = rows[0].cancelled_at;
 
         for row in rows.into_iter() {
             let wallet_id = WalletId::from(row.wallet_id);
@@ -160,26 +179,209 @@ impl Batches {
             unsigned_psbt,
             signed_tx,
             wallet_summaries,
+            replaces_batch_id,
+            superseded_by_batch_id,
+            broadcast_results,
+            cancelled_at,
         })
     }
 
+    #[instrument(name = "batches.supersede_in_op", skip(self, op))]
+    pub async fn supersede_in_op(
+        &self,
+        op: &mut impl es_entity::AtomicOperation,
+        batch_id: BatchId,
+        superseded_by: BatchId,
+    ) -> Result<(), BatchError> {
+        let rows_affected = sqlx::query!(
+            r#"UPDATE bria_batches SET superseded_by_batch_id = $1
+               WHERE id = $2 AND superseded_by_batch_id IS NULL"#,
+            superseded_by as BatchId,
+            batch_id as BatchId,
+        )
+        .execute(op.as_executor())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(BatchError::BatchAlreadySuperseded(batch_id));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "batches.cancel_in_op", skip(self, op))]
+    pub async fn cancel_in_op(
+        &self,
+        op: &mut impl es_entity::AtomicOperation,
+        account_id: AccountId,
+        batch_id: BatchId,
+    ) -> Result<(), BatchError> {
+        // Replacements can't be undone without resurrecting the batch they superseded
+        let rows_affected = sqlx::query!(
+            r#"UPDATE bria_batches SET cancelled_at = NOW()
+               WHERE id = $1 AND account_id = $2
+                 AND signed_tx IS NULL
+                 AND cancelled_at IS NULL
+                 AND superseded_by_batch_id IS NULL
+                 AND replaces_batch_id IS NULL"#,
+            batch_id as BatchId,
+            account_id as AccountId,
+        )
+        .execute(op.as_executor())
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(BatchError::BatchCannotBeCancelled(batch_id));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "batches.find_rbf_candidates", skip(self))]
+    pub async fn find_rbf_candidates(
+        &self,
+        account_id: AccountId,
+        payout_queue_id: PayoutQueueId,
+        older_than: chrono::DateTime<chrono::Utc>,
+        older_than_block: u32,
+    ) -> Result<Vec<BatchId>, BatchError> {
+        let rows = sqlx::query!(
+            r#"SELECT b.id
+               FROM bria_batches b
+               WHERE b.account_id = $1
+                 AND b.payout_queue_id = $2
+                 AND b.created_at < $3
+                 AND b.signed_tx IS NOT NULL
+                 AND b.superseded_by_batch_id IS NULL
+                 AND EXISTS (
+                   SELECT 1 FROM bria_utxos u
+                   WHERE u.origin_tx_batch_id = b.id
+                     AND u.detected_block_height < $4
+                     AND u.income_settled_ledger_tx_id IS NULL
+                 )
+                 AND NOT EXISTS (
+                   SELECT 1 FROM bria_utxos u
+                   WHERE u.spending_batch_id = b.id
+                     AND u.spend_settled_ledger_tx_id IS NOT NULL
+                 )
+               ORDER BY b.created_at"#,
+            account_id as AccountId,
+            payout_queue_id as PayoutQueueId,
+            older_than,
+            older_than_block as i32,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| BatchId::from(row.id)).collect())
+    }
+
+    #[instrument(name = "batches.find_unconfirmed_for_queue", skip(self))]
+    pub async fn find_unconfirmed_for_queue(
+        &self,
+        account_id: AccountId,
+        payout_queue_id: PayoutQueueId,
+    ) -> Result<Option<BatchId>, BatchError> {
+        let row = sqlx::query!(
+            r#"SELECT b.id
+               FROM (
+                 SELECT id, signed_tx
+                 FROM bria_batches
+                 WHERE account_id = $1
+                   AND payout_queue_id = $2
+                   AND superseded_by_batch_id IS NULL
+                 ORDER BY created_at DESC
+                 LIMIT 1
+               ) b
+               WHERE b.signed_tx IS NOT NULL
+                 AND NOT EXISTS (
+                   SELECT 1 FROM bria_utxos u
+                   WHERE u.spending_batch_id = b.id
+                     AND u.spend_settled_ledger_tx_id IS NOT NULL
+                 )
+                 AND NOT EXISTS (
+                   SELECT 1 FROM bria_utxos u
+                   WHERE u.origin_tx_batch_id = b.id
+                     AND (u.spending_batch_id IS NOT NULL OR u.income_settled_ledger_tx_id IS NOT NULL)
+                 )"#,
+            account_id as AccountId,
+            payout_queue_id as PayoutQueueId,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| BatchId::from(row.id)))
+    }
+
     #[instrument(name = "batches.set_signed_tx", skip(self))]
     pub async fn set_signed_tx(
         &self,
         batch_id: BatchId,
         bitcoin_tx: bitcoin::Transaction,
     ) -> Result<(), BatchError> {
-        sqlx::query!(
-            r#"UPDATE bria_batches SET signed_tx = $1 WHERE id = $2"#,
+        let rows_affected = sqlx::query!(
+            r#"UPDATE bria_batches SET signed_tx = $1 WHERE id = $2 AND cancelled_at IS NULL"#,
             bitcoin::consensus::encode::serialize(&bitcoin_tx),
             batch_id as BatchId,
         )
         .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if rows_affected == 0 {
+            return Err(BatchError::BatchCancelled(batch_id));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "batches.record_broadcast_results", skip(self))]
+    pub async fn record_broadcast_results(
+        &self,
+        batch_id: BatchId,
+        results: &[BroadcastResult],
+    ) -> Result<(), BatchError> {
+        sqlx::query!(
+            r#"UPDATE bria_batches SET broadcast_results = $1, last_broadcast_at = NOW() WHERE id = $2"#,
+            serde_json::to_value(results).expect("serialize broadcast results"),
+            batch_id as BatchId,
+        )
+        .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    #[instrument(name = "batches.find_rebroadcast_candidates", skip(self))]
+    pub async fn find_rebroadcast_candidates(
+        &self,
+        broadcast_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<(AccountId, BatchId)>, BatchError> {
+        let rows = sqlx::query!(
+            r#"SELECT b.account_id, b.id
+               FROM bria_batches b
+               WHERE b.signed_tx IS NOT NULL
+                 AND b.superseded_by_batch_id IS NULL
+                 AND b.last_broadcast_at < $1
+                 AND NOT EXISTS (
+                   SELECT 1 FROM bria_utxos u
+                   WHERE u.spending_batch_id = b.id
+                     AND u.spend_settled_ledger_tx_id IS NOT NULL
+                 )
+               ORDER BY b.created_at"#,
+            broadcast_before,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (AccountId::from(row.account_id), BatchId::from(row.id)))
+            .collect())
+    }
+
     #[instrument(name = "batches.set_batch_created_ledger_tx_id", skip(self))]
     pub async fn set_batch_created_ledger_tx_id(
         &self,
@@ -207,6 +409,89 @@ impl Batches {
         }
     }
 
+    #[instrument(name = "batches.set_batch_replaced_ledger_tx_id", skip(self))]
+    pub async fn set_batch_replaced_ledger_tx_id(
+        &self,
+        replaced_batch_id: BatchId,
+        wallet_id: WalletId,
+    ) -> Result<Option<(Transaction<'_, Postgres>, ReplacedBatchInfo, LedgerTxId)>, BatchError>
+    {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query!(
+            r#"SELECT b.payout_queue_id, s.batch_created_ledger_tx_id, s.batch_broadcast_ledger_tx_id
+               FROM bria_batch_wallet_summaries s
+               JOIN bria_batches b ON b.id = s.batch_id
+               WHERE s.batch_id = $1 AND s.wallet_id = $2 AND s.batch_replaced_ledger_tx_id IS NULL
+               FOR UPDATE OF s"#,
+            replaced_batch_id as BatchId,
+            wallet_id as WalletId,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let row = match row {
+            Some(row) if row.batch_created_ledger_tx_id.is_some() => row,
+            _ => return Ok(None),
+        };
+
+        let ledger_transaction_id = LedgerTxId::new();
+        sqlx::query!(
+            r#"UPDATE bria_batch_wallet_summaries
+               SET batch_replaced_ledger_tx_id = $1
+               WHERE batch_id = $2 AND wallet_id = $3"#,
+            ledger_transaction_id as LedgerTxId,
+            replaced_batch_id as BatchId,
+            wallet_id as WalletId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(Some((
+            tx,
+            ReplacedBatchInfo {
+                id: replaced_batch_id,
+                payout_queue_id: PayoutQueueId::from(row.payout_queue_id),
+                created_ledger_tx_id: LedgerTxId::from(row.batch_created_ledger_tx_id.unwrap()),
+                broadcast_ledger_tx_id: row.batch_broadcast_ledger_tx_id.map(LedgerTxId::from),
+            },
+            ledger_transaction_id,
+        )))
+    }
+
+    #[instrument(name = "batches.set_batch_cancelled_ledger_tx_id", skip(self))]
+    pub async fn set_batch_cancelled_ledger_tx_id(
+        &self,
+        batch_id: BatchId,
+        wallet_id: WalletId,
+    ) -> Result<Option<(Transaction<'_, Postgres>, CancelledBatchInfo, LedgerTxId)>, BatchError>
+    {
+        let mut tx = self.pool.begin().await?;
+        let ledger_transaction_id = LedgerTxId::new();
+        let row = sqlx::query!(
+            r#"UPDATE bria_batch_wallet_summaries
+               SET batch_cancelled_ledger_tx_id = $1
+               WHERE batch_id = $2 AND wallet_id = $3
+                 AND batch_created_ledger_tx_id IS NOT NULL
+                 AND batch_cancelled_ledger_tx_id IS NULL
+               RETURNING batch_created_ledger_tx_id as "batch_created_ledger_tx_id!""#,
+            ledger_transaction_id as LedgerTxId,
+            batch_id as BatchId,
+            wallet_id as WalletId,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        Ok(row.map(|row| {
+            (
+                tx,
+                CancelledBatchInfo {
+                    id: batch_id,
+                    created_ledger_tx_id: LedgerTxId::from(row.batch_created_ledger_tx_id),
+                },
+                ledger_transaction_id,
+            )
+        }))
+    }
+
     #[instrument(name = "batches.set_batch_broadcast_ledger_tx_id", skip(self))]
     pub async fn set_batch_broadcast_ledger_tx_id(
         &self,
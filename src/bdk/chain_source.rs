@@ -0,0 +1,476 @@
+use bdk::{
+    blockchain::{
+        electrum::ElectrumBlockchainConfig,
+        esplora::{EsploraBlockchain, EsploraBlockchainConfig},
+        rpc::{Auth, RpcBlockchain, RpcConfig, RpcSyncParams},
+        AnyBlockchain, Blockchain, ConfigurableBlockchain, ElectrumBlockchain, GetBlockHash,
+        GetHeight, Progress, WalletSync,
+    },
+    database::BatchDatabase,
+};
+use bitcoincore_rpc::RpcApi;
+use electrum_client::{Client, ConfigBuilder, ElectrumApi};
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+
+#[cfg(feature = "compact-filters")]
+use super::compact_filters::SharedCompactFilters;
+use super::{error::BdkError, esplora::*, pg::KeychainRescan};
+use crate::{
+    app::BlockchainConfig,
+    primitives::{bitcoin, KeychainId},
+};
+
+// bitcoind's error code for an unknown tx
+const RPC_INVALID_ADDRESS_OR_KEY: i32 = -5;
+// bitcoind's error code when broadcasting a tx that is already confirmed
+const RPC_VERIFY_ALREADY_IN_CHAIN: i32 = -27;
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainSourceConfig {
+    #[default]
+    Electrum,
+    Esplora(EsploraChainSourceConfig),
+    Bitcoind(BitcoindChainSourceConfig),
+    #[cfg(feature = "compact-filters")]
+    CompactFilters(CompactFiltersChainSourceConfig),
+}
+
+#[serde_with::serde_as]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BitcoindChainSourceConfig {
+    pub rpc_url: String,
+    pub rpc_user: String,
+    pub rpc_password: String,
+    /// zmqpubhashblock endpoint of the node, eg. tcp://127.0.0.1:28332
+    #[serde(default)]
+    pub zmq_block_endpoint: Option<String>,
+    /// zmqpubrawtx endpoint of the node, eg. tcp://127.0.0.1:28333
+    #[serde(default)]
+    pub zmq_tx_endpoint: Option<String>,
+    /// Minimum time between two wallet syncs triggered by zmq notifications. A busy
+    /// mempool announces many transactions per second which are synced together.
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_zmq_min_sync_interval")]
+    pub zmq_min_sync_interval: std::time::Duration,
+}
+
+fn default_zmq_min_sync_interval() -> std::time::Duration {
+    std::time::Duration::from_secs(5)
+}
+
+/// Light client mode that scans BIP158 compact block filters served by the
+/// given peers (they must run with `peerblockfilters=1`).
+/// Requires building with the `compact-filters` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactFiltersChainSourceConfig {
+    /// p2p addresses of the peers, eg. 127.0.0.1:18444
+    pub peers: Vec<String>,
+    /// Directory where the downloaded headers and filters are stored, one store per network
+    pub storage_dir: String,
+    /// Number of blocks to skip from the start of the chain
+    #[serde(default)]
+    pub skip_blocks: Option<usize>,
+}
+
+impl BitcoindChainSourceConfig {
+    fn client(&self) -> Result<bitcoincore_rpc::Client, BdkError> {
+        Ok(bitcoincore_rpc::Client::new(
+            &self.rpc_url,
+            bitcoincore_rpc::Auth::UserPass(self.rpc_user.clone(), self.rpc_password.clone()),
+        )?)
+    }
+
+    /// Lets bitcoind scan its watch-only wallet from the birthday block, `force`
+    /// rescans even if the wallet was imported before. Without a birthday a new wallet
+    /// is only scanned from the current tip (it can't have older history unless its
+    /// xpubs were used elsewhere, which requires a birthday) while a rescan starts at genesis.
+    fn sync_params(
+        &self,
+        birthday_height: Option<u32>,
+        gap_limit: Option<usize>,
+        force: bool,
+    ) -> Result<RpcSyncParams, BdkError> {
+        let defaults = RpcSyncParams::default();
+        let start_time = match (birthday_height, force) {
+            (Some(height), _) => {
+                let client = self.client()?;
+                let hash = client.get_block_hash(height as u64)?;
+                client.get_block_header(&hash)?.time as u64
+            }
+            (None, true) => defaults.start_time,
+            (None, false) => {
+                let client = self.client()?;
+                let hash = client.get_best_block_hash()?;
+                client.get_block_header(&hash)?.time as u64
+            }
+        };
+        Ok(RpcSyncParams {
+            start_script_count: gap_limit.unwrap_or(defaults.start_script_count),
+            start_time,
+            force_start_time: force,
+            ..defaults
+        })
+    }
+}
+
+impl ChainSourceConfig {
+    pub fn name(&self, electrum_url: &str) -> String {
+        match self {
+            ChainSourceConfig::Electrum => format!("electrum:{electrum_url}"),
+            ChainSourceConfig::Esplora(config) => format!("esplora:{}", config.url),
+            ChainSourceConfig::Bitcoind(config) => format!("bitcoind:{}", config.rpc_url),
+            #[cfg(feature = "compact-filters")]
+            ChainSourceConfig::CompactFilters(config) => {
+                format!("compact_filters:{}", config.peers.join(","))
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ChainSourceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainSourceConfig::Electrum => write!(f, "ChainSourceConfig::Electrum"),
+            ChainSourceConfig::Esplora(config) => {
+                write!(f, "ChainSourceConfig::Esplora(url={})", config.url)
+            }
+            ChainSourceConfig::Bitcoind(config) => {
+                write!(f, "ChainSourceConfig::Bitcoind(rpc_url={})", config.rpc_url)
+            }
+            #[cfg(feature = "compact-filters")]
+            ChainSourceConfig::CompactFilters(config) => {
+                write!(
+                    f,
+                    "ChainSourceConfig::CompactFilters(peers={:?})",
+                    config.peers
+                )
+            }
+        }
+    }
+}
+
+/// Blockchain backing the sync of a keychain.
+pub enum SyncBlockchain {
+    Any(AnyBlockchain),
+    #[cfg(feature = "compact-filters")]
+    CompactFilters(SharedCompactFilters),
+}
+
+impl WalletSync for SyncBlockchain {
+    fn wallet_setup<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        match self {
+            SyncBlockchain::Any(blockchain) => blockchain.wallet_setup(database, progress_update),
+            #[cfg(feature = "compact-filters")]
+            SyncBlockchain::CompactFilters(blockchain) => {
+                blockchain.wallet_setup(database, progress_update)
+            }
+        }
+    }
+
+    fn wallet_sync<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        match self {
+            SyncBlockchain::Any(blockchain) => blockchain.wallet_sync(database, progress_update),
+            #[cfg(feature = "compact-filters")]
+            SyncBlockchain::CompactFilters(blockchain) => {
+                blockchain.wallet_sync(database, progress_update)
+            }
+        }
+    }
+}
+
+impl GetHeight for SyncBlockchain {
+    fn get_height(&self) -> Result<u32, bdk::Error> {
+        match self {
+            SyncBlockchain::Any(blockchain) => blockchain.get_height(),
+            #[cfg(feature = "compact-filters")]
+            SyncBlockchain::CompactFilters(blockchain) => blockchain.get_height(),
+        }
+    }
+}
+
+impl GetBlockHash for SyncBlockchain {
+    fn get_block_hash(&self, height: u64) -> Result<bitcoin::BlockHash, bdk::Error> {
+        match self {
+            SyncBlockchain::Any(blockchain) => blockchain.get_block_hash(height),
+            #[cfg(feature = "compact-filters")]
+            SyncBlockchain::CompactFilters(blockchain) => blockchain.get_block_hash(height),
+        }
+    }
+}
+
+/// Returns the blockchain used to sync the given keychain together with the current height.
+/// Sources that support it skip history below the birthday height. A pending rescan
+/// widens the gap limit and restarts the scan from its (or the wallet's) birthday height.
+/// Compact filters share one store per network, so they only honour the configured `skip_blocks`.
+pub async fn init_blockchain(
+    cfg: &BlockchainConfig,
+    keychain_id: KeychainId,
+    birthday_height: Option<u32>,
+    rescan: Option<&KeychainRescan>,
+) -> Result<(SyncBlockchain, u32), BdkError> {
+    let gap_limit = rescan.and_then(|r| r.gap_limit).map(|g| g as usize);
+    let birthday_height = rescan.and_then(|r| r.birthday_height).or(birthday_height);
+    let blockchain = match cfg.chain_source {
+        ChainSourceConfig::Electrum => match gap_limit {
+            Some(stop_gap) => AnyBlockchain::from(ElectrumBlockchain::from_config(
+                &ElectrumBlockchainConfig {
+                    url: cfg.electrum_url.clone(),
+                    socks5: None,
+                    retry: 10,
+                    timeout: Some(60),
+                    stop_gap,
+                    validate_domain: true,
+                },
+            )?),
+            None => AnyBlockchain::from(init_electrum(&cfg.electrum_url)?),
+        },
+        ChainSourceConfig::Esplora(ref esplora) => {
+            let current_height = EsploraClient::new(esplora).get_height().await?;
+            let blockchain = EsploraBlockchain::from_config(&EsploraBlockchainConfig {
+                base_url: esplora.url.clone(),
+                proxy: None,
+                concurrency: None,
+                stop_gap: gap_limit.unwrap_or(esplora.stop_gap),
+                timeout: Some(esplora.timeout.as_secs()),
+            })?;
+            return Ok((
+                SyncBlockchain::Any(AnyBlockchain::from(blockchain)),
+                current_height,
+            ));
+        }
+        ChainSourceConfig::Bitcoind(ref bitcoind) => {
+            // bdk only uses the start time when the keychain wasn't synced before
+            // (or when it is forced by a rescan)
+            let sync_params =
+                Some(bitcoind.sync_params(birthday_height, gap_limit, rescan.is_some())?);
+            AnyBlockchain::from(RpcBlockchain::from_config(&RpcConfig {
+                url: bitcoind.rpc_url.clone(),
+                auth: Auth::UserPass {
+                    username: bitcoind.rpc_user.clone(),
+                    password: bitcoind.rpc_password.clone(),
+                },
+                network: cfg.network,
+                // bitcoind tracks every keychain in its own watch-only wallet
+                wallet_name: format!("bria-{keychain_id}"),
+                sync_params,
+            })?)
+        }
+        #[cfg(feature = "compact-filters")]
+        ChainSourceConfig::CompactFilters(ref compact_filters) => {
+            let blockchain = SharedCompactFilters::for_network(compact_filters, cfg.network)?;
+            let current_height = blockchain.get_height()?;
+            return Ok((SyncBlockchain::CompactFilters(blockchain), current_height));
+        }
+    };
+    let current_height = blockchain.get_height()?;
+    Ok((SyncBlockchain::Any(blockchain), current_height))
+}
+
+/// Broadcasts the tx through the primary chain source and every additional
+/// `broadcast_sources` backend, returning the outcome per backend.
+pub async fn broadcast_all(
+    cfg: &BlockchainConfig,
+    tx: &bitcoin::Transaction,
+) -> Vec<(String, Result<(), BdkError>)> {
+    let mut results = Vec::new();
+    for source in std::iter::once(&cfg.chain_source).chain(cfg.broadcast_sources.iter()) {
+        let res = broadcast(source, cfg, tx).await;
+        if let Err(ref e) = res {
+            tracing::warn!(
+                "broadcast via {} failed: {e}",
+                source.name(&cfg.electrum_url)
+            );
+        }
+        results.push((source.name(&cfg.electrum_url), res));
+    }
+    results
+}
+
+/// Checks whether the primary chain source still knows about the tx (in the mempool or a block).
+pub async fn is_tx_known(cfg: &BlockchainConfig, tx_id: bitcoin::Txid) -> Result<bool, BdkError> {
+    match cfg.chain_source {
+        ChainSourceConfig::Electrum => {
+            match init_electrum_client(&cfg.electrum_url)?.transaction_get_raw(&tx_id) {
+                Ok(_) => Ok(true),
+                Err(electrum_client::Error::Protocol(_)) => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        }
+        ChainSourceConfig::Esplora(ref esplora) => {
+            EsploraClient::new(esplora).is_tx_known(tx_id).await
+        }
+        ChainSourceConfig::Bitcoind(ref bitcoind) => {
+            // finds mempool txs and, when bitcoind runs with txindex, confirmed ones
+            match bitcoind.client()?.get_raw_transaction(&tx_id, None) {
+                Ok(_) => Ok(true),
+                Err(bitcoincore_rpc::Error::JsonRpc(bitcoincore_rpc::jsonrpc::Error::Rpc(e)))
+                    if e.code == RPC_INVALID_ADDRESS_OR_KEY =>
+                {
+                    Ok(false)
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+        // peers don't serve mempool lookups, rebroadcasting an already known tx is harmless
+        #[cfg(feature = "compact-filters")]
+        ChainSourceConfig::CompactFilters(_) => Ok(false),
+    }
+}
+
+async fn broadcast(
+    source: &ChainSourceConfig,
+    cfg: &BlockchainConfig,
+    tx: &bitcoin::Transaction,
+) -> Result<(), BdkError> {
+    match source {
+        ChainSourceConfig::Electrum => init_electrum(&cfg.electrum_url)?.broadcast(tx)?,
+        ChainSourceConfig::Esplora(esplora) => EsploraClient::new(esplora).broadcast(tx).await?,
+        ChainSourceConfig::Bitcoind(bitcoind) => {
+            match bitcoind.client()?.send_raw_transaction(tx) {
+                Ok(_) => (),
+                // without txindex confirmed txs aren't found by is_tx_known
+                Err(bitcoincore_rpc::Error::JsonRpc(bitcoincore_rpc::jsonrpc::Error::Rpc(e)))
+                    if e.code == RPC_VERIFY_ALREADY_IN_CHAIN => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        #[cfg(feature = "compact-filters")]
+        ChainSourceConfig::CompactFilters(compact_filters) => {
+            SharedCompactFilters::for_network(compact_filters, cfg.network)?.broadcast(tx)?
+        }
+    }
+    Ok(())
+}
+
+fn init_electrum(electrum_url: &str) -> Result<ElectrumBlockchain, BdkError> {
+    Ok(ElectrumBlockchain::from(init_electrum_client(
+        electrum_url,
+    )?))
+}
+
+fn init_electrum_client(electrum_url: &str) -> Result<Client, BdkError> {
+    Ok(Client::from_config(
+        electrum_url,
+        ConfigBuilder::new().retry(10).timeout(Some(60)).build(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn electrum_is_the_default_chain_source() {
+        let cfg: BlockchainConfig = serde_yaml::from_str("network: regtest").unwrap();
+        assert!(matches!(cfg.chain_source, ChainSourceConfig::Electrum));
+    }
+
+    #[test]
+    fn deserialize_bitcoind_chain_source() {
+        let cfg: BlockchainConfig = serde_yaml::from_str(
+            r#"
+            network: regtest
+            chain_source:
+              type: bitcoind
+              rpc_url: http://localhost:18443
+              rpc_user: rpcuser
+              rpc_password: rpcpassword
+              zmq_block_endpoint: tcp://localhost:28332
+            "#,
+        )
+        .unwrap();
+        let ChainSourceConfig::Bitcoind(bitcoind) = cfg.chain_source else {
+            panic!("expected bitcoind chain source");
+        };
+        assert_eq!(bitcoind.rpc_url, "http://localhost:18443");
+        assert_eq!(
+            bitcoind.zmq_block_endpoint.as_deref(),
+            Some("tcp://localhost:28332")
+        );
+        assert!(bitcoind.zmq_tx_endpoint.is_none());
+        assert_eq!(
+            bitcoind.zmq_min_sync_interval,
+            std::time::Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "compact-filters")]
+    fn deserialize_compact_filters_chain_source() {
+        let cfg: BlockchainConfig = serde_yaml::from_str(
+            r#"
+            network: regtest
+            chain_source:
+              type: compact_filters
+              peers:
+                - 127.0.0.1:18444
+              storage_dir: /tmp/bria-filters
+            "#,
+        )
+        .unwrap();
+        let ChainSourceConfig::CompactFilters(compact_filters) = cfg.chain_source else {
+            panic!("expected compact filters chain source");
+        };
+        assert_eq!(compact_filters.peers, vec!["127.0.0.1:18444"]);
+        assert!(compact_filters.skip_blocks.is_none());
+    }
+
+    #[test]
+    fn deserialize_broadcast_sources() {
+        let cfg: BlockchainConfig = serde_yaml::from_str(
+            r#"
+            network: regtest
+            broadcast_sources:
+              - type: esplora
+                url: http://localhost:3002
+              - type: bitcoind
+                rpc_url: http://localhost:18443
+                rpc_user: rpcuser
+                rpc_password: rpcpassword
+            "#,
+        )
+        .unwrap();
+        let names: Vec<_> = cfg
+            .broadcast_sources
+            .iter()
+            .map(|source| source.name(&cfg.electrum_url))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "esplora:http://localhost:3002",
+                "bitcoind:http://localhost:18443"
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_esplora_chain_source() {
+        let cfg: BlockchainConfig = serde_yaml::from_str(
+            r#"
+            network: signet
+            chain_source:
+              type: esplora
+              url: https://blockstream.info/signet/api
+            "#,
+        )
+        .unwrap();
+        let ChainSourceConfig::Esplora(esplora) = cfg.chain_source else {
+            panic!("expected esplora chain source");
+        };
+        assert_eq!(esplora.url, "https://blockstream.info/signet/api");
+        assert_eq!(esplora.timeout, std::time::Duration::from_secs(30));
+        assert_eq!(esplora.stop_gap, 20);
+    }
+}
@@ -0,0 +1,112 @@
+use bdk::{
+    bitcoin::{BlockHash, Transaction},
+    blockchain::{
+        compact_filters::{
+            BitcoinPeerConfig, CompactFiltersBlockchain, CompactFiltersBlockchainConfig,
+        },
+        Blockchain, ConfigurableBlockchain, GetBlockHash, GetHeight, Progress, WalletSync,
+    },
+    database::BatchDatabase,
+};
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use super::{chain_source::CompactFiltersChainSourceConfig, error::BdkError};
+use crate::primitives::bitcoin;
+
+static FILTER_STORES: OnceLock<Mutex<HashMap<String, SharedCompactFilters>>> = OnceLock::new();
+
+/// Handle on the header and filter store of a network. The store is opened once per
+/// process and shared by every keychain, its peer connections are reused for broadcasting.
+/// A store whose peers fail gets dropped so that the next use reconnects.
+#[derive(Clone)]
+pub struct SharedCompactFilters {
+    storage_dir: String,
+    blockchain: Arc<Mutex<CompactFiltersBlockchain>>,
+}
+
+impl SharedCompactFilters {
+    pub fn for_network(
+        config: &CompactFiltersChainSourceConfig,
+        network: bitcoin::Network,
+    ) -> Result<Self, BdkError> {
+        let storage_dir = format!("{}/{network}", config.storage_dir);
+        let mut stores = FILTER_STORES
+            .get_or_init(Default::default)
+            .lock()
+            .expect("filter stores lock poisoned");
+        if let Some(store) = stores.get(&storage_dir) {
+            return Ok(store.clone());
+        }
+        let blockchain = CompactFiltersBlockchain::from_config(&CompactFiltersBlockchainConfig {
+            peers: config
+                .peers
+                .iter()
+                .map(|address| BitcoinPeerConfig {
+                    address: address.clone(),
+                    socks5: None,
+                    socks5_credentials: None,
+                })
+                .collect(),
+            network,
+            storage_dir: storage_dir.clone(),
+            skip_blocks: config.skip_blocks,
+        })?;
+        let store = Self {
+            storage_dir: storage_dir.clone(),
+            blockchain: Arc::new(Mutex::new(blockchain)),
+        };
+        stores.insert(storage_dir, store.clone());
+        Ok(store)
+    }
+
+    pub fn broadcast(&self, tx: &Transaction) -> Result<(), BdkError> {
+        Ok(self.with_blockchain(|blockchain| blockchain.broadcast(tx))?)
+    }
+
+    fn with_blockchain<T>(
+        &self,
+        f: impl FnOnce(&CompactFiltersBlockchain) -> Result<T, bdk::Error>,
+    ) -> Result<T, bdk::Error> {
+        let res = f(&self.blockchain.lock().expect("filter store lock poisoned"));
+        if res.is_err() {
+            let mut stores = FILTER_STORES
+                .get_or_init(Default::default)
+                .lock()
+                .expect("filter stores lock poisoned");
+            if stores
+                .get(&self.storage_dir)
+                .is_some_and(|store| Arc::ptr_eq(&store.blockchain, &self.blockchain))
+            {
+                stores.remove(&self.storage_dir);
+            }
+        }
+        res
+    }
+}
+
+impl WalletSync for SharedCompactFilters {
+    fn wallet_setup<D: BatchDatabase>(
+        &self,
+        database: &RefCell<D>,
+        progress_update: Box<dyn Progress>,
+    ) -> Result<(), bdk::Error> {
+        self.with_blockchain(|blockchain| blockchain.wallet_setup(database, progress_update))
+    }
+}
+
+impl GetHeight for SharedCompactFilters {
+    fn get_height(&self) -> Result<u32, bdk::Error> {
+        self.with_blockchain(|blockchain| blockchain.get_height())
+    }
+}
+
+impl GetBlockHash for SharedCompactFilters {
+    fn get_block_hash(&self, height: u64) -> Result<BlockHash, bdk::Error> {
+        self.with_blockchain(|blockchain| blockchain.get_block_hash(height))
+    }
+}
@@ -0,0 +1,329 @@
+use bdk::bitcoin::{block, consensus, hashes::hex::FromHex};
+use electrum_client::{HeaderNotification, ScriptHash, ToElectrumScriptHash};
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::{runtime::Handle, sync::Notify};
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    sync::Arc,
+};
+
+use super::{chain_source::ChainSourceConfig, error::BdkError, pg::SyncCursors};
+use crate::{app::BlockchainConfig, primitives::KeychainId};
+
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+const SUBSCRIBE_BATCH_SIZE: usize = 1000;
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Subscribes to new headers and to the script hashes of every keychain on a
+/// single electrum connection. Keychains with activity (and keychains waiting
+/// for confirmations when a block arrives) get their sync cursor marked dirty
+/// before notifying. A header that doesn't chain onto the previous tip marks every
+/// keychain dirty so that the reorg gets picked up by all of them.
+pub fn spawn_electrum_watcher(pool: PgPool, cfg: &BlockchainConfig) -> Option<Arc<Notify>> {
+    if !cfg.incremental_sync || !matches!(cfg.chain_source, ChainSourceConfig::Electrum) {
+        return None;
+    }
+    let electrum_url = cfg.electrum_url.clone();
+    let notify = Arc::new(Notify::new());
+    let rt = Handle::current();
+    let watcher_notify = Arc::clone(&notify);
+    tokio::task::spawn_blocking(move || {
+        let cursors = SyncCursors::new(pool);
+        loop {
+            if let Err(e) = watch(&rt, &cursors, &electrum_url, &watcher_notify) {
+                tracing::error!("electrum watcher on {electrum_url} failed: {e}");
+            }
+            std::thread::sleep(RECONNECT_DELAY);
+        }
+    });
+    Some(notify)
+}
+
+fn watch(
+    rt: &Handle,
+    cursors: &SyncCursors,
+    electrum_url: &str,
+    notify: &Notify,
+) -> Result<(), BdkError> {
+    let mut connection = NotificationConnection::connect(electrum_url)?;
+    let header = connection.subscribe_headers()?;
+    let mut last_header = (header.height, header.header.block_hash());
+    // anything could have happened while we were not subscribed
+    rt.block_on(cursors.mark_all_dirty())?;
+    notify.notify_one();
+
+    let mut scripts: HashMap<ScriptHash, KeychainId> = HashMap::new();
+    let mut loaded_since = None;
+    loop {
+        let new_scripts: Vec<_> = rt
+            .block_on(cursors.script_pubkeys_since(loaded_since))?
+            .into_iter()
+            .filter_map(|(keychain_id, script, created_at)| {
+                loaded_since = Some(created_at);
+                let script_hash = script.to_electrum_scripthash();
+                scripts
+                    .insert(script_hash, keychain_id)
+                    .is_none()
+                    .then_some(script_hash)
+            })
+            .collect();
+        for chunk in new_scripts.chunks(SUBSCRIBE_BATCH_SIZE) {
+            connection.subscribe_scripts(chunk)?;
+        }
+
+        // notifications are only read off the socket while a request is in flight
+        connection.ping()?;
+        let mut tip = None;
+        let mut reorged = false;
+        let mut dirty = HashSet::new();
+        while let Some(notification) = connection.pop_notification() {
+            match notification {
+                Notification::Header(header) => {
+                    let (last_height, last_hash) = last_header;
+                    reorged |= if header.height == last_height + 1 {
+                        header.header.prev_blockhash != last_hash
+                    } else if header.height > last_height {
+                        // servers may only announce the latest of several new blocks
+                        connection.block_header(last_height)?.block_hash() != last_hash
+                    } else {
+                        header.header.block_hash() != last_hash
+                    };
+                    last_header = (header.height, header.header.block_hash());
+                    tip = Some(header.height as u32);
+                }
+                Notification::Script(script_hash) => {
+                    if let Some(keychain_id) = scripts.get(&script_hash) {
+                        dirty.insert(*keychain_id);
+                    }
+                }
+            }
+        }
+
+        if reorged {
+            // settled utxos may be affected, sync everything to detect the fork
+            rt.block_on(cursors.mark_all_dirty())?;
+        } else if let Some(tip) = tip {
+            rt.block_on(cursors.mark_pending_dirty(tip))?;
+        }
+        if !dirty.is_empty() {
+            rt.block_on(cursors.mark_dirty(dirty.iter().copied()))?;
+        }
+        if tip.is_some() || !dirty.is_empty() {
+            notify.notify_one();
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+enum Notification {
+    Header(HeaderNotification),
+    Script(ScriptHash),
+}
+
+trait ElectrumStream: Read + Write + Send {}
+impl<T: Read + Write + Send> ElectrumStream for T {}
+
+/// Electrum connection that queues subscription notifications in the order they
+/// arrive. electrum_client only lets them be polled script by script, which
+/// doesn't scale to every address of every keychain. A dropped connection
+/// can't be resumed since the server forgets all subscriptions.
+struct NotificationConnection {
+    stream: BufReader<Box<dyn ElectrumStream>>,
+    next_id: usize,
+    notifications: VecDeque<Notification>,
+}
+
+impl NotificationConnection {
+    fn connect(electrum_url: &str) -> Result<Self, electrum_client::Error> {
+        let (ssl, address) = match electrum_url.split_once("://") {
+            Some(("ssl", address)) => (true, address),
+            Some(("tcp", address)) => (false, address),
+            Some(_) => {
+                return Err(electrum_client::Error::Message(format!(
+                    "Unsupported electrum url {electrum_url}"
+                )))
+            }
+            None => (false, electrum_url),
+        };
+        let tcp = TcpStream::connect(address)?;
+        tcp.set_read_timeout(Some(TIMEOUT))?;
+        tcp.set_write_timeout(Some(TIMEOUT))?;
+        let stream: Box<dyn ElectrumStream> = if ssl {
+            let domain = address.rsplit_once(':').map_or(address, |(host, _)| host);
+            let server_name = rustls::ServerName::try_from(domain)
+                .map_err(|_| electrum_client::Error::InvalidDNSNameError(domain.to_owned()))?;
+            let session = rustls::ClientConnection::new(tls_config(), server_name)
+                .map_err(electrum_client::Error::CouldNotCreateConnection)?;
+            Box::new(rustls::StreamOwned::new(session, tcp))
+        } else {
+            Box::new(tcp)
+        };
+        Ok(Self {
+            stream: BufReader::new(stream),
+            next_id: 0,
+            notifications: VecDeque::new(),
+        })
+    }
+
+    fn subscribe_headers(&mut self) -> Result<HeaderNotification, electrum_client::Error> {
+        let mut results = self.call("blockchain.headers.subscribe", vec![json!([])])?;
+        Ok(serde_json::from_value(results.remove(0))?)
+    }
+
+    fn subscribe_scripts(&mut self, scripts: &[ScriptHash]) -> Result<(), electrum_client::Error> {
+        self.call(
+            "blockchain.scripthash.subscribe",
+            scripts.iter().map(|script| json!([script])).collect(),
+        )?;
+        Ok(())
+    }
+
+    fn block_header(&mut self, height: usize) -> Result<block::Header, electrum_client::Error> {
+        let mut results = self.call("blockchain.block.header", vec![json!([height])])?;
+        let result = results.remove(0);
+        let raw = Vec::<u8>::from_hex(
+            result
+                .as_str()
+                .ok_or_else(|| electrum_client::Error::InvalidResponse(result.clone()))?,
+        )?;
+        Ok(consensus::deserialize(&raw)?)
+    }
+
+    fn ping(&mut self) -> Result<(), electrum_client::Error> {
+        self.call("server.ping", vec![json!([])])?;
+        Ok(())
+    }
+
+    fn pop_notification(&mut self) -> Option<Notification> {
+        self.notifications.pop_front()
+    }
+
+    /// Sends one request per entry of `params` as a single batch and returns
+    /// the results in the same order. Notifications read in the meantime are queued.
+    fn call(
+        &mut self,
+        method: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<Vec<serde_json::Value>, electrum_client::Error> {
+        let first_id = self.next_id;
+        self.next_id += params.len();
+        let requests: Vec<_> = params
+            .into_iter()
+            .enumerate()
+            .map(|(idx, params)| {
+                json!({"jsonrpc": "2.0", "id": first_id + idx, "method": method, "params": params})
+            })
+            .collect();
+        let mut results = vec![None; requests.len()];
+        let mut line = serde_json::to_vec(&requests)?;
+        line.push(b'\n');
+        self.stream.get_mut().write_all(&line)?;
+        self.stream.get_mut().flush()?;
+
+        while results.iter().any(Option::is_none) {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line)? == 0 {
+                return Err(electrum_client::Error::IOError(
+                    std::io::ErrorKind::UnexpectedEof.into(),
+                ));
+            }
+            let messages = match serde_json::from_str(&line)? {
+                serde_json::Value::Array(messages) => messages,
+                message => vec![message],
+            };
+            for mut message in messages {
+                if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+                    let notification = match method {
+                        "blockchain.headers.subscribe" => Notification::Header(
+                            serde_json::from_value(message["params"][0].take())?,
+                        ),
+                        "blockchain.scripthash.subscribe" => Notification::Script(
+                            serde_json::from_value(message["params"][0].take())?,
+                        ),
+                        _ => continue,
+                    };
+                    self.notifications.push_back(notification);
+                    continue;
+                }
+                let idx = message
+                    .get("id")
+                    .and_then(|id| id.as_u64())
+                    .and_then(|id| (id as usize).checked_sub(first_id))
+                    .filter(|idx| *idx < results.len())
+                    .ok_or_else(|| electrum_client::Error::InvalidResponse(message.clone()))?;
+                if let Some(error) = message.get("error").filter(|e| !e.is_null()) {
+                    return Err(electrum_client::Error::Protocol(error.clone()));
+                }
+                results[idx] = Some(message["result"].take());
+            }
+        }
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+fn tls_config() -> Arc<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    Arc::new(
+        rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_notifications_read_while_waiting_for_a_response() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let script_hash = ScriptHash::from([7; 32]);
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let requests: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(requests.as_array().unwrap().len(), 2);
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "blockchain.scripthash.subscribe",
+                "params": [script_hash, "00".repeat(32)],
+            });
+            let responses = json!([
+                {"jsonrpc": "2.0", "id": 1, "result": null},
+                {"jsonrpc": "2.0", "id": 0, "result": "11".repeat(32)},
+            ]);
+            writeln!(writer, "{notification}\n{responses}").unwrap();
+        });
+
+        let mut connection = NotificationConnection::connect(&address).unwrap();
+        let results = connection
+            .call(
+                "blockchain.scripthash.subscribe",
+                vec![json!([ScriptHash::from([1; 32])]), json!([script_hash])],
+            )
+            .unwrap();
+        assert_eq!(results, vec![json!("11".repeat(32)), json!(null)]);
+        assert!(matches!(
+            connection.pop_notification(),
+            Some(Notification::Script(hash)) if hash == script_hash
+        ));
+        assert!(connection.pop_notification().is_none());
+    }
+}
@@ -8,8 +8,20 @@ pub enum BdkError {
     BdkLibError(#[from] bdk::Error),
     #[error("BdkError - ElectrumClient: {0}")]
     ElectrumClient(#[from] electrum_client::Error),
+    #[error("BdkError - BitcoindRpc: {0}")]
+    BitcoindRpc(#[from] bitcoincore_rpc::Error),
+    #[error("BdkError - Esplora: {0}")]
+    Esplora(#[from] reqwest::Error),
+    #[error("BdkError - EsploraInvalidResponse: {0}")]
+    EsploraInvalidResponse(String),
+    #[error("BdkError - EsploraBroadcastRejected: {0}")]
+    EsploraBroadcastRejected(String),
+    #[error("BdkError - Zmq: {0}")]
+    Zmq(#[from] zeromq::ZmqError),
     #[error("BdkError - Sqlx: {0}")]
     Sqlx(#[from] sqlx::Error),
+    #[error("BdkError - InvalidBlockHash: {0}")]
+    InvalidBlockHash(#[from] bdk::bitcoin::hashes::hex::Error),
     #[error("BdkError - Serde: {0}")]
     Serde(#[from] serde_json::Error),
 }
@@ -0,0 +1,203 @@
+use bdk::bitcoin::consensus::encode::serialize_hex;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::error::BdkError;
+use crate::primitives::bitcoin;
+
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EsploraChainSourceConfig {
+    /// Base url of the esplora api, eg. https://blockstream.info/signet/api
+    pub url: String,
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_timeout")]
+    pub timeout: std::time::Duration,
+    #[serde(default = "default_stop_gap")]
+    pub stop_gap: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct EsploraClient {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl EsploraClient {
+    pub fn new(config: &EsploraChainSourceConfig) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(config.timeout)
+                .build()
+                .expect("could not build reqwest client"),
+            url: config.url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    #[instrument(name = "esplora.get_height", skip(self), ret, err)]
+    pub async fn get_height(&self) -> Result<u32, BdkError> {
+        let body = self
+            .client
+            .get(format!("{}/blocks/tip/height", self.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        body.trim()
+            .parse()
+            .map_err(|_| BdkError::EsploraInvalidResponse(body))
+    }
+
+    #[instrument(name = "esplora.is_tx_known", skip(self), ret, err)]
+    pub async fn is_tx_known(&self, tx_id: bitcoin::Txid) -> Result<bool, BdkError> {
+        let response = self
+            .client
+            .get(format!("{}/tx/{tx_id}/status", self.url))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        response.error_for_status()?;
+        Ok(true)
+    }
+
+    #[instrument(name = "esplora.broadcast", skip_all, fields(txid = %tx.txid()), err)]
+    pub async fn broadcast(&self, tx: &bitcoin::Transaction) -> Result<(), BdkError> {
+        let response = self
+            .client
+            .post(format!("{}/tx", self.url))
+            .body(serialize_hex(tx))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(BdkError::EsploraBroadcastRejected(response.text().await?));
+        }
+        Ok(())
+    }
+}
+
+fn default_timeout() -> std::time::Duration {
+    std::time::Duration::from_secs(30)
+}
+
+fn default_stop_gap() -> usize {
+    20
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::oneshot,
+    };
+
+    use super::*;
+
+    /// Serves a single request with the given response body and hands back the raw request.
+    async fn mock_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (send, recv) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, payload)) = text.split_once("\r\n\r\n") {
+                    let content_length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(|v| v.parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if payload.len() >= content_length {
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let _ = send.send(String::from_utf8(request).unwrap());
+        });
+        (url, recv)
+    }
+
+    fn client(url: String) -> EsploraClient {
+        EsploraClient::new(&EsploraChainSourceConfig {
+            url,
+            timeout: default_timeout(),
+            stop_gap: default_stop_gap(),
+        })
+    }
+
+    #[tokio::test]
+    async fn get_height() {
+        let (url, request) = mock_server("200 OK", "812345").await;
+        assert_eq!(client(url).get_height().await.unwrap(), 812345);
+        assert!(request
+            .await
+            .unwrap()
+            .starts_with("GET /blocks/tip/height HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn is_tx_known() {
+        let tx_id: bitcoin::Txid =
+            "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+                .parse()
+                .unwrap();
+        let (url, request) = mock_server("200 OK", r#"{"confirmed":false}"#).await;
+        assert!(client(url).is_tx_known(tx_id).await.unwrap());
+        assert!(request
+            .await
+            .unwrap()
+            .starts_with(&format!("GET /tx/{tx_id}/status HTTP/1.1")));
+
+        let (url, _) = mock_server("404 Not Found", "Transaction not found").await;
+        assert!(!client(url).is_tx_known(tx_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn broadcast_posts_raw_tx_hex() {
+        let tx = bitcoin::Transaction {
+            version: 2,
+            lock_time: bdk::bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        let (url, request) = mock_server("200 OK", "").await;
+        client(format!("{url}/")).broadcast(&tx).await.unwrap();
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST /tx HTTP/1.1"));
+        assert!(request.ends_with(&serialize_hex(&tx)));
+    }
+
+    #[tokio::test]
+    async fn broadcast_surfaces_rejection() {
+        let tx = bitcoin::Transaction {
+            version: 2,
+            lock_time: bdk::bitcoin::absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        };
+        let (url, _) = mock_server("400 Bad Request", "bad-txns-vin-empty").await;
+        let err = client(url).broadcast(&tx).await.unwrap_err();
+        assert!(
+            matches!(err, BdkError::EsploraBroadcastRejected(msg) if msg == "bad-txns-vin-empty")
+        );
+    }
+}
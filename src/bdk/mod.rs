@@ -1,5 +1,11 @@
+pub mod chain_source;
+#[cfg(feature = "compact-filters")]
+pub mod compact_filters;
+pub(crate) mod electrum_watcher;
 pub mod error;
-pub(crate) mod pg;
+pub mod esplora;
+pub mod pg;
+pub(crate) mod zmq;
 
 pub async fn last_sync_time(pool: &sqlx::PgPool) -> Result<u32, error::BdkError> {
     pg::SyncTimes::last_sync_time(pool).await
@@ -0,0 +1,168 @@
+use bdk::blockchain::GetBlockHash;
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::instrument;
+
+use crate::{bdk::error::BdkError, primitives::*};
+
+/// Blocks older than this (relative to the tip) are no longer checked for reorgs.
+const MAX_TRACKED_DEPTH: u32 = 1000;
+
+/// Remembers the hash of the tip every time a keychain gets synced so that a
+/// later sync can tell whether those blocks are still part of the best chain.
+pub struct BlockHashes {
+    keychain_id: KeychainId,
+    pool: PgPool,
+}
+
+impl BlockHashes {
+    pub fn new(keychain_id: KeychainId, pool: PgPool) -> Self {
+        Self { keychain_id, pool }
+    }
+
+    /// Returns the lowest height that may have been reorged out since the last
+    /// recorded sync, or `None` if the recorded blocks are still in the chain.
+    #[instrument(
+        name = "bdk.block_hashes.find_fork_height",
+        skip(self, blockchain),
+        ret,
+        err
+    )]
+    pub async fn find_fork_height<B: GetBlockHash>(
+        &self,
+        blockchain: &B,
+        current_height: u32,
+    ) -> Result<Option<u32>, BdkError> {
+        let rows = sqlx::query!(
+            r#"SELECT height, block_hash FROM bria_block_hashes
+            WHERE keychain_id = $1 ORDER BY height DESC"#,
+            self.keychain_id as KeychainId,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let recorded = rows
+            .into_iter()
+            .map(|row| Ok((row.height as u32, row.block_hash.parse()?)))
+            .collect::<Result<Vec<_>, BdkError>>()?;
+        fork_height(&recorded, current_height, |height| {
+            Ok(blockchain.get_block_hash(height as u64)?)
+        })
+    }
+
+    #[instrument(name = "bdk.block_hashes.remove_reorged", skip(self, tx))]
+    pub async fn remove_reorged(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        fork_height: u32,
+    ) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"DELETE FROM bria_block_hashes WHERE keychain_id = $1 AND height >= $2"#,
+            self.keychain_id as KeychainId,
+            fork_height as i32,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "bdk.block_hashes.record_tip", skip(self))]
+    pub async fn record_tip(
+        &self,
+        height: u32,
+        block_hash: bitcoin::BlockHash,
+    ) -> Result<(), BdkError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"INSERT INTO bria_block_hashes (keychain_id, height, block_hash)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (keychain_id, height) DO UPDATE SET block_hash = EXCLUDED.block_hash"#,
+            self.keychain_id as KeychainId,
+            height as i32,
+            block_hash.to_string(),
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"DELETE FROM bria_block_hashes WHERE keychain_id = $1 AND height < $2"#,
+            self.keychain_id as KeychainId,
+            height.saturating_sub(MAX_TRACKED_DEPTH) as i32,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Walks the recorded hashes (highest first) until one still matches the chain.
+/// Everything above the match may have been reorged out. Without any match the
+/// fork is assumed at the deepest tracked height rather than at genesis.
+fn fork_height(
+    recorded: &[(u32, bitcoin::BlockHash)],
+    current_height: u32,
+    block_hash_at: impl Fn(u32) -> Result<bitcoin::BlockHash, BdkError>,
+) -> Result<Option<u32>, BdkError> {
+    let mut reorged = false;
+    for &(height, block_hash) in recorded {
+        if height <= current_height && block_hash_at(height)? == block_hash {
+            return Ok(reorged.then_some(height + 1));
+        }
+        reorged = true;
+    }
+    Ok(reorged.then_some(current_height.saturating_sub(MAX_TRACKED_DEPTH)))
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::hashes::Hash;
+
+    use super::*;
+
+    fn hash(n: u8) -> bitcoin::BlockHash {
+        bitcoin::BlockHash::hash(&[n])
+    }
+
+    fn chain(tip: u32) -> impl Fn(u32) -> Result<bitcoin::BlockHash, BdkError> {
+        move |height| {
+            assert!(height <= tip);
+            Ok(hash(height as u8))
+        }
+    }
+
+    #[test]
+    fn no_fork_without_records() {
+        assert_eq!(fork_height(&[], 10, chain(10)).unwrap(), None);
+    }
+
+    #[test]
+    fn no_fork_when_tip_matches() {
+        let recorded = [(10, hash(10)), (5, hash(5))];
+        assert_eq!(fork_height(&recorded, 12, chain(12)).unwrap(), None);
+    }
+
+    #[test]
+    fn fork_above_last_matching_block() {
+        let recorded = [(10, hash(100)), (8, hash(80)), (5, hash(5))];
+        assert_eq!(fork_height(&recorded, 12, chain(12)).unwrap(), Some(6));
+    }
+
+    #[test]
+    fn blocks_above_tip_count_as_reorged() {
+        let recorded = [(10, hash(10)), (7, hash(7))];
+        assert_eq!(fork_height(&recorded, 9, chain(9)).unwrap(), Some(8));
+    }
+
+    #[test]
+    fn fork_at_genesis_when_nothing_matches() {
+        let recorded = [(10, hash(100)), (5, hash(50))];
+        assert_eq!(fork_height(&recorded, 12, chain(12)).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn fork_without_match_is_bounded_by_tracked_depth() {
+        let recorded = [(5000, hash(100)), (4500, hash(50))];
+        assert_eq!(
+            fork_height(&recorded, 5000, chain(5000)).unwrap(),
+            Some(5000 - MAX_TRACKED_DEPTH)
+        );
+    }
+}
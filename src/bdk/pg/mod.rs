@@ -1,7 +1,10 @@
+mod block_hashes;
 mod convert;
 mod descriptor_checksum;
 mod index;
+mod rescans;
 mod script_pubkeys;
+mod sync_cursors;
 mod sync_times;
 mod transactions;
 mod utxos;
@@ -15,15 +18,18 @@ use sqlx::PgPool;
 use tokio::runtime::Handle;
 
 use crate::primitives::*;
+pub use block_hashes::BlockHashes;
 use convert::BdkKeychainKind;
 use descriptor_checksum::DescriptorChecksums;
 use index::Indexes;
+pub use rescans::{KeychainRescan, KeychainRescans};
 use script_pubkeys::ScriptPubkeys;
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
-pub(super) use sync_times::SyncTimes;
+pub use sync_cursors::SyncCursors;
+pub use sync_times::SyncTimes;
 pub use transactions::*;
 pub use utxos::*;
 
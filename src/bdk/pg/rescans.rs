@@ -0,0 +1,96 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::SyncTimes;
+use crate::{bdk::error::BdkError, primitives::*};
+
+/// Overrides applied to the chain source while a keychain is being rescanned.
+#[derive(Debug, Clone)]
+pub struct KeychainRescan {
+    pub birthday_height: Option<u32>,
+    pub gap_limit: Option<u32>,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Pending rescans that get picked up by the next sync of the keychain.
+#[derive(Clone)]
+pub struct KeychainRescans {
+    pool: PgPool,
+}
+
+impl KeychainRescans {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Forgets the bdk sync time of the keychain and records the overrides for
+    /// the next sync. Address indexes are left untouched as they only ever move
+    /// forward, so addresses that were already handed out are never reissued.
+    #[instrument(name = "bdk.keychain_rescans.request", skip(self, tx))]
+    pub async fn request(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        account_id: AccountId,
+        wallet_id: WalletId,
+        keychain_id: KeychainId,
+        birthday_height: Option<u32>,
+        gap_limit: Option<u32>,
+    ) -> Result<(), BdkError> {
+        SyncTimes::new(keychain_id, self.pool.clone())
+            .reset(tx)
+            .await?;
+        sqlx::query!(
+            r#"INSERT INTO bria_keychain_rescans (keychain_id, account_id, wallet_id, birthday_height, gap_limit)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (keychain_id) DO UPDATE SET
+              birthday_height = EXCLUDED.birthday_height,
+              gap_limit = EXCLUDED.gap_limit,
+              requested_at = NOW()"#,
+            Uuid::from(keychain_id),
+            Uuid::from(account_id),
+            Uuid::from(wallet_id),
+            birthday_height.map(|h| h as i32),
+            gap_limit.map(|g| g as i32),
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "bdk.keychain_rescans.find_pending", skip(self))]
+    pub async fn find_pending(
+        &self,
+        keychain_id: KeychainId,
+    ) -> Result<Option<KeychainRescan>, BdkError> {
+        let row = sqlx::query!(
+            r#"SELECT birthday_height, gap_limit, requested_at
+            FROM bria_keychain_rescans WHERE keychain_id = $1"#,
+            Uuid::from(keychain_id),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| KeychainRescan {
+            birthday_height: row.birthday_height.map(|h| h as u32),
+            gap_limit: row.gap_limit.map(|g| g as u32),
+            requested_at: row.requested_at,
+        }))
+    }
+
+    /// Clears the rescan unless it was requested again in the meantime.
+    #[instrument(name = "bdk.keychain_rescans.complete", skip(self))]
+    pub async fn complete(
+        &self,
+        keychain_id: KeychainId,
+        rescan: &KeychainRescan,
+    ) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"DELETE FROM bria_keychain_rescans WHERE keychain_id = $1 AND requested_at <= $2"#,
+            Uuid::from(keychain_id),
+            rescan.requested_at,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use sqlx::PgPool;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{bdk::error::BdkError, primitives::*};
+
+/// Tracks up to which height every keychain has been synced and whether the
+/// chain source reported activity for it since. `dirty_since` holds the time of the
+/// latest mark so that activity reported while a sync is running isn't lost.
+#[derive(Clone)]
+pub struct SyncCursors {
+    pool: PgPool,
+}
+
+impl SyncCursors {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "bdk.sync_cursors.needs_sync", skip(self))]
+    pub async fn needs_sync(&self, keychain_id: KeychainId) -> Result<bool, BdkError> {
+        let row = sqlx::query!(
+            r#"SELECT dirty_since FROM bria_keychain_sync_cursors WHERE keychain_id = $1"#,
+            Uuid::from(keychain_id),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| row.dirty_since.is_some()).unwrap_or(true))
+    }
+
+    /// Moves the cursor to `height` and clears the dirty flag unless the
+    /// keychain was marked dirty again after the sync started at `synced_from`.
+    #[instrument(name = "bdk.sync_cursors.advance", skip(self))]
+    pub async fn advance(
+        &self,
+        account_id: AccountId,
+        wallet_id: WalletId,
+        keychain_id: KeychainId,
+        height: u32,
+        synced_from: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"INSERT INTO bria_keychain_sync_cursors (keychain_id, account_id, wallet_id, height)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (keychain_id) DO UPDATE SET
+              height = EXCLUDED.height,
+              dirty_since = CASE
+                WHEN bria_keychain_sync_cursors.dirty_since <= $5 THEN NULL
+                ELSE bria_keychain_sync_cursors.dirty_since
+              END,
+              modified_at = NOW()"#,
+            Uuid::from(keychain_id),
+            Uuid::from(account_id),
+            Uuid::from(wallet_id),
+            height as i32,
+            synced_from,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "bdk.sync_cursors.mark_dirty", skip_all)]
+    pub async fn mark_dirty(
+        &self,
+        keychain_ids: impl IntoIterator<Item = KeychainId>,
+    ) -> Result<(), BdkError> {
+        let keychain_ids: Vec<Uuid> = keychain_ids.into_iter().map(Uuid::from).collect();
+        sqlx::query!(
+            r#"UPDATE bria_keychain_sync_cursors
+            SET dirty_since = NOW(), modified_at = NOW()
+            WHERE keychain_id = ANY($1)"#,
+            &keychain_ids,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "bdk.sync_cursors.mark_all_dirty", skip(self))]
+    pub async fn mark_all_dirty(&self) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"UPDATE bria_keychain_sync_cursors
+            SET dirty_since = NOW(), modified_at = NOW()"#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Marks keychains that still have unconfirmed txs or unsettled utxos so
+    /// that settlement progresses with the new tip.
+    #[instrument(name = "bdk.sync_cursors.mark_pending_dirty", skip(self))]
+    pub async fn mark_pending_dirty(&self, tip: u32) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"UPDATE bria_keychain_sync_cursors c
+            SET dirty_since = NOW(), modified_at = NOW()
+            WHERE c.height < $1
+              AND (
+                EXISTS (
+                  SELECT 1 FROM bdk_transactions t
+                  WHERE t.keychain_id = c.keychain_id AND t.confirmation_synced_to_bria IS NOT TRUE
+                )
+                OR EXISTS (
+                  SELECT 1 FROM bria_utxos u
+                  WHERE u.keychain_id = c.keychain_id
+                    AND (
+                      u.income_settled_ledger_tx_id IS NULL
+                      OR (u.spend_detected_ledger_tx_id IS NOT NULL AND u.spend_settled_ledger_tx_id IS NULL)
+                    )
+                )
+              )"#,
+            tip as i32,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Wallets that were never synced or have a dirty keychain.
+    #[instrument(name = "bdk.sync_cursors.wallets_to_sync", skip(self))]
+    pub async fn wallets_to_sync(&self) -> Result<Vec<(AccountId, WalletId)>, BdkError> {
+        let rows = sqlx::query!(
+            r#"SELECT w.account_id as "account_id!", w.id as "wallet_id!"
+            FROM bria_wallets w
+            WHERE NOT EXISTS (
+              SELECT 1 FROM bria_keychain_sync_cursors c WHERE c.wallet_id = w.id
+            )
+            UNION
+            SELECT account_id, wallet_id
+            FROM bria_keychain_sync_cursors
+            WHERE dirty_since IS NOT NULL"#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    AccountId::from(row.account_id),
+                    WalletId::from(row.wallet_id),
+                )
+            })
+            .collect())
+    }
+
+    /// Script pubkeys of all keychains that were derived at or after `since`.
+    #[instrument(name = "bdk.sync_cursors.script_pubkeys_since", skip(self))]
+    pub async fn script_pubkeys_since(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<
+        Vec<(
+            KeychainId,
+            bitcoin::ScriptBuf,
+            chrono::DateTime<chrono::Utc>,
+        )>,
+        BdkError,
+    > {
+        let rows = sqlx::query!(
+            r#"SELECT keychain_id, script, created_at
+            FROM bdk_script_pubkeys
+            WHERE $1::timestamptz IS NULL OR created_at >= $1
+            ORDER BY created_at"#,
+            since,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    KeychainId::from(row.keychain_id),
+                    bitcoin::ScriptBuf::from(row.script),
+                    row.created_at,
+                )
+            })
+            .collect())
+    }
+}
@@ -1,5 +1,5 @@
 use bdk::database::SyncTime;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -48,6 +48,17 @@ impl SyncTimes {
         }))
     }
 
+    #[instrument(name = "bdk.sync_times.reset", skip_all)]
+    pub async fn reset(&self, tx: &mut Transaction<'_, Postgres>) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"DELETE FROM bdk_sync_times WHERE keychain_id = $1"#,
+            Uuid::from(self.keychain_id),
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     #[instrument(name = "bdk.sync_times.last_sync_time", skip_all)]
     pub async fn last_sync_time(pool: &PgPool) -> Result<u32, BdkError> {
         let sync_time =
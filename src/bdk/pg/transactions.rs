@@ -276,6 +276,48 @@ impl Transactions {
         Ok(())
     }
 
+    /// Whether the output was spent by a tx confirmed below `height`.
+    #[instrument(name = "bdk.transactions.is_spent_below", skip(self), ret, err)]
+    pub async fn is_spent_below(
+        &self,
+        outpoint: bitcoin::OutPoint,
+        height: u32,
+    ) -> Result<bool, BdkError> {
+        let row = sqlx::query!(
+            r#"SELECT EXISTS (
+              SELECT 1 FROM bdk_transactions
+              WHERE keychain_id = $1 AND height < $3 AND deleted_at IS NULL
+                AND details_json->'transaction'->'input' @> jsonb_build_array(
+                  jsonb_build_object('previous_output', $2::text)
+                )
+            ) AS "spent!""#,
+            self.keychain_id as KeychainId,
+            outpoint.to_string(),
+            height as i32,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.spent)
+    }
+
+    /// Makes spends confirmed at or above `fork_height` eligible for settling again.
+    #[instrument(name = "bdk.transactions.mark_reorged", skip(self, tx))]
+    pub async fn mark_reorged(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        fork_height: u32,
+    ) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"UPDATE bdk_transactions SET confirmation_synced_to_bria = false, modified_at = NOW()
+            WHERE keychain_id = $1 AND height >= $2"#,
+            self.keychain_id as KeychainId,
+            fork_height as i32,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     #[instrument(
         name = "bdk.transactions.delete_transaction_if_no_more_utxos_exist",
         skip(self, tx)
@@ -163,6 +163,26 @@ impl Utxos {
         Ok(())
     }
 
+    /// Makes utxos confirmed at or above `fork_height` eligible for settling again.
+    #[instrument(name = "bdk.utxos.mark_reorged", skip(self, tx))]
+    pub async fn mark_reorged(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        fork_height: u32,
+    ) -> Result<(), BdkError> {
+        sqlx::query!(
+            r#"UPDATE bdk_utxos u SET confirmation_synced_to_bria = false, modified_at = NOW()
+            FROM bdk_transactions t
+            WHERE u.keychain_id = $1 AND t.keychain_id = u.keychain_id AND t.tx_id = u.tx_id
+            AND t.height >= $2"#,
+            self.keychain_id as KeychainId,
+            fork_height as i32,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     #[instrument(name = "bdk.utxos.find_confirmed_income_utxo", skip(self, tx))]
     pub async fn find_confirmed_income_utxo(
         &self,
@@ -0,0 +1,104 @@
+use tokio::{sync::Notify, time::Instant};
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+use std::{sync::Arc, time::Duration};
+
+use super::{chain_source::*, error::BdkError};
+
+const HASHBLOCK_TOPIC: &str = "hashblock";
+const RAWTX_TOPIC: &str = "rawtx";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Notifications of the chain event listeners, limited to one per `min_interval`.
+/// Everything announced in between is collapsed into the next notification.
+pub struct ChainEvents {
+    notify: Arc<Notify>,
+    min_interval: Duration,
+    last: Option<Instant>,
+}
+
+impl ChainEvents {
+    fn new(notify: Arc<Notify>, min_interval: Duration) -> Self {
+        Self {
+            notify,
+            min_interval,
+            last: None,
+        }
+    }
+
+    pub async fn notified(&mut self) {
+        if let Some(last) = self.last {
+            tokio::time::sleep_until(last + self.min_interval).await;
+        }
+        self.notify.notified().await;
+        self.last = Some(Instant::now());
+    }
+}
+
+/// Subscribes to the zmq endpoints of the node (if configured) and notifies
+/// whenever a new block or transaction was announced.
+pub fn spawn_chain_event_listeners(config: &ChainSourceConfig) -> Option<ChainEvents> {
+    let ChainSourceConfig::Bitcoind(bitcoind) = config else {
+        return None;
+    };
+    let subscriptions: Vec<_> = [
+        (bitcoind.zmq_block_endpoint.clone(), HASHBLOCK_TOPIC),
+        (bitcoind.zmq_tx_endpoint.clone(), RAWTX_TOPIC),
+    ]
+    .into_iter()
+    .filter_map(|(endpoint, topic)| endpoint.map(|endpoint| (endpoint, topic)))
+    .collect();
+    if subscriptions.is_empty() {
+        return None;
+    }
+
+    let notify = Arc::new(Notify::new());
+    for (endpoint, topic) in subscriptions {
+        let notify = Arc::clone(&notify);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = subscribe(&endpoint, topic, &notify).await {
+                    tracing::error!("zmq subscription to {endpoint} failed: {e}");
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+    }
+    Some(ChainEvents::new(notify, bitcoind.zmq_min_sync_interval))
+}
+
+async fn subscribe(endpoint: &str, topic: &str, notify: &Notify) -> Result<(), BdkError> {
+    let mut socket = SubSocket::new();
+    socket.connect(endpoint).await?;
+    socket.subscribe(topic).await?;
+    loop {
+        socket.recv().await?;
+        notify.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn chain_events_are_rate_limited() {
+        let notify = Arc::new(Notify::new());
+        let mut events = ChainEvents::new(Arc::clone(&notify), Duration::from_millis(200));
+        let start = Instant::now();
+
+        notify.notify_one();
+        events.notified().await;
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        for _ in 0..100 {
+            notify.notify_one();
+        }
+        events.notified().await;
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        // the burst was collapsed into a single notification
+        let next = tokio::time::timeout(Duration::from_millis(500), events.notified()).await;
+        assert!(next.is_err());
+    }
+}
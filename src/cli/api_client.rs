@@ -7,7 +7,7 @@ use crate::{
 };
 type ProtoClient = proto::bria_service_client::BriaServiceClient<tonic::transport::Channel>;
 
-use super::token_store;
+use super::{psbt_bundle, token_store};
 
 pub struct ApiClientConfig {
     pub url: Url,
@@ -209,16 +209,61 @@ impl ApiClient {
         output_json(response)
     }
 
+    pub async fn export_unsigned_psbts(
+        &self,
+        xpub_ref: String,
+        out_dir: std::path::PathBuf,
+        frame_size: usize,
+        ur_fragment_len: usize,
+    ) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::ExportUnsignedPsbtsRequest { xpub_ref });
+        let response = self
+            .connect()
+            .await?
+            .export_unsigned_psbts(self.inject_auth_token(request)?)
+            .await?;
+        let exported = psbt_bundle::write_bundle(
+            out_dir,
+            response.into_inner().psbts,
+            frame_size,
+            ur_fragment_len,
+        )?;
+        output_json(tonic::Response::new(exported))
+    }
+
+    pub async fn submit_signed_psbts(
+        &self,
+        xpub_ref: String,
+        files: Vec<std::path::PathBuf>,
+    ) -> anyhow::Result<()> {
+        let signed_psbts = files
+            .iter()
+            .map(|file| psbt_bundle::read_psbt(file).map(|psbt| psbt.to_string()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let request = tonic::Request::new(proto::SubmitSignedPsbtsRequest {
+            xpub_ref,
+            signed_psbts,
+        });
+        let response = self
+            .connect()
+            .await?
+            .submit_signed_psbts(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
     pub async fn create_wallet(
         &self,
         name: String,
         config: impl Into<proto::keychain_config::Config>,
+        birthday_height: Option<u32>,
     ) -> anyhow::Result<()> {
         let request = tonic::Request::new(proto::CreateWalletRequest {
             name,
             keychain_config: Some(proto::KeychainConfig {
                 config: Some(config.into()),
             }),
+            birthday_height,
         });
         let response = self
             .connect()
@@ -248,6 +293,16 @@ impl ApiClient {
         output_json(response)
     }
 
+    pub async fn get_fee_estimator_health(&self) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::GetFeeEstimatorHealthRequest {});
+        let response = self
+            .connect()
+            .await?
+            .get_fee_estimator_health(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
     pub async fn new_address(
         &self,
         wallet: String,
@@ -351,7 +406,13 @@ impl ApiClient {
         manual_trigger: Option<bool>,
         cpfp_payouts_after_mins: Option<u32>,
         cpfp_payouts_after_blocks: Option<u32>,
+        rbf_after_mins: Option<u32>,
+        rbf_after_blocks: Option<u32>,
+        add_payouts_to_unconfirmed_batch: bool,
         force_min_change_sats: Option<u64>,
+        min_fee_rate: Option<f64>,
+        max_fee_rate: Option<f64>,
+        defer_above_max_fee_rate: bool,
     ) -> anyhow::Result<()> {
         let tx_priority = match tx_priority {
             TxPriority::NextBlock => proto::TxPriority::NextBlock as i32,
@@ -377,7 +438,13 @@ impl ApiClient {
             trigger,
             cpfp_payouts_after_mins,
             cpfp_payouts_after_blocks,
+            rbf_after_mins,
+            rbf_after_blocks,
+            add_payouts_to_unconfirmed_batch,
             force_min_change_sats,
+            min_fee_rate,
+            max_fee_rate,
+            defer_above_max_fee_rate,
         };
 
         let request = tonic::Request::new(proto::CreatePayoutQueueRequest {
@@ -475,6 +542,25 @@ impl ApiClient {
         output_json(response)
     }
 
+    pub async fn rescan_wallet(
+        &self,
+        wallet_name: String,
+        birthday_height: Option<u32>,
+        gap_limit: Option<u32>,
+    ) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::RescanWalletRequest {
+            wallet_name,
+            birthday_height,
+            gap_limit,
+        });
+        let response = self
+            .connect()
+            .await?
+            .rescan_wallet(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
     pub async fn list_payouts(
         &self,
         wallet: String,
@@ -579,7 +665,13 @@ impl ApiClient {
         interval_trigger: Option<u32>,
         cpfp_payouts_after_mins: Option<u32>,
         cpfp_payouts_after_blocks: Option<u32>,
+        rbf_after_mins: Option<u32>,
+        rbf_after_blocks: Option<u32>,
+        add_payouts_to_unconfirmed_batch: Option<bool>,
         force_min_change_sats: Option<u64>,
+        min_fee_rate: Option<f64>,
+        max_fee_rate: Option<f64>,
+        defer_above_max_fee_rate: Option<bool>,
     ) -> anyhow::Result<()> {
         let tx_priority = tx_priority.map(|priority| match priority {
             TxPriority::NextBlock => proto::TxPriority::NextBlock as i32,
@@ -598,7 +690,13 @@ impl ApiClient {
                 trigger,
                 cpfp_payouts_after_mins,
                 cpfp_payouts_after_blocks,
+                rbf_after_mins,
+                rbf_after_blocks,
+                add_payouts_to_unconfirmed_batch: add_payouts_to_unconfirmed_batch.unwrap_or(false),
                 force_min_change_sats,
+                min_fee_rate,
+                max_fee_rate,
+                defer_above_max_fee_rate: defer_above_max_fee_rate.unwrap_or(false),
             })
         } else {
             None
@@ -626,6 +724,85 @@ impl ApiClient {
         output_json(response)
     }
 
+    pub async fn bump_batch_fee(
+        &self,
+        batch_id: String,
+        sats_per_vbyte: Option<f32>,
+    ) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::BumpBatchFeeRequest {
+            batch_id,
+            sats_per_vbyte,
+        });
+        let response = self
+            .connect()
+            .await?
+            .bump_batch_fee(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
+    pub async fn sweep_with_recovery_key(
+        &self,
+        wallet_name: String,
+        destination: String,
+        sats_per_vbyte: Option<f32>,
+    ) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::SweepWithRecoveryKeyRequest {
+            wallet_name,
+            destination,
+            sats_per_vbyte,
+        });
+        let response = self
+            .connect()
+            .await?
+            .sweep_with_recovery_key(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
+    pub async fn release_recovery_sweep(&self, wallet_name: String) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::ReleaseRecoverySweepRequest { wallet_name });
+        let response = self
+            .connect()
+            .await?
+            .release_recovery_sweep(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
+    pub async fn list_signing_sessions(
+        &self,
+        batch_id: Option<String>,
+        xpub_ref: Option<String>,
+        state: Option<String>,
+        after: Option<String>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::ListSigningSessionsRequest {
+            batch_id,
+            xpub_ref,
+            state,
+            after,
+            limit,
+        });
+        let response = self
+            .connect()
+            .await?
+            .list_signing_sessions(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
+    pub async fn get_signing_session(&self, id: String) -> anyhow::Result<()> {
+        let request = tonic::Request::new(proto::GetSigningSessionRequest { id });
+        let response = self
+            .connect()
+            .await?
+            .get_signing_session(self.inject_auth_token(request)?)
+            .await?;
+        output_json(response)
+    }
+
     pub async fn watch_events(
         &self,
         one_shot: bool,
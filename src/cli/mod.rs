@@ -4,6 +4,7 @@ mod api_client;
 mod config;
 mod db;
 mod gen;
+mod psbt_bundle;
 
 use anyhow::Context;
 use clap::{Parser, Subcommand};
@@ -211,6 +212,48 @@ enum Command {
         #[clap(short, long)]
         signed_psbt: String,
     },
+    /// Export all psbts waiting on an xpub as base64, binary and BBQr files
+    ExportUnsignedPsbts {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        xpub_ref: String,
+        /// Directory the psbt files are written to
+        #[clap(short, long)]
+        out_dir: PathBuf,
+        /// Maximum number of characters per BBQr frame, including the 8 character header
+        #[clap(short, long, default_value = "400")]
+        frame_size: usize,
+        /// Maximum number of psbt bytes per UR part
+        #[clap(long, default_value = "200")]
+        ur_fragment_len: usize,
+    },
+    /// Submit signed psbts exported via export-unsigned-psbts in bulk
+    SubmitSignedPsbts {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        xpub_ref: String,
+        /// Signed psbt files (base64, binary, BBQr frames or UR parts)
+        #[clap(required = true, num_args(1..))]
+        files: Vec<PathBuf>,
+    },
     /// Create a wallet from imported xpubs
     CreateWallet {
         #[clap(
@@ -225,6 +268,9 @@ enum Command {
         api_key: String,
         #[clap(short, long)]
         name: String,
+        /// Height of the first block that could contain activity of the wallet
+        #[clap(long)]
+        birthday_height: Option<u32>,
         #[clap(subcommand)]
         command: CreateWalletCommand,
     },
@@ -257,6 +303,20 @@ enum Command {
         api_key: String,
     },
 
+    /// Success and failure counts of the daemon's fee estimators
+    FeeEstimatorHealth {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+    },
+
     /// Get a new address for a wallet
     NewAddress {
         #[clap(
@@ -373,8 +433,23 @@ enum Command {
         cpfp_payouts_after_mins: Option<u32>,
         #[clap(long = "cpfp-after-blocks")]
         cpfp_payouts_after_blocks: Option<u32>,
+        #[clap(long = "rbf-after-mins")]
+        rbf_after_mins: Option<u32>,
+        #[clap(long = "rbf-after-blocks")]
+        rbf_after_blocks: Option<u32>,
+        #[clap(long = "add-to-unconfirmed-batch", default_value = "false")]
+        add_payouts_to_unconfirmed_batch: bool,
         #[clap(long)]
         min_change: Option<u64>,
+        /// Lower bound for the fee rate in sats/vbyte
+        #[clap(long)]
+        min_fee_rate: Option<f64>,
+        /// Upper bound for the fee rate in sats/vbyte
+        #[clap(long)]
+        max_fee_rate: Option<f64>,
+        /// Defer batches while the fee rate is above --max-fee-rate instead of capping it
+        #[clap(long, default_value = "false")]
+        defer_above_max_fee_rate: bool,
     },
     /// Trigger Payout Queue
     TriggerPayoutQueue {
@@ -492,6 +567,27 @@ enum Command {
         #[clap(env = "BRIA_API_KEY", default_value = "")]
         api_key: String,
     },
+    /// Rescan the history of a wallet, eg. after importing descriptors that were used elsewhere
+    RescanWallet {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        wallet: String,
+        /// Height of the first block that could contain activity of the wallet
+        #[clap(long)]
+        birthday_height: Option<u32>,
+        /// Number of consecutive unused addresses after which the scan stops
+        #[clap(long)]
+        gap_limit: Option<u32>,
+    },
 
     /// List Payout Queue
     ListPayoutQueues {
@@ -534,8 +630,23 @@ enum Command {
         cpfp_payouts_after_mins: Option<u32>,
         #[clap(long = "cpfp-after-blocks")]
         cpfp_payouts_after_blocks: Option<u32>,
+        #[clap(long = "rbf-after-mins")]
+        rbf_after_mins: Option<u32>,
+        #[clap(long = "rbf-after-blocks")]
+        rbf_after_blocks: Option<u32>,
+        #[clap(long = "add-to-unconfirmed-batch")]
+        add_payouts_to_unconfirmed_batch: Option<bool>,
         #[clap(long)]
         min_change: Option<u64>,
+        /// Lower bound for the fee rate in sats/vbyte
+        #[clap(long)]
+        min_fee_rate: Option<f64>,
+        /// Upper bound for the fee rate in sats/vbyte
+        #[clap(long)]
+        max_fee_rate: Option<f64>,
+        /// Defer batches while the fee rate is above --max-fee-rate instead of capping it
+        #[clap(long)]
+        defer_above_max_fee_rate: Option<bool>,
     },
     /// Get Batch details
     GetBatch {
@@ -552,6 +663,101 @@ enum Command {
         #[clap(short, long)]
         batch_id: String,
     },
+    /// Replace a stuck batch with one paying a higher fee
+    BumpBatchFee {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        batch_id: String,
+        /// Fee rate of the replacement (defaults to the payout queue's priority estimate)
+        #[clap(short, long)]
+        sats_per_vbyte: Option<f32>,
+    },
+    /// Build psbts (one per keychain) sweeping the utxos whose recovery timelock has passed
+    SweepWithRecoveryKey {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        wallet: String,
+        #[clap(short, long)]
+        destination: String,
+        /// Fee rate of the sweep (defaults to the next block estimate)
+        #[clap(short, long)]
+        sats_per_vbyte: Option<f32>,
+    },
+    /// Make the utxos reserved by an unbroadcast recovery sweep spendable by batches again
+    ReleaseRecoverySweep {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        wallet: String,
+    },
+    /// List signing sessions filtered by batch, xpub or state
+    ListSigningSessions {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        batch_id: Option<String>,
+        /// The xpub-ref of the cosigner
+        #[clap(short, long)]
+        xpub: Option<String>,
+        /// One of initialized, failed, complete, no_longer_needed or cancelled
+        #[clap(short, long)]
+        state: Option<String>,
+        /// The end_cursor of the previous page
+        #[clap(short, long)]
+        after: Option<String>,
+        /// Maximum number of sessions to return
+        #[clap(short, long)]
+        limit: Option<u32>,
+    },
+    /// Get the details of a signing session
+    GetSigningSession {
+        #[clap(
+            short,
+            long,
+            value_parser,
+            default_value = "http://localhost:2742",
+            env = "BRIA_API_URL"
+        )]
+        url: Option<Url>,
+        #[clap(env = "BRIA_API_KEY", default_value = "")]
+        api_key: String,
+        #[clap(short, long)]
+        id: String,
+    },
     /// Watch or fetch events
     WatchEvents {
         #[clap(
@@ -651,6 +857,35 @@ enum CreateWalletCommand {
         #[clap(short, long)]
         threshold: u32,
     },
+    /// Initialize the wallet via taproot (single key, key-path spend)
+    Taproot {
+        /// The xpub-ref or xpub to use
+        #[clap(short, long)]
+        xpub: String,
+        /// If an xpub is being imported, the derivation path to use
+        #[clap(short, long)]
+        derivation: Option<String>,
+    },
+    /// Initialize the wallet via taproot script-path multisig (multi_a)
+    TaprootMultisig {
+        #[clap(short, long, num_args(2..=15) )]
+        xpub: Vec<String>,
+        #[clap(short, long)]
+        threshold: u32,
+    },
+    /// Initialize the wallet via multisig with a relative-timelocked recovery key
+    SortedMultisigWithRecovery {
+        #[clap(short, long, num_args(2..=15) )]
+        xpub: Vec<String>,
+        #[clap(short, long)]
+        threshold: u32,
+        /// The xpub-ref or xpub that can spend alone once the timelock has passed
+        #[clap(short, long)]
+        recovery_xpub: String,
+        /// Number of confirmations before the recovery key can spend a utxo
+        #[clap(short = 'b', long)]
+        recovery_after_blocks: u32,
+    },
 }
 
 #[derive(Subcommand)]
@@ -671,6 +906,14 @@ enum SetSignerConfigCommand {
         #[clap(short = 'p', long)]
         rpc_password: String,
     },
+    /// Sign by POSTing the psbt to an https endpoint
+    Webhook {
+        #[clap(short, long)]
+        endpoint: String,
+        /// Secret used to sign `<x-bria-timestamp>.<body>` (sent as x-bria-signature)
+        #[clap(short = 's', long)]
+        hmac_secret: String,
+    },
 }
 
 pub async fn run() -> anyhow::Result<()> {
@@ -824,14 +1067,37 @@ pub async fn run() -> anyhow::Result<()> {
                 .submit_signed_psbt(batch_id, xpub_ref, signed_psbt)
                 .await?;
         }
+        Command::ExportUnsignedPsbts {
+            url,
+            api_key,
+            xpub_ref,
+            out_dir,
+            frame_size,
+            ur_fragment_len,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client
+                .export_unsigned_psbts(xpub_ref, out_dir, frame_size, ur_fragment_len)
+                .await?;
+        }
+        Command::SubmitSignedPsbts {
+            url,
+            api_key,
+            xpub_ref,
+            files,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client.submit_signed_psbts(xpub_ref, files).await?;
+        }
         Command::CreateWallet {
             url,
             api_key,
             name,
+            birthday_height,
             command,
         } => {
             let client = api_client(cli.bria_home, url, api_key);
-            client.create_wallet(name, command).await?;
+            client.create_wallet(name, command, birthday_height).await?;
         }
         Command::WalletBalance {
             url,
@@ -845,6 +1111,10 @@ pub async fn run() -> anyhow::Result<()> {
             let client = api_client(cli.bria_home, url, api_key);
             client.get_account_balance_summary().await?;
         }
+        Command::FeeEstimatorHealth { url, api_key } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client.get_fee_estimator_health().await?;
+        }
         Command::NewAddress {
             url,
             api_key,
@@ -903,7 +1173,13 @@ pub async fn run() -> anyhow::Result<()> {
             manual_trigger,
             cpfp_payouts_after_mins,
             cpfp_payouts_after_blocks,
+            rbf_after_mins,
+            rbf_after_blocks,
+            add_payouts_to_unconfirmed_batch,
             min_change,
+            min_fee_rate,
+            max_fee_rate,
+            defer_above_max_fee_rate,
         } => {
             let client = api_client(cli.bria_home, url, api_key);
             client
@@ -916,7 +1192,13 @@ pub async fn run() -> anyhow::Result<()> {
                     manual_trigger,
                     cpfp_payouts_after_mins,
                     cpfp_payouts_after_blocks,
+                    rbf_after_mins,
+                    rbf_after_blocks,
+                    add_payouts_to_unconfirmed_batch,
                     min_change,
+                    min_fee_rate,
+                    max_fee_rate,
+                    defer_above_max_fee_rate,
                 )
                 .await?;
         }
@@ -986,6 +1268,18 @@ pub async fn run() -> anyhow::Result<()> {
             let client = api_client(cli.bria_home, url, api_key);
             client.list_wallets().await?;
         }
+        Command::RescanWallet {
+            url,
+            api_key,
+            wallet,
+            birthday_height,
+            gap_limit,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client
+                .rescan_wallet(wallet, birthday_height, gap_limit)
+                .await?;
+        }
         Command::ListPayoutQueues { url, api_key } => {
             let client = api_client(cli.bria_home, url, api_key);
             client.list_payout_queues().await?;
@@ -1000,7 +1294,13 @@ pub async fn run() -> anyhow::Result<()> {
             interval_trigger,
             cpfp_payouts_after_mins,
             cpfp_payouts_after_blocks,
+            rbf_after_mins,
+            rbf_after_blocks,
+            add_payouts_to_unconfirmed_batch,
             min_change,
+            min_fee_rate,
+            max_fee_rate,
+            defer_above_max_fee_rate,
         } => {
             let client = api_client(cli.bria_home, url, api_key);
             client
@@ -1012,7 +1312,13 @@ pub async fn run() -> anyhow::Result<()> {
                     interval_trigger,
                     cpfp_payouts_after_mins,
                     cpfp_payouts_after_blocks,
+                    rbf_after_mins,
+                    rbf_after_blocks,
+                    add_payouts_to_unconfirmed_batch,
                     min_change,
+                    min_fee_rate,
+                    max_fee_rate,
+                    defer_above_max_fee_rate,
                 )
                 .await?;
         }
@@ -1028,6 +1334,53 @@ pub async fn run() -> anyhow::Result<()> {
             let client = api_client(cli.bria_home, url, api_key);
             client.get_batch(batch_id).await?;
         }
+        Command::BumpBatchFee {
+            url,
+            api_key,
+            batch_id,
+            sats_per_vbyte,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client.bump_batch_fee(batch_id, sats_per_vbyte).await?;
+        }
+        Command::SweepWithRecoveryKey {
+            url,
+            api_key,
+            wallet,
+            destination,
+            sats_per_vbyte,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client
+                .sweep_with_recovery_key(wallet, destination, sats_per_vbyte)
+                .await?;
+        }
+        Command::ReleaseRecoverySweep {
+            url,
+            api_key,
+            wallet,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client.release_recovery_sweep(wallet).await?;
+        }
+        Command::ListSigningSessions {
+            url,
+            api_key,
+            batch_id,
+            xpub,
+            state,
+            after,
+            limit,
+        } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client
+                .list_signing_sessions(batch_id, xpub, state, after, limit)
+                .await?;
+        }
+        Command::GetSigningSession { url, api_key, id } => {
+            let client = api_client(cli.bria_home, url, api_key);
+            client.get_signing_session(id).await?;
+        }
         Command::WatchEvents {
             url,
             api_key,
@@ -1150,6 +1503,13 @@ impl TryFrom<SetSignerConfigCommand> for crate::api::proto::set_signer_config_re
                 rpc_user,
                 rpc_password,
             }),
+            SetSignerConfigCommand::Webhook {
+                endpoint,
+                hmac_secret,
+            } => Config::Webhook(crate::api::proto::WebhookSignerConfig {
+                endpoint,
+                hmac_secret,
+            }),
         };
         Ok(ret)
     }
@@ -1176,6 +1536,27 @@ impl From<CreateWalletCommand> for crate::api::proto::keychain_config::Config {
                     threshold,
                 })
             }
+            CreateWalletCommand::Taproot { xpub, derivation } => Config::Taproot(Taproot {
+                xpub,
+                derivation_path: derivation,
+            }),
+            CreateWalletCommand::TaprootMultisig { xpub, threshold } => {
+                Config::TaprootMultisig(TaprootMultisig {
+                    xpubs: xpub,
+                    threshold,
+                })
+            }
+            CreateWalletCommand::SortedMultisigWithRecovery {
+                xpub,
+                threshold,
+                recovery_xpub,
+                recovery_after_blocks,
+            } => Config::SortedMultisigWithRecovery(SortedMultisigWithRecovery {
+                xpubs: xpub,
+                threshold,
+                recovery_xpub,
+                recovery_after_blocks,
+            }),
         }
     }
 }
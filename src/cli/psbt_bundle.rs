@@ -0,0 +1,426 @@
+use anyhow::Context;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::{api::proto, primitives::bitcoin::psbt::PartiallySignedTransaction};
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const BBQR_HEADER: &str = "B$";
+const BBQR_HEX_ENCODING: char = 'H';
+const BBQR_PSBT_FILE_TYPE: char = 'P';
+const BBQR_HEADER_LEN: usize = 8;
+const BBQR_MAX_PARTS: usize = 36 * 36 - 1;
+const UR_PSBT_PREFIX: &str = "ur:crypto-psbt/";
+const UR_MIN_FRAGMENT_LEN: usize = 10;
+const CBOR_UINT: u8 = 0;
+const CBOR_BYTES: u8 = 2;
+const CBOR_ARRAY: u8 = 4;
+/// BCR-2020-012 bytewords, the minimal encoding uses the first and last letter.
+const BYTEWORDS: &str = "\
+    able acid also apex aqua arch atom aunt away axis back bald barn belt beta bias \
+    blue body brag brew bulb buzz calm cash cats chef city claw code cola cook cost \
+    crux curl cusp cyan dark data days deli dice diet door down draw drop drum dull \
+    duty each easy echo edge epic even exam exit eyes fact fair fern figs film fish \
+    fizz flap flew flux foxy free frog fuel fund gala game gear gems gift girl glow \
+    good gray grim guru gush gyro half hang hard hawk heat help high hill holy hope \
+    horn huts iced idea idle inch inky into iris iron item jade jazz join jolt jowl \
+    judo jugs jump junk jury keep keno kept keys kick kiln king kite kiwi knob lamb \
+    lava lazy leaf legs liar limp lion list logo loud love luau luck lung main many \
+    math maze memo menu meow mild mint miss monk nail navy need news next noon note \
+    numb obey oboe omit onyx open oval owls paid part peck play plus poem pool pose \
+    puff puma purr quad quiz race ramp real redo rich road rock roof ruby ruin runs \
+    rust safe saga scar sets silk skew slot soap solo song stub surf swan taco task \
+    taxi tent tied time tiny toil tomb toys trip tuna twin ugly undo unit urge user \
+    vast very veto vial vibe view visa void vows wall wand warm wasp wave waxy webs \
+    what when whiz wolf work yank yawn yell yoga yurt zaps zero zest zinc zone zoom";
+
+#[derive(Serialize)]
+pub struct ExportedPsbt {
+    batch_id: String,
+    signing_session_id: String,
+    tx_id: String,
+    base64: PathBuf,
+    binary: PathBuf,
+    bbqr: PathBuf,
+    bbqr_frames: usize,
+    ur: PathBuf,
+    ur_parts: usize,
+}
+
+/// Writes every psbt as `<batch_id>.b64.txt`, `<batch_id>.psbt`,
+/// `<batch_id>.bbqr.txt` (one BBQr frame per line) and `<batch_id>.ur.txt`
+/// (one `crypto-psbt` UR part per line) into `dir`.
+pub fn write_bundle(
+    dir: impl AsRef<Path>,
+    psbts: Vec<proto::UnsignedPsbt>,
+    frame_size: usize,
+    ur_fragment_len: usize,
+) -> anyhow::Result<Vec<ExportedPsbt>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir).context("Couldn't create export directory")?;
+    let mut exported = Vec::new();
+    for psbt in psbts {
+        let parsed = psbt
+            .unsigned_psbt
+            .parse::<PartiallySignedTransaction>()
+            .context("Couldn't parse psbt")?;
+        let base64 = dir.join(format!("{}.b64.txt", psbt.batch_id));
+        std::fs::write(&base64, &psbt.unsigned_psbt).context("Couldn't write base64 psbt")?;
+        let bytes = parsed.serialize();
+        let binary = dir.join(format!("{}.psbt", psbt.batch_id));
+        std::fs::write(&binary, &bytes).context("Couldn't write binary psbt")?;
+        let frames = bbqr_encode(&bytes, frame_size)?;
+        let bbqr = dir.join(format!("{}.bbqr.txt", psbt.batch_id));
+        std::fs::write(&bbqr, frames.join("\n")).context("Couldn't write bbqr frames")?;
+        let parts = ur_encode(&bytes, ur_fragment_len)?;
+        let ur = dir.join(format!("{}.ur.txt", psbt.batch_id));
+        std::fs::write(&ur, parts.join("\n")).context("Couldn't write ur parts")?;
+        exported.push(ExportedPsbt {
+            batch_id: psbt.batch_id,
+            signing_session_id: psbt.signing_session_id,
+            tx_id: psbt.tx_id,
+            base64,
+            binary,
+            bbqr,
+            bbqr_frames: frames.len(),
+            ur,
+            ur_parts: parts.len(),
+        });
+    }
+    Ok(exported)
+}
+
+/// Reads a signed psbt in any of the formats produced by `write_bundle`.
+pub fn read_psbt(path: impl AsRef<Path>) -> anyhow::Result<PartiallySignedTransaction> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).with_context(|| format!("Couldn't read {}", path.display()))?;
+    if bytes.starts_with(PSBT_MAGIC) {
+        return PartiallySignedTransaction::deserialize(&bytes)
+            .context("Couldn't parse binary psbt");
+    }
+    let text = String::from_utf8(bytes).context("Psbt file is neither binary nor text")?;
+    let text = text.trim();
+    if text.starts_with(BBQR_HEADER) {
+        let bytes = bbqr_decode(text.lines())?;
+        return PartiallySignedTransaction::deserialize(&bytes).context("Couldn't parse bbqr psbt");
+    }
+    if text
+        .get(..3)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ur:"))
+    {
+        let bytes = ur_decode(text.lines())?;
+        return PartiallySignedTransaction::deserialize(&bytes).context("Couldn't parse ur psbt");
+    }
+    text.parse::<PartiallySignedTransaction>()
+        .context("Couldn't parse base64 psbt")
+}
+
+/// Splits `bytes` into frames of at most `frame_size` characters including the
+/// 8 character header.
+fn bbqr_encode(bytes: &[u8], frame_size: usize) -> anyhow::Result<Vec<String>> {
+    if frame_size < BBQR_HEADER_LEN + 2 {
+        anyhow::bail!(
+            "Bbqr frame size must be at least {} characters",
+            BBQR_HEADER_LEN + 2
+        );
+    }
+    // hex encoded parts must contain whole bytes
+    let chunk_size = frame_size - BBQR_HEADER_LEN;
+    let chunk_size = chunk_size - chunk_size % 2;
+    let data = hex::encode_upper(bytes);
+    let chunks: Vec<_> = data.as_bytes().chunks(chunk_size).collect();
+    if chunks.len() > BBQR_MAX_PARTS {
+        anyhow::bail!("Psbt is too large for the given bbqr frame size");
+    }
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(idx, chunk)| {
+            format!(
+                "{}{}{}{}{}{}",
+                BBQR_HEADER,
+                BBQR_HEX_ENCODING,
+                BBQR_PSBT_FILE_TYPE,
+                to_base36(chunks.len()),
+                to_base36(idx),
+                std::str::from_utf8(chunk).expect("hex is always utf8")
+            )
+        })
+        .collect())
+}
+
+fn bbqr_decode<'a>(frames: impl Iterator<Item = &'a str>) -> anyhow::Result<Vec<u8>> {
+    let mut total = None;
+    let mut parts = Vec::new();
+    for frame in frames.map(str::trim).filter(|f| !f.is_empty()) {
+        if frame.len() < BBQR_HEADER_LEN || !frame.is_ascii() || !frame.starts_with(BBQR_HEADER) {
+            anyhow::bail!("Invalid bbqr frame");
+        }
+        let mut header = frame[2..4].chars();
+        if header.next() != Some(BBQR_HEX_ENCODING) {
+            anyhow::bail!("Only hex encoded bbqr frames are supported");
+        }
+        if header.next() != Some(BBQR_PSBT_FILE_TYPE) {
+            anyhow::bail!("Bbqr frames don't contain a psbt");
+        }
+        let frame_total = usize::from_str_radix(&frame[4..6], 36)?;
+        if *total.get_or_insert(frame_total) != frame_total {
+            anyhow::bail!("Bbqr frames belong to different files");
+        }
+        let idx = usize::from_str_radix(&frame[6..8], 36)?;
+        parts.push((idx, &frame[BBQR_HEADER_LEN..]));
+    }
+    parts.sort_by_key(|(idx, _)| *idx);
+    parts.dedup_by_key(|(idx, _)| *idx);
+    if Some(parts.len()) != total {
+        anyhow::bail!("Missing bbqr frames");
+    }
+    let data: String = parts.into_iter().map(|(_, part)| part).collect();
+    Ok(hex::decode(data)?)
+}
+
+fn to_base36(n: usize) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    format!("{}{}", DIGITS[n / 36] as char, DIGITS[n % 36] as char)
+}
+
+/// Encodes `bytes` as `crypto-psbt` URs (BCR-2020-005) whose fragments carry at most
+/// `max_fragment_len` bytes. Multi part URs only contain the plain fragments
+/// (sequence numbers up to the part count), no fountain-mixed parts.
+fn ur_encode(bytes: &[u8], max_fragment_len: usize) -> anyhow::Result<Vec<String>> {
+    if max_fragment_len < UR_MIN_FRAGMENT_LEN {
+        anyhow::bail!("Ur fragments must be at least {UR_MIN_FRAGMENT_LEN} bytes");
+    }
+    let mut message = Vec::new();
+    cbor_write(&mut message, CBOR_BYTES, bytes.len() as u64);
+    message.extend_from_slice(bytes);
+    let fragment_len = (1..=(message.len() / UR_MIN_FRAGMENT_LEN).max(1))
+        .map(|count| message.len().div_ceil(count))
+        .find(|len| *len <= max_fragment_len)
+        .unwrap_or(max_fragment_len);
+    let seq_len = message.len().div_ceil(fragment_len);
+    if seq_len == 1 {
+        return Ok(vec![format!(
+            "{UR_PSBT_PREFIX}{}",
+            bytewords_encode(&message)
+        )]);
+    }
+    let checksum = crc32fast::hash(&message);
+    Ok(message
+        .chunks(fragment_len)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let mut part = Vec::new();
+            cbor_write(&mut part, CBOR_ARRAY, 5);
+            cbor_write(&mut part, CBOR_UINT, idx as u64 + 1);
+            cbor_write(&mut part, CBOR_UINT, seq_len as u64);
+            cbor_write(&mut part, CBOR_UINT, message.len() as u64);
+            cbor_write(&mut part, CBOR_UINT, u64::from(checksum));
+            cbor_write(&mut part, CBOR_BYTES, fragment_len as u64);
+            part.extend_from_slice(chunk);
+            // the last fragment is zero padded to the nominal length
+            part.resize(part.len() + fragment_len - chunk.len(), 0);
+            format!(
+                "{UR_PSBT_PREFIX}{}-{}/{}",
+                idx + 1,
+                seq_len,
+                bytewords_encode(&part)
+            )
+        })
+        .collect())
+}
+
+/// Decodes single part or multi part `crypto-psbt` URs. Fountain-mixed parts are
+/// ignored so every plain fragment must be present.
+fn ur_decode<'a>(parts: impl Iterator<Item = &'a str>) -> anyhow::Result<Vec<u8>> {
+    let mut header = None;
+    let mut fragments = Vec::new();
+    for part in parts.map(str::trim).filter(|p| !p.is_empty()) {
+        let part = part.to_ascii_lowercase();
+        let body = part
+            .strip_prefix(UR_PSBT_PREFIX)
+            .context("Ur doesn't contain a psbt")?;
+        let Some((seq, data)) = body.split_once('/') else {
+            return cbor_bytes(&bytewords_decode(body)?);
+        };
+        let (seq_num, seq_len) = seq.split_once('-').context("Invalid ur sequence")?;
+        if seq_num.parse::<usize>()? > seq_len.parse::<usize>()? {
+            continue;
+        }
+        let data = bytewords_decode(data)?;
+        let mut data = data.as_slice();
+        if cbor_read(&mut data, CBOR_ARRAY)? != 5 {
+            anyhow::bail!("Invalid ur part");
+        }
+        let seq_num = cbor_read(&mut data, CBOR_UINT)?;
+        let part_header = (
+            cbor_read(&mut data, CBOR_UINT)?,
+            cbor_read(&mut data, CBOR_UINT)?,
+            cbor_read(&mut data, CBOR_UINT)?,
+        );
+        if *header.get_or_insert(part_header) != part_header {
+            anyhow::bail!("Ur parts belong to different messages");
+        }
+        fragments.push((seq_num, cbor_bytes(data)?));
+    }
+    let (seq_len, message_len, checksum) = header.context("Missing ur parts")?;
+    fragments.sort_by_key(|(seq_num, _)| *seq_num);
+    fragments.dedup_by_key(|(seq_num, _)| *seq_num);
+    if fragments.len() as u64 != seq_len {
+        anyhow::bail!("Missing ur parts");
+    }
+    let mut message: Vec<u8> = fragments.into_iter().flat_map(|(_, f)| f).collect();
+    message.truncate(message_len as usize);
+    if u64::from(crc32fast::hash(&message)) != checksum {
+        anyhow::bail!("Invalid ur checksum");
+    }
+    cbor_bytes(&message)
+}
+
+fn bytewords_encode(bytes: &[u8]) -> String {
+    let words = BYTEWORDS.as_bytes();
+    let checksum = crc32fast::hash(bytes).to_be_bytes();
+    bytes
+        .iter()
+        .chain(checksum.iter())
+        .flat_map(|byte| {
+            let word = &words[*byte as usize * 5..];
+            [word[0] as char, word[3] as char]
+        })
+        .collect()
+}
+
+fn bytewords_decode(data: &str) -> anyhow::Result<Vec<u8>> {
+    let words = BYTEWORDS.as_bytes();
+    let bytes = data
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            (0..256)
+                .find(|idx| {
+                    pair.len() == 2 && words[idx * 5] == pair[0] && words[idx * 5 + 3] == pair[1]
+                })
+                .map(|idx| idx as u8)
+                .context("Invalid bytewords")
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if bytes.len() < 4 {
+        anyhow::bail!("Invalid bytewords");
+    }
+    let (bytes, checksum) = bytes.split_at(bytes.len() - 4);
+    if crc32fast::hash(bytes).to_be_bytes() != checksum {
+        anyhow::bail!("Invalid bytewords checksum");
+    }
+    Ok(bytes.to_vec())
+}
+
+fn cbor_write(buf: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => buf.push(major | value as u8),
+        24..=0xff => buf.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            buf.push(major | 25);
+            buf.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            buf.push(major | 26);
+            buf.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(major | 27);
+            buf.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn cbor_read(data: &mut &[u8], major: u8) -> anyhow::Result<u64> {
+    let (&initial, rest) = data.split_first().context("Truncated cbor")?;
+    if initial >> 5 != major {
+        anyhow::bail!("Unexpected cbor type");
+    }
+    let len = match initial & 0x1f {
+        value @ 0..=23 => {
+            *data = rest;
+            return Ok(u64::from(value));
+        }
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => anyhow::bail!("Unsupported cbor encoding"),
+    };
+    if rest.len() < len {
+        anyhow::bail!("Truncated cbor");
+    }
+    let value = rest[..len]
+        .iter()
+        .fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+    *data = &rest[len..];
+    Ok(value)
+}
+
+/// Reads a cbor byte string that spans all of `data`.
+fn cbor_bytes(mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let len = cbor_read(&mut data, CBOR_BYTES)?;
+    if data.len() as u64 != len {
+        anyhow::bail!("Invalid cbor byte string");
+    }
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bbqr_roundtrip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let frames = bbqr_encode(&bytes, 109).unwrap();
+        assert_eq!(frames.len(), 6);
+        assert!(frames.iter().all(|frame| frame.len() <= 109));
+        assert!(frames[0].starts_with("B$HP0600"));
+        assert!(frames[5].starts_with("B$HP0605"));
+        let decoded = bbqr_decode(frames.iter().rev().map(String::as_str)).unwrap();
+        assert_eq!(decoded, bytes);
+    }
+
+    #[test]
+    fn bbqr_decode_detects_missing_frames() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let frames = bbqr_encode(&bytes, 100).unwrap();
+        assert!(bbqr_decode(frames.iter().skip(1).map(String::as_str)).is_err());
+    }
+
+    #[test]
+    fn bbqr_encode_rejects_frames_without_room_for_data() {
+        assert!(bbqr_encode(&[0], 9).is_err());
+        assert_eq!(bbqr_encode(&[0], 10).unwrap(), vec!["B$HP010000"]);
+    }
+
+    #[test]
+    fn bytewords_minimal_encoding() {
+        assert_eq!(bytewords_encode(&[0, 1, 2, 128, 255]), "aeadaolazmjendeoti");
+        assert_eq!(
+            bytewords_decode("aeadaolazmjendeoti").unwrap(),
+            vec![0, 1, 2, 128, 255]
+        );
+        assert!(bytewords_decode("aeadaolazmjendeota").is_err());
+    }
+
+    #[test]
+    fn ur_roundtrip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let single = ur_encode(&bytes, 300).unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(single[0].starts_with("ur:crypto-psbt/hkadae"));
+        assert_eq!(ur_decode(single.iter().map(String::as_str)).unwrap(), bytes);
+
+        let parts = ur_encode(&bytes, 100).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("ur:crypto-psbt/1-3/"));
+        let upper: Vec<_> = parts.iter().rev().map(|p| p.to_uppercase()).collect();
+        assert_eq!(ur_decode(upper.iter().map(String::as_str)).unwrap(), bytes);
+        assert!(ur_decode(parts.iter().skip(1).map(String::as_str)).is_err());
+    }
+}
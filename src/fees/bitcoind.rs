@@ -0,0 +1,46 @@
+use bdk::FeeRate;
+use bitcoincore_rpc::{json::EstimateMode, RpcApi};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::error::FeeEstimationError;
+use crate::primitives::TxPriority;
+
+#[derive(Clone, Debug)]
+pub struct BitcoindFeesClient {
+    config: BitcoindFeesConfig,
+}
+
+impl BitcoindFeesClient {
+    pub fn new(config: BitcoindFeesConfig) -> Self {
+        Self { config }
+    }
+
+    #[instrument(name = "bitcoind.fee_rate", skip(self), ret, err)]
+    pub async fn fee_rate(&self, priority: TxPriority) -> Result<FeeRate, FeeEstimationError> {
+        let config = self.config.clone();
+        let estimate = tokio::task::spawn_blocking(move || {
+            let client = bitcoincore_rpc::Client::new(
+                &config.rpc_url,
+                bitcoincore_rpc::Auth::UserPass(config.rpc_user, config.rpc_password),
+            )?;
+            client.estimate_smart_fee(priority.n_blocks() as u16, Some(EstimateMode::Conservative))
+        })
+        .await
+        .expect("estimatesmartfee task panicked")?;
+        match estimate.fee_rate {
+            // estimatesmartfee returns BTC/kvB
+            Some(fee_rate) => Ok(FeeRate::from_sat_per_vb(fee_rate.to_sat() as f32 / 1000.0)),
+            None => Err(FeeEstimationError::NoEstimateAvailable(
+                estimate.errors.unwrap_or_default().join(", "),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoindFeesConfig {
+    pub rpc_url: String,
+    pub rpc_user: String,
+    pub rpc_password: String,
+}
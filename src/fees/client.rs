@@ -1,34 +1,218 @@
 use bdk::FeeRate;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
 use tracing::instrument;
 
-use super::{blockstream::*, config::*, error::*, mempool_space::*};
+use super::{bitcoind::*, blockstream::*, config::*, error::*, mempool_space::*, static_rates::*};
 use crate::primitives::TxPriority;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimatorHealth {
+    pub estimator: FeeEstimatorKind,
+    pub successes: u64,
+    pub failures: u64,
+    pub implausible_estimates: u64,
+    pub consecutive_failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct EstimatorStats {
+    successes: AtomicU64,
+    failures: AtomicU64,
+    implausible_estimates: AtomicU64,
+    consecutive_failures: AtomicU64,
+}
+
+impl EstimatorStats {
+    fn record_success(&self) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, err: &FeeEstimationError) {
+        if matches!(err, FeeEstimationError::ImplausibleFeeRate(_)) {
+            self.implausible_estimates.fetch_add(1, Ordering::Relaxed);
+        }
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FeesClient {
+    estimators: Vec<(FeeEstimatorKind, Arc<EstimatorStats>)>,
+    bitcoind: Option<BitcoindFeesClient>,
     mempool_space: MempoolSpaceClient,
     blockstream: BlockstreamClient,
+    static_rates: StaticFeeRatesConfig,
+    max_plausible_fee_rate: f32,
+    cache_ttl: Duration,
+    cache: Arc<RwLock<HashMap<TxPriority, (FeeRate, Instant)>>>,
 }
 
 impl FeesClient {
     pub fn new(config: FeesConfig) -> Self {
         Self {
+            estimators: config
+                .estimators
+                .into_iter()
+                .map(|kind| (kind, Arc::new(EstimatorStats::default())))
+                .collect(),
+            bitcoind: config.bitcoind.map(BitcoindFeesClient::new),
             mempool_space: MempoolSpaceClient::new(config.mempool_space),
             blockstream: BlockstreamClient::new(config.blockstream),
+            static_rates: config.static_rates,
+            max_plausible_fee_rate: config.max_plausible_fee_rate,
+            cache_ttl: config.cache_ttl,
+            cache: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    #[instrument(name = "fees.fee_rate", skip(self), fields(fee_rate), err)]
+    #[instrument(name = "fees.fee_rate", skip(self), fields(fee_rate, estimator), err)]
     pub async fn fee_rate(&self, priority: TxPriority) -> Result<FeeRate, FeeEstimationError> {
-        let fee_rate = if let Ok(fee_rate) = self.mempool_space.fee_rate(priority).await {
-            fee_rate
-        } else {
-            self.blockstream.fee_rate(priority).await?
+        if let Some(fee_rate) = self.cached_fee_rate(priority) {
+            tracing::Span::current().record(
+                "fee_rate",
+                tracing::field::display(format!("{:?}", fee_rate)),
+            );
+            tracing::Span::current().record("estimator", "cache");
+            return Ok(fee_rate);
+        }
+        for (kind, stats) in self.estimators.iter() {
+            match self.estimate(*kind, priority).await {
+                Ok(fee_rate) => {
+                    stats.record_success();
+                    self.cache
+                        .write()
+                        .expect("fee rate cache poisoned")
+                        .insert(priority, (fee_rate, Instant::now()));
+                    let span = tracing::Span::current();
+                    span.record(
+                        "fee_rate",
+                        tracing::field::display(format!("{:?}", fee_rate)),
+                    );
+                    span.record("estimator", tracing::field::display(kind));
+                    return Ok(fee_rate);
+                }
+                Err(err) => {
+                    stats.record_failure(&err);
+                    tracing::warn!(estimator = %kind, error = %err, "fee estimator failed");
+                }
+            }
+        }
+        Err(FeeEstimationError::NoEstimatorSucceeded)
+    }
+
+    fn cached_fee_rate(&self, priority: TxPriority) -> Option<FeeRate> {
+        let cache = self.cache.read().expect("fee rate cache poisoned");
+        cache
+            .get(&priority)
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(fee_rate, _)| *fee_rate)
+    }
+
+    pub fn estimator_health(&self) -> Vec<FeeEstimatorHealth> {
+        self.estimators
+            .iter()
+            .map(|(kind, stats)| FeeEstimatorHealth {
+                estimator: *kind,
+                successes: stats.successes.load(Ordering::Relaxed),
+                failures: stats.failures.load(Ordering::Relaxed),
+                implausible_estimates: stats.implausible_estimates.load(Ordering::Relaxed),
+                consecutive_failures: stats.consecutive_failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    async fn estimate(
+        &self,
+        kind: FeeEstimatorKind,
+        priority: TxPriority,
+    ) -> Result<FeeRate, FeeEstimationError> {
+        let fee_rate = match kind {
+            FeeEstimatorKind::Bitcoind => match self.bitcoind {
+                Some(ref bitcoind) => bitcoind.fee_rate(priority).await?,
+                None => return Err(FeeEstimationError::EstimatorNotConfigured(kind.to_string())),
+            },
+            FeeEstimatorKind::MempoolSpace => self.mempool_space.fee_rate(priority).await?,
+            FeeEstimatorKind::Blockstream => self.blockstream.fee_rate(priority).await?,
+            FeeEstimatorKind::Static => self.static_rates.fee_rate(priority),
         };
-        tracing::Span::current().record(
-            "fee_rate",
-            tracing::field::display(format!("{:?}", fee_rate)),
-        );
+        check_plausible(fee_rate, self.max_plausible_fee_rate)
+    }
+}
+
+fn check_plausible(fee_rate: FeeRate, max_plausible: f32) -> Result<FeeRate, FeeEstimationError> {
+    let sats_per_vb = fee_rate.as_sat_per_vb();
+    if sats_per_vb > 0.0 && sats_per_vb <= max_plausible {
         Ok(fee_rate)
+    } else {
+        Err(FeeEstimationError::ImplausibleFeeRate(sats_per_vb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implausible_fee_rates_are_rejected() {
+        assert!(check_plausible(FeeRate::from_sat_per_vb(12.0), 500.0).is_ok());
+        assert!(check_plausible(FeeRate::from_sat_per_vb(0.0), 500.0).is_err());
+        assert!(check_plausible(FeeRate::from_sat_per_vb(5000.0), 500.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn falls_through_to_next_estimator() {
+        let client = FeesClient::new(FeesConfig {
+            estimators: vec![FeeEstimatorKind::Bitcoind, FeeEstimatorKind::Static],
+            ..FeesConfig::default()
+        });
+        let fee_rate = client.fee_rate(TxPriority::HalfHour).await.unwrap();
+        assert_eq!(
+            fee_rate,
+            StaticFeeRatesConfig::default().fee_rate(TxPriority::HalfHour)
+        );
+        let health = client.estimator_health();
+        assert_eq!(health[0].failures, 1);
+        assert_eq!(health[1].successes, 1);
+    }
+
+    #[tokio::test]
+    async fn static_rates_above_cap_are_rejected() {
+        let client = FeesClient::new(FeesConfig {
+            estimators: vec![FeeEstimatorKind::Static],
+            max_plausible_fee_rate: 5.0,
+            ..FeesConfig::default()
+        });
+        assert!(client.fee_rate(TxPriority::NextBlock).await.is_err());
+        assert_eq!(client.estimator_health()[0].implausible_estimates, 1);
+    }
+
+    #[tokio::test]
+    async fn estimates_are_cached_and_shared_between_clones() {
+        let client = FeesClient::new(FeesConfig {
+            estimators: vec![FeeEstimatorKind::Static],
+            ..FeesConfig::default()
+        });
+        let clone = client.clone();
+        client.fee_rate(TxPriority::OneHour).await.unwrap();
+        clone.fee_rate(TxPriority::OneHour).await.unwrap();
+        assert_eq!(client.estimator_health()[0].successes, 1);
+
+        let uncached = FeesClient::new(FeesConfig {
+            estimators: vec![FeeEstimatorKind::Static],
+            cache_ttl: Duration::ZERO,
+            ..FeesConfig::default()
+        });
+        uncached.fee_rate(TxPriority::OneHour).await.unwrap();
+        uncached.fee_rate(TxPriority::OneHour).await.unwrap();
+        assert_eq!(uncached.estimator_health()[0].successes, 2);
     }
 }
@@ -1,12 +1,79 @@
 use serde::{Deserialize, Serialize};
 
+use super::bitcoind::BitcoindFeesConfig;
 use super::blockstream::BlockstreamConfig;
 use super::mempool_space::MempoolSpaceConfig;
+use super::static_rates::StaticFeeRatesConfig;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeEstimatorKind {
+    Bitcoind,
+    MempoolSpace,
+    Blockstream,
+    Static,
+}
+
+impl std::fmt::Display for FeeEstimatorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bitcoind => write!(f, "bitcoind"),
+            Self::MempoolSpace => write!(f, "mempool_space"),
+            Self::Blockstream => write!(f, "blockstream"),
+            Self::Static => write!(f, "static"),
+        }
+    }
+}
+
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeesConfig {
+    /// Estimators in the order they are tried until one returns a plausible fee rate
+    #[serde(default = "default_estimators")]
+    pub estimators: Vec<FeeEstimatorKind>,
+    #[serde(default)]
+    pub bitcoind: Option<BitcoindFeesConfig>,
     #[serde(default)]
     pub mempool_space: MempoolSpaceConfig,
     #[serde(default)]
     pub blockstream: BlockstreamConfig,
+    #[serde(default)]
+    pub static_rates: StaticFeeRatesConfig,
+    /// Estimates above this rate (sats/vbyte) are discarded as implausible
+    #[serde(default = "default_max_plausible_fee_rate")]
+    pub max_plausible_fee_rate: f32,
+    /// How long an estimate is reused before the estimators are queried again
+    #[serde_as(as = "serde_with::DurationSeconds<u64>")]
+    #[serde(default = "default_cache_ttl")]
+    pub cache_ttl: std::time::Duration,
+}
+
+impl Default for FeesConfig {
+    fn default() -> Self {
+        Self {
+            estimators: default_estimators(),
+            bitcoind: None,
+            mempool_space: MempoolSpaceConfig::default(),
+            blockstream: BlockstreamConfig::default(),
+            static_rates: StaticFeeRatesConfig::default(),
+            max_plausible_fee_rate: default_max_plausible_fee_rate(),
+            cache_ttl: default_cache_ttl(),
+        }
+    }
+}
+
+fn default_estimators() -> Vec<FeeEstimatorKind> {
+    vec![
+        FeeEstimatorKind::MempoolSpace,
+        FeeEstimatorKind::Blockstream,
+        FeeEstimatorKind::Static,
+    ]
+}
+
+fn default_max_plausible_fee_rate() -> f32 {
+    500.0
+}
+
+fn default_cache_ttl() -> std::time::Duration {
+    std::time::Duration::from_secs(30)
 }
@@ -6,4 +6,14 @@ pub enum FeeEstimationError {
     FeeEstimation(#[from] reqwest_middleware::Error),
     #[error("FeeEstimationError - CouldNotDecodeResponseBody: {0}")]
     CouldNotDecodeResponseBody(reqwest::Error),
+    #[error("FeeEstimationError - BitcoindRpc: {0}")]
+    BitcoindRpc(#[from] bitcoincore_rpc::Error),
+    #[error("FeeEstimationError - NoEstimateAvailable: {0}")]
+    NoEstimateAvailable(String),
+    #[error("FeeEstimationError - ImplausibleFeeRate: {0} sats/vbyte")]
+    ImplausibleFeeRate(f32),
+    #[error("FeeEstimationError - EstimatorNotConfigured: {0}")]
+    EstimatorNotConfigured(String),
+    #[error("FeeEstimationError - NoEstimatorSucceeded")]
+    NoEstimatorSucceeded,
 }
@@ -1,19 +1,23 @@
 #![allow(warnings)]
+mod bitcoind;
 mod blockstream;
 mod client;
 mod config;
 pub mod error;
 mod mempool_space;
+mod static_rates;
 
 use bdk::bitcoin::{locktime::absolute::LockTime, Transaction, TxOut, Weight};
 use std::collections::HashMap;
 
 use crate::primitives::*;
 
+pub use bitcoind::*;
 pub use blockstream::*;
 pub use client::*;
 pub use config::*;
 pub use mempool_space::*;
+pub use static_rates::*;
 
 use error::FeeEstimationError;
 
@@ -155,6 +159,33 @@ mod tests {
         assert_eq!(estimate, total_fee);
     }
 
+    #[test]
+    fn test_taproot_inputs_are_cheaper() {
+        let wpkh: bdk::descriptor::ExtendedDescriptor = "wpkh([6f2fa1b2/84'/0'/0']tpubDDDDGYiFda8HfJRc2AHFJDxVzzEtBPrKsbh35EaW2UGd5qfzrF2G87ewAgeeRyHEz4iB3kvhAYW1sH6dpLepTkFUzAktumBN8AXeXWE9nd1/0/*)".parse().unwrap();
+        let tr: bdk::descriptor::ExtendedDescriptor = "tr([6f2fa1b2/86'/0'/0']tpubDDDDGYiFda8HfJRc2AHFJDxVzzEtBPrKsbh35EaW2UGd5qfzrF2G87ewAgeeRyHEz4iB3kvhAYW1sH6dpLepTkFUzAktumBN8AXeXWE9nd1/0/*)".parse().unwrap();
+        let address = "bc1qc7yu0g5qplddngesxuarkkp3na9hkrugpydqs0"
+            .parse::<Address>()
+            .unwrap();
+        let fee_rate = bitcoin::FeeRate::from_sat_per_vb(10.);
+
+        let estimate = |weight| {
+            estimate_proportional_fee(
+                Some(Satoshis::from(100_000)),
+                weight,
+                fee_rate,
+                0,
+                Satoshis::ZERO,
+                address.clone(),
+                Satoshis::from(250_000),
+            )
+        };
+
+        assert!(
+            estimate(tr.max_satisfaction_weight().unwrap())
+                < estimate(wpkh.max_satisfaction_weight().unwrap())
+        );
+    }
+
     #[test]
     fn test_allocate_proportional_fees() {
         let fees = Satoshis::from(1000);
@@ -0,0 +1,47 @@
+use bdk::FeeRate;
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::TxPriority;
+
+/// Fixed fee rates (sats/vbyte) used when no other estimator is reachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticFeeRatesConfig {
+    #[serde(default = "default_next_block")]
+    pub next_block: f32,
+    #[serde(default = "default_half_hour")]
+    pub half_hour: f32,
+    #[serde(default = "default_one_hour")]
+    pub one_hour: f32,
+}
+
+impl StaticFeeRatesConfig {
+    pub fn fee_rate(&self, priority: TxPriority) -> FeeRate {
+        match priority {
+            TxPriority::NextBlock => FeeRate::from_sat_per_vb(self.next_block),
+            TxPriority::HalfHour => FeeRate::from_sat_per_vb(self.half_hour),
+            TxPriority::OneHour => FeeRate::from_sat_per_vb(self.one_hour),
+        }
+    }
+}
+
+impl Default for StaticFeeRatesConfig {
+    fn default() -> Self {
+        Self {
+            next_block: default_next_block(),
+            half_hour: default_half_hour(),
+            one_hour: default_one_hour(),
+        }
+    }
+}
+
+fn default_next_block() -> f32 {
+    10.0
+}
+
+fn default_half_hour() -> f32 {
+    6.0
+}
+
+fn default_one_hour() -> f32 {
+    4.0
+}
@@ -1,17 +1,17 @@
-use bdk::blockchain::{Blockchain, ElectrumBlockchain};
-use electrum_client::{Client, ConfigBuilder};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use std::collections::HashMap;
 
 use super::error::JobError;
-use crate::{app::BlockchainConfig, batch::*, bdk::error::BdkError, primitives::*};
+use crate::{app::BlockchainConfig, batch::*, bdk::chain_source, primitives::*};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchBroadcastingData {
     pub(super) account_id: AccountId,
     pub(super) batch_id: BatchId,
+    #[serde(default)]
+    pub(super) rebroadcast: bool,
     #[serde(flatten)]
     pub(super) tracing_data: HashMap<String, String>,
 }
@@ -27,23 +27,32 @@ pub async fn execute(
     blockchain_cfg: BlockchainConfig,
     batches: Batches,
 ) -> Result<BatchBroadcastingData, JobError> {
-    let blockchain = init_electrum(&blockchain_cfg.electrum_url).await?;
     let batch = batches.find_by_id(data.account_id, data.batch_id).await?;
     let span = tracing::Span::current();
     span.record("txid", tracing::field::display(batch.bitcoin_tx_id));
-    if batch.accounting_complete() {
+    if batch.accounting_complete() && !batch.is_superseded() {
         if let Some(tx) = batch.signed_tx {
-            blockchain.broadcast(&tx).map_err(BdkError::BdkLibError)?;
+            if data.rebroadcast
+                && chain_source::is_tx_known(&blockchain_cfg, batch.bitcoin_tx_id).await?
+            {
+                return Ok(data);
+            }
+            let results: Vec<_> = chain_source::broadcast_all(&blockchain_cfg, &tx)
+                .await
+                .into_iter()
+                .map(|(chain_source, res)| BroadcastResult {
+                    chain_source,
+                    error: res.err().map(|e| e.to_string()),
+                })
+                .collect();
+            batches.record_broadcast_results(batch.id, &results).await?;
+            if !results.iter().any(BroadcastResult::succeeded) {
+                return Err(JobError::BroadcastFailed(
+                    results.into_iter().filter_map(|r| r.error).collect(),
+                ));
+            }
             span.record("broadcast", true);
         }
     }
     Ok(data)
 }
-
-async fn init_electrum(electrum_url: &str) -> Result<ElectrumBlockchain, BdkError> {
-    let blockchain = ElectrumBlockchain::from(Client::from_config(
-        electrum_url,
-        ConfigBuilder::new().retry(10).timeout(Some(60)).build(),
-    )?);
-    Ok(blockchain)
-}
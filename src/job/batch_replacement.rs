@@ -0,0 +1,137 @@
+use sqlx::{Postgres, Transaction};
+use tracing::instrument;
+
+use super::error::JobError;
+use crate::{
+    batch::*,
+    payout::*,
+    payout_queue::*,
+    primitives::*,
+    utxo::{error::UtxoError, *},
+};
+
+#[instrument(
+    name = "job.batch_replacement",
+    skip_all,
+    fields(batch_id, replaced_batch_id),
+    err
+)]
+pub(super) async fn execute<'a>(
+    pool: &sqlx::PgPool,
+    batches: &Batches,
+    payouts: &Payouts,
+    utxos: &Utxos,
+    account_id: AccountId,
+    new_batch: NewBatch,
+    fee_rate: bitcoin::FeeRate,
+) -> Result<(Transaction<'a, Postgres>, Vec<WalletId>), JobError> {
+    let batch_id = new_batch.id;
+    let tx_id = new_batch.tx_id;
+    let replaced_batch_id = new_batch
+        .replaces_batch_id
+        .expect("replacement batch must reference the replaced batch");
+    let span = tracing::Span::current();
+    span.record("batch_id", tracing::field::display(batch_id));
+    span.record(
+        "replaced_batch_id",
+        tracing::field::display(replaced_batch_id),
+    );
+
+    let wallet_ids = new_batch.wallet_ids();
+    let replaced_payouts = payouts
+        .list_for_batch(account_id, replaced_batch_id)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+    batches.create_in_op(&mut tx, new_batch).await?;
+    batches
+        .supersede_in_op(&mut tx, replaced_batch_id, batch_id)
+        .await?;
+    utxos
+        .move_utxos_to_replacement_batch(&mut tx, replaced_batch_id, batch_id, fee_rate)
+        .await?;
+    payouts
+        .move_to_replacement_batch(
+            &mut tx,
+            replaced_payouts.into_values().flatten().filter_map(|p| {
+                let outpoint = p.outpoint.map(|outpoint| bitcoin::OutPoint {
+                    txid: tx_id,
+                    vout: outpoint.vout,
+                })?;
+                Some((p, outpoint))
+            }),
+            batch_id,
+        )
+        .await?;
+
+    Ok((tx, wallet_ids))
+}
+
+#[instrument(
+    name = "job.replace_stuck_batches",
+    skip_all,
+    fields(payout_queue_id, n_candidates),
+    err
+)]
+pub(super) async fn replace_stuck_batches(
+    pool: &sqlx::PgPool,
+    batches: &Batches,
+    payouts: &Payouts,
+    utxos: &Utxos,
+    payout_queue: &PayoutQueue,
+    fee_rate: bitcoin::FeeRate,
+) -> Result<(), JobError> {
+    let span = tracing::Span::current();
+    span.record("payout_queue_id", tracing::field::display(payout_queue.id));
+    let candidates = batches
+        .find_rbf_candidates(
+            payout_queue.account_id,
+            payout_queue.id,
+            payout_queue.config.rbf_batches_created_before(),
+            payout_queue
+                .config
+                .rbf_batches_detected_before_block(crate::bdk::last_sync_time(pool).await?),
+        )
+        .await?;
+    span.record("n_candidates", candidates.len());
+
+    for batch_id in candidates {
+        let batch = match batches.find_by_id(payout_queue.account_id, batch_id).await {
+            Ok(batch) => batch,
+            Err(err) => {
+                tracing::error!(%batch_id, "{}", err.to_string());
+                continue;
+            }
+        };
+        if !batch.pays_less_than(fee_rate) {
+            continue;
+        }
+        let new_batch = match batch.replace_by_fee(fee_rate) {
+            Ok(new_batch) => new_batch,
+            Err(err) => {
+                tracing::warn!(%batch_id, "{}", err.to_string());
+                continue;
+            }
+        };
+        match super::replace_batch(
+            pool,
+            batches,
+            payouts,
+            utxos,
+            payout_queue.account_id,
+            new_batch,
+            fee_rate,
+        )
+        .await
+        {
+            Ok(()) => (),
+            Err(JobError::UtxoError(UtxoError::ChangeAlreadySpent)) => {
+                tracing::warn!(%batch_id, "replaced_batch_change_already_spent");
+            }
+            Err(err) => {
+                tracing::error!(%batch_id, "{}", err.to_string());
+            }
+        }
+    }
+    Ok(())
+}
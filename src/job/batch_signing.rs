@@ -5,7 +5,12 @@ use std::collections::HashMap;
 
 use super::error::JobError;
 use crate::{
-    app::BlockchainConfig, batch::*, primitives::*, signing_session::*, wallet::*, xpub::*,
+    app::BlockchainConfig,
+    batch::{error::BatchError, *},
+    primitives::*,
+    signing_session::*,
+    wallet::*,
+    xpub::*,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +49,30 @@ pub async fn execute(
     let mut stalled = false;
     let mut last_err = None;
     let mut current_keychain = None;
+    let batch = batches.find_by_id(data.account_id, data.batch_id).await?;
+    span.record("tx_id", tracing::field::display(batch.bitcoin_tx_id));
+    if batch.is_cancelled() {
+        span.record("finalization_status", "cancelled");
+        return Ok((data, false));
+    }
+    let mut batch_xpubs = Vec::new();
+    let mut signing_requirements = Vec::new();
+    for (wallet_id, summary) in batch.wallet_summaries {
+        let wallet = wallets.find_by_id(wallet_id).await?;
+        if current_keychain.is_none() {
+            current_keychain = Some(wallet.current_keychain_wallet(&pool));
+        }
+        let thresholds = wallet.signing_thresholds_for_keychains(&summary.signing_keychains);
+        let keychain_xpubs = wallet.xpubs_for_keychains(&summary.signing_keychains);
+        for (keychain_id, keychain_xpubs) in keychain_xpubs.into_iter() {
+            signing_requirements.push(SigningRequirement::new(
+                thresholds[&keychain_id],
+                keychain_xpubs.iter().map(|xpub| xpub.fingerprint()),
+            ));
+            batch_xpubs.extend(keychain_xpubs);
+        }
+    }
+
     let (mut sessions, mut account_xpub_cache) = if let Some(batch_session) = signing_sessions
         .list_for_batch(data.account_id, data.batch_id)
         .await?
@@ -52,31 +81,19 @@ pub async fn execute(
     } else {
         let mut new_sessions = HashMap::new();
         let mut account_xpubs = HashMap::new();
-        let batch = batches.find_by_id(data.account_id, data.batch_id).await?;
-        span.record("tx_id", tracing::field::display(batch.bitcoin_tx_id));
-        let unsigned_psbt = batch.unsigned_psbt;
-        for (wallet_id, summary) in batch.wallet_summaries {
-            let wallet = wallets.find_by_id(wallet_id).await?;
-            if current_keychain.is_none() {
-                current_keychain = Some(wallet.current_keychain_wallet(&pool));
-            }
-            let keychain_xpubs = wallet.xpubs_for_keychains(&summary.signing_keychains);
-            for (_, keychain_xpubs) in keychain_xpubs.into_iter() {
-                for xpub in keychain_xpubs.into_iter() {
-                    let account_xpub = xpubs
-                        .find_from_ref(data.account_id, xpub.fingerprint())
-                        .await?;
-                    let new_session = NewSigningSession::builder()
-                        .account_id(data.account_id)
-                        .batch_id(data.batch_id)
-                        .xpub_fingerprint(xpub.fingerprint())
-                        .unsigned_psbt(unsigned_psbt.clone())
-                        .build()
-                        .expect("Could not build signing session");
-                    new_sessions.insert(account_xpub.fingerprint(), new_session);
-                    account_xpubs.insert(account_xpub.fingerprint(), account_xpub);
-                }
-            }
+        for xpub in batch_xpubs {
+            let account_xpub = xpubs
+                .find_from_ref(data.account_id, xpub.fingerprint())
+                .await?;
+            let new_session = NewSigningSession::builder()
+                .account_id(data.account_id)
+                .batch_id(data.batch_id)
+                .xpub_fingerprint(xpub.fingerprint())
+                .unsigned_psbt(batch.unsigned_psbt.clone())
+                .build()
+                .expect("Could not build signing session");
+            new_sessions.insert(account_xpub.fingerprint(), new_session);
+            account_xpubs.insert(account_xpub.fingerprint(), account_xpub);
         }
 
         (
@@ -89,7 +106,19 @@ pub async fn execute(
     };
 
     let mut any_updated = false;
-    for (xpub_fingerprint, session) in sessions.iter_mut().filter(|(_, s)| !s.is_completed()) {
+    let pending: Vec<_> = sessions
+        .iter()
+        .filter(|(_, s)| !s.is_completed() && !s.is_no_longer_needed() && !s.is_cancelled())
+        .map(|(xpub_fingerprint, _)| *xpub_fingerprint)
+        .collect();
+    for xpub_fingerprint in pending.iter() {
+        // Once every keychain has enough signatures the remaining signers can be skipped
+        if signing_requirements.iter().all(|r| r.is_met(&sessions)) {
+            break;
+        }
+        let session = sessions
+            .get_mut(xpub_fingerprint)
+            .expect("pending session should exist");
         any_updated = true;
         let account_xpub = if let Some(xpub) = account_xpub_cache.remove(xpub_fingerprint) {
             xpub
@@ -134,21 +163,12 @@ pub async fn execute(
         signing_sessions.update_sessions(&mut tx, &sessions).await?;
         tx.commit().await?;
     }
-    let mut sessions = sessions.into_values();
 
     span.record("stalled", tracing::field::display(stalled));
-    if let Some(mut first_signed_psbt) = sessions.find_map(|s| s.signed_psbt().cloned()) {
-        for s in sessions {
-            if let Some(psbt) = s.signed_psbt() {
-                let _ = first_signed_psbt.combine(psbt.clone());
-            }
-        }
-        if current_keychain.is_none() {
-            let batch = batches.find_by_id(data.account_id, data.batch_id).await?;
-            span.record("tx_id", tracing::field::display(batch.bitcoin_tx_id));
-            let wallet_id = batch.wallet_summaries.into_keys().next().unwrap();
-            let wallet = wallets.find_by_id(wallet_id).await?;
-            current_keychain = Some(wallet.current_keychain_wallet(&pool));
+    let mut signed_psbts = sessions.values().filter_map(|s| s.signed_psbt().cloned());
+    if let Some(mut first_signed_psbt) = signed_psbts.next() {
+        for psbt in signed_psbts {
+            let _ = first_signed_psbt.combine(psbt);
         }
         match (
             current_keychain
@@ -159,9 +179,24 @@ pub async fn execute(
         ) {
             (Ok(Some(finalized_psbt)), _) => {
                 span.record("finalization_status", "complete");
+                // The quorum has been reached so the remaining cosigners don't need to sign
+                for session in sessions.values_mut() {
+                    session.signing_no_longer_needed();
+                }
+                let mut tx = pool.begin().await?;
+                signing_sessions.update_sessions(&mut tx, &sessions).await?;
+                tx.commit().await?;
                 let tx = finalized_psbt.extract_tx();
-                batches.set_signed_tx(data.batch_id, tx).await?;
-                Ok((data, true))
+                match batches.set_signed_tx(data.batch_id, tx).await {
+                    Err(BatchError::BatchCancelled(_)) => {
+                        span.record("finalization_status", "cancelled");
+                        Ok((data, false))
+                    }
+                    res => {
+                        res?;
+                        Ok((data, true))
+                    }
+                }
             }
             (_, Some(e)) => {
                 span.record("finalization_status", "returning_last_error");
@@ -35,6 +35,8 @@ pub async fn execute(
         bitcoin_tx_id,
         payout_queue_id,
         mut wallet_summaries,
+        replaces_batch_id,
+        cancelled_at,
         ..
     } = batches.find_by_id(data.account_id, data.batch_id).await?;
 
@@ -42,6 +44,40 @@ pub async fn execute(
         .remove(&data.wallet_id)
         .expect("wallet summary not found");
     let wallet = wallets.find_by_id(data.wallet_id).await?;
+    if cancelled_at.is_some() {
+        if let Some((tx, cancelled_batch, tx_id)) = batches
+            .set_batch_cancelled_ledger_tx_id(id, data.wallet_id)
+            .await?
+        {
+            ledger
+                .batch_cancelled(
+                    tx,
+                    tx_id,
+                    cancelled_batch.created_ledger_tx_id,
+                    wallet.ledger_account_ids,
+                )
+                .await?;
+        }
+        return Ok(data);
+    }
+    if let Some(replaced_batch_id) = replaces_batch_id {
+        if let Some((tx, replaced_batch, tx_id)) = batches
+            .set_batch_replaced_ledger_tx_id(replaced_batch_id, data.wallet_id)
+            .await?
+        {
+            ledger
+                .batch_replaced(
+                    tx,
+                    tx_id,
+                    replaced_batch.created_ledger_tx_id,
+                    replaced_batch.broadcast_ledger_tx_id,
+                    wallet.ledger_account_ids,
+                    id,
+                    bitcoin_tx_id,
+                )
+                .await?;
+        }
+    }
     let (income_ids, settled_sats) = bria_utxos
         .accounting_info_for_batch(data.batch_id, data.wallet_id)
         .await?;
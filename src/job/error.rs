@@ -56,6 +56,8 @@ pub enum JobError {
     Sqlx(#[from] sqlx::Error),
     #[error("JobError - PsbtMissingInSigningSessions")]
     PsbtMissingInSigningSessions,
+    #[error("JobError - BroadcastFailed: {0:?}")]
+    BroadcastFailed(Vec<String>),
     #[error("JobError - psbt::Error: {0}")]
     PsbtError(#[from] psbt::Error),
 }
@@ -182,10 +182,25 @@ struct JobData<T> {
     job_meta: JobMeta,
     #[serde(flatten)]
     data: Option<T>,
-    #[serde(flatten)]
+    #[serde(flatten, deserialize_with = "string_values")]
     tracing_data: HashMap<String, String>,
 }
 
+// Job data containing a flattened map exposes all of its fields here, not just the tracing headers
+fn string_values<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            serde_json::Value::String(value) => Some((key, value)),
+            _ => None,
+        })
+        .collect())
+}
+
 impl<'a, T: Deserialize<'a>> JobData<T> {
     pub fn from_raw_payload(payload: Option<&'a str>) -> Result<Self, serde_json::Error> {
         if let Some(payload) = payload {
@@ -260,4 +275,25 @@ mod tests {
         assert!(job_data.data.is_none());
         assert_eq!(job_data.tracing_data.get("header").unwrap(), "value");
     }
+
+    #[derive(Deserialize)]
+    struct DataWithTracing {
+        flag: bool,
+        #[serde(flatten)]
+        tracing_data: HashMap<String, String>,
+    }
+
+    #[test]
+    fn from_raw_with_non_string_fields() {
+        let json = r#"{
+            "flag": true,
+            "header": "value"
+        }"#;
+        let job_data: JobData<DataWithTracing> = JobData::from_raw_payload(Some(json)).unwrap();
+        let data = job_data.data.unwrap();
+        assert!(data.flag);
+        assert_eq!(data.tracing_data.get("header").unwrap(), "value");
+        assert_eq!(job_data.tracing_data.get("header").unwrap(), "value");
+        assert!(!job_data.tracing_data.contains_key("flag"));
+    }
 }
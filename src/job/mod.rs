@@ -1,4 +1,5 @@
 mod batch_broadcasting;
+mod batch_replacement;
 mod batch_signing;
 mod batch_wallet_accounting;
 mod config;
@@ -16,9 +17,9 @@ use tracing::instrument;
 use uuid::{uuid, Uuid};
 
 use crate::{
-    account::*, address::Addresses, app::BlockchainConfig, batch::*, fees::FeesClient,
-    ledger::Ledger, outbox::*, payout::*, payout_queue::*, primitives::*, signing_session::*,
-    utxo::Utxos, wallet::*, xpub::*,
+    account::*, address::Addresses, app::BlockchainConfig, batch::*, bdk::pg::SyncCursors,
+    fees::FeesClient, ledger::Ledger, outbox::*, payout::*, payout_queue::*, primitives::*,
+    signing_session::*, utxo::Utxos, wallet::*, xpub::*,
 };
 use batch_broadcasting::BatchBroadcastingData;
 use batch_signing::BatchSigningData;
@@ -28,11 +29,12 @@ pub use executor::JobExecutionError;
 use executor::JobExecutor;
 use populate_outbox::PopulateOutboxData;
 use process_payout_queue::ProcessPayoutQueueData;
-use sync_wallet::SyncWalletData;
+pub use sync_wallet::SyncWalletData;
 
 const SYNC_ALL_WALLETS_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000001");
 const PROCESS_ALL_PAYOUT_QUEUES_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000002");
 const RESPAWN_ALL_OUTBOX_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000003");
+const REBROADCAST_BATCHES_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000004");
 
 #[allow(clippy::too_many_arguments)]
 pub async fn start_job_runner(
@@ -63,6 +65,7 @@ pub async fn start_job_runner(
         batch_broadcasting,
         respawn_all_outbox_handlers,
         populate_outbox,
+        rebroadcast_batches,
     ]);
     registry.set_context(config);
     registry.set_context(blockchain_cfg);
@@ -86,6 +89,7 @@ pub async fn start_job_runner(
 async fn sync_all_wallets(
     mut current_job: CurrentJob,
     wallets: Wallets,
+    blockchain_cfg: BlockchainConfig,
     JobsConfig {
         sync_all_wallets_delay: delay,
         ..
@@ -96,7 +100,12 @@ async fn sync_all_wallets(
         .build()
         .expect("couldn't build JobExecutor")
         .execute(|_| async move {
-            for (account_id, wallet_id) in wallets.all_ids().await? {
+            let ids: Vec<_> = if blockchain_cfg.incremental_sync {
+                SyncCursors::new(pool.clone()).wallets_to_sync().await?
+            } else {
+                wallets.all_ids().await?.collect()
+            };
+            for (account_id, wallet_id) in ids {
                 let _ = spawn_sync_wallet(&pool, SyncWalletData::new(account_id, wallet_id)).await;
             }
             Ok::<(), JobError>(())
@@ -182,6 +191,44 @@ async fn respawn_all_outbox_handlers(
     Ok(())
 }
 
+#[job(name = "rebroadcast_batches")]
+async fn rebroadcast_batches(
+    mut current_job: CurrentJob,
+    batches: Batches,
+    JobsConfig {
+        rebroadcast_batches_delay: delay,
+        ..
+    }: JobsConfig,
+) -> Result<(), JobError> {
+    let pool = current_job.pool().clone();
+    JobExecutor::builder(&mut current_job)
+        .build()
+        .expect("couldn't build JobExecutor")
+        .execute(|_| async move {
+            let broadcast_before =
+                chrono::Utc::now() - chrono::Duration::from_std(delay).expect("delay out of range");
+            for (account_id, batch_id) in batches
+                .find_rebroadcast_candidates(broadcast_before)
+                .await?
+            {
+                let _ = spawn_batch_broadcasting(
+                    pool.begin().await?,
+                    BatchBroadcastingData {
+                        account_id,
+                        batch_id,
+                        rebroadcast: true,
+                        tracing_data: crate::tracing::extract_tracing_data(),
+                    },
+                )
+                .await;
+            }
+            Ok::<(), JobError>(())
+        })
+        .await?;
+    spawn_rebroadcast_batches(current_job.pool(), delay).await?;
+    Ok(())
+}
+
 #[job(name = "sync_wallet")]
 #[allow(clippy::too_many_arguments)]
 async fn sync_wallet(
@@ -420,8 +467,24 @@ pub async fn spawn_sync_all_wallets(
     }
 }
 
+#[instrument(name = "job.spawn_sync_wallets", skip_all, fields(error, error.level, error.message), err)]
+pub async fn spawn_sync_wallets(pool: &sqlx::PgPool, wallets: &Wallets) -> Result<(), JobError> {
+    for (account_id, wallet_id) in wallets.all_ids().await? {
+        spawn_sync_wallet(pool, SyncWalletData::new(account_id, wallet_id)).await?;
+    }
+    Ok(())
+}
+
+#[instrument(name = "job.spawn_sync_dirty_wallets", skip_all, fields(error, error.level, error.message), err)]
+pub async fn spawn_sync_dirty_wallets(pool: &sqlx::PgPool) -> Result<(), JobError> {
+    for (account_id, wallet_id) in SyncCursors::new(pool.clone()).wallets_to_sync().await? {
+        spawn_sync_wallet(pool, SyncWalletData::new(account_id, wallet_id)).await?;
+    }
+    Ok(())
+}
+
 #[instrument(name = "job.spawn_sync_wallet", skip_all, fields(error, error.level, error.message), err)]
-async fn spawn_sync_wallet(pool: &sqlx::PgPool, data: SyncWalletData) -> Result<(), JobError> {
+pub async fn spawn_sync_wallet(pool: &sqlx::PgPool, data: SyncWalletData) -> Result<(), JobError> {
     onto_account_main_channel(pool, data.account_id, data.wallet_id, "sync_wallet", data).await?;
     Ok(())
 }
@@ -543,6 +606,40 @@ pub async fn spawn_all_batch_signings(
     Ok(())
 }
 
+#[instrument(name = "job.replace_batch", skip_all, err)]
+pub async fn replace_batch(
+    pool: &sqlx::PgPool,
+    batches: &Batches,
+    payouts: &Payouts,
+    utxos: &Utxos,
+    account_id: AccountId,
+    new_batch: NewBatch,
+    fee_rate: bitcoin::FeeRate,
+) -> Result<(), JobError> {
+    let batch_id = new_batch.id;
+    let (mut tx, wallet_ids) = batch_replacement::execute(
+        pool, batches, payouts, utxos, account_id, new_batch, fee_rate,
+    )
+    .await?;
+    for wallet_id in wallet_ids {
+        spawn_batch_wallet_accounting(&mut tx, (account_id, batch_id, wallet_id)).await?;
+    }
+    spawn_batch_signing(tx, (account_id, batch_id)).await
+}
+
+#[instrument(name = "job.spawn_batch_cancelled_accounting", skip_all, err)]
+pub async fn spawn_batch_cancelled_accounting(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    account_id: AccountId,
+    batch_id: BatchId,
+    wallet_ids: impl IntoIterator<Item = WalletId>,
+) -> Result<(), JobError> {
+    for wallet_id in wallet_ids {
+        spawn_batch_wallet_accounting(tx, (account_id, batch_id, wallet_id)).await?;
+    }
+    Ok(())
+}
+
 #[instrument(name = "job.spawn_batch_broadcasting", skip_all, fields(error, error.level, error.message), err)]
 async fn spawn_batch_broadcasting(
     mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
@@ -611,6 +708,26 @@ pub async fn spawn_respawn_all_outbox_handlers(
     }
 }
 
+#[instrument(name = "job.spawn_rebroadcast_batches", skip_all, fields(error, error.level, error.message), err)]
+pub async fn spawn_rebroadcast_batches(
+    pool: &sqlx::PgPool,
+    duration: std::time::Duration,
+) -> Result<(), JobError> {
+    match JobBuilder::new_with_id(REBROADCAST_BATCHES_ID, "rebroadcast_batches")
+        .set_channel_name("rebroadcast_batches")
+        .set_delay(duration)
+        .spawn(pool)
+        .await
+    {
+        Err(sqlx::Error::Database(err)) if err.message().contains("duplicate key") => Ok(()),
+        Err(e) => {
+            crate::tracing::insert_error_fields(tracing::Level::ERROR, &e);
+            Err(e.into())
+        }
+        Ok(_) => Ok(()),
+    }
+}
+
 fn schedule_payout_queue_channel_arg(payout_queue_id: PayoutQueueId) -> String {
     format!("payout_queue_id:{payout_queue_id}")
 }
@@ -676,6 +793,17 @@ impl From<(&ProcessPayoutQueueData, WalletId)> for BatchWalletAccountingData {
     }
 }
 
+impl From<(AccountId, BatchId, WalletId)> for BatchWalletAccountingData {
+    fn from((account_id, batch_id, wallet_id): (AccountId, BatchId, WalletId)) -> Self {
+        Self {
+            tracing_data: crate::tracing::extract_tracing_data(),
+            account_id,
+            batch_id,
+            wallet_id,
+        }
+    }
+}
+
 impl From<&ProcessPayoutQueueData> for BatchSigningData {
     fn from(data: &ProcessPayoutQueueData) -> Self {
         Self {
@@ -700,6 +828,7 @@ impl From<BatchWalletAccountingData> for BatchBroadcastingData {
         Self {
             account_id: data.account_id,
             batch_id: data.batch_id,
+            rebroadcast: false,
             tracing_data: crate::tracing::extract_tracing_data(),
         }
     }
@@ -710,6 +839,7 @@ impl From<BatchSigningData> for BatchBroadcastingData {
         Self {
             account_id: data.account_id,
             batch_id: data.batch_id,
+            rebroadcast: false,
             tracing_data: crate::tracing::extract_tracing_data(),
         }
     }
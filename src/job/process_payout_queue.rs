@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::instrument;
 
 use super::error::JobError;
@@ -30,7 +30,8 @@ pub struct ProcessPayoutQueueData {
         total_change_sats,
         psbt,
         batch_id,
-        payout_queue_id
+        payout_queue_id,
+        replaced_batch_id
     ),
     err
 )]
@@ -54,13 +55,95 @@ pub(super) async fn execute<'a>(
     let payout_queue = payout_queues
         .find_by_account_id_and_id(data.account_id, data.payout_queue_id)
         .await?;
+    let estimated_fee_rate = fees_client
+        .fee_rate(payout_queue.config.tx_priority)
+        .await?;
+    if payout_queue.config.should_defer_batch(estimated_fee_rate) {
+        tracing::warn!(
+            "deferring batch of payout queue {} - fee rate {:?} is above max_fee_rate",
+            payout_queue.name,
+            estimated_fee_rate
+        );
+        return Ok((data, None));
+    }
+    let mut fee_rate = payout_queue.config.bounded_fee_rate(estimated_fee_rate);
+    if payout_queue.config.should_rbf() {
+        super::batch_replacement::replace_stuck_batches(
+            &pool,
+            &batches,
+            &payouts,
+            &utxos,
+            &payout_queue,
+            fee_rate,
+        )
+        .await?;
+    }
+    let mut replaced_batch = None;
+    if payout_queue.config.add_payouts_to_unconfirmed_batch {
+        if let Some(batch_id) = batches
+            .find_unconfirmed_for_queue(data.account_id, data.payout_queue_id)
+            .await?
+        {
+            let batch = batches.find_by_id(data.account_id, batch_id).await?;
+            if batch.accounting_complete() {
+                replaced_batch = Some(batch);
+            }
+        }
+    }
     let mut tx = pool.begin().await?;
     let mut unbatched_payouts = payouts
         .list_unbatched(&mut tx, data.account_id, data.payout_queue_id)
         .await?;
-    let fee_rate = fees_client
-        .fee_rate(payout_queue.config.tx_priority)
+    if unbatched_payouts.n_payouts() == 0 {
+        replaced_batch = None;
+    }
+
+    let mut replaced_payouts = HashMap::new();
+    let mut build = None;
+    if let Some(batch) = replaced_batch.as_ref() {
+        replaced_payouts = payouts.list_for_batch(data.account_id, batch.id).await?;
+        unbatched_payouts.include_replaced_payouts(&replaced_payouts);
+        let required_utxos = utxos.find_batch_inputs(&mut tx, batch.id).await?;
+        let replacement_fee_rate = batch.replacement_fee_rate(fee_rate);
+        let replacement = construct_psbt(
+            &pool,
+            &mut tx,
+            &unbatched_payouts,
+            &utxos,
+            &wallets,
+            &payout_queue,
+            replacement_fee_rate,
+            required_utxos,
+            false,
+        )
         .await?;
+        if replacement_is_valid(batch, &replaced_payouts, &replacement) {
+            fee_rate = replacement_fee_rate;
+            build = Some(replacement);
+        } else {
+            tracing::warn!("could not add payouts to unconfirmed batch {}", batch.id);
+            unbatched_payouts.include_replaced_payouts(&HashMap::new());
+            replaced_payouts.clear();
+        }
+    }
+    let replaced_batch = replaced_batch.filter(|_| build.is_some());
+    let build = match build {
+        Some(build) => build,
+        None => {
+            construct_psbt(
+                &pool,
+                &mut tx,
+                &unbatched_payouts,
+                &utxos,
+                &wallets,
+                &payout_queue,
+                fee_rate,
+                HashMap::new(),
+                false,
+            )
+            .await?
+        }
+    };
     let FinishedPsbtBuild {
         psbt,
         included_payouts,
@@ -69,17 +152,7 @@ pub(super) async fn execute<'a>(
         tx_id,
         fee_satoshis,
         ..
-    } = construct_psbt(
-        &pool,
-        &mut tx,
-        &unbatched_payouts,
-        &utxos,
-        &wallets,
-        payout_queue,
-        fee_rate,
-        false,
-    )
-    .await?;
+    } = build;
 
     let span = tracing::Span::current();
     if let (Some(tx_id), Some(psbt)) = (tx_id, psbt) {
@@ -112,6 +185,7 @@ pub(super) async fn execute<'a>(
             .tx_id(tx_id)
             .unsigned_psbt(psbt)
             .total_fee_sats(fee_satoshis)
+            .replaces_batch_id(replaced_batch.as_ref().map(|batch| batch.id))
             .wallet_summaries(
                 wallet_totals
                     .into_iter()
@@ -135,6 +209,18 @@ pub(super) async fn execute<'a>(
 
         let batch_id = batch.id;
         batches.create_in_op(&mut tx, batch).await?;
+        if let Some(replaced_batch) = replaced_batch.as_ref() {
+            span.record(
+                "replaced_batch_id",
+                tracing::field::display(replaced_batch.id),
+            );
+            batches
+                .supersede_in_op(&mut tx, replaced_batch.id, batch_id)
+                .await?;
+            utxos
+                .move_utxos_to_replacement_batch(&mut tx, replaced_batch.id, batch_id, fee_rate)
+                .await?;
+        }
         utxos
             .reserve_utxos_in_batch(
                 &mut tx,
@@ -146,13 +232,24 @@ pub(super) async fn execute<'a>(
             )
             .await?;
 
-        unbatched_payouts.commit_to_batch(
-            tx_id,
-            batch_id,
-            included_payouts
-                .into_values()
-                .flat_map(|payouts| payouts.into_iter().map(|((id, _, _), vout)| (id, vout))),
-        );
+        let mut replaced_vouts: HashMap<_, _> = included_payouts
+            .into_values()
+            .flat_map(|payouts| payouts.into_iter().map(|((id, _, _), vout)| (id, vout)))
+            .collect();
+        payouts
+            .move_to_replacement_batch(
+                &mut tx,
+                replaced_payouts
+                    .into_values()
+                    .flatten()
+                    .filter_map(|payout| {
+                        let vout = replaced_vouts.remove(&uuid::Uuid::from(payout.id))?;
+                        Some((payout, bitcoin::OutPoint { txid: tx_id, vout }))
+                    }),
+                batch_id,
+            )
+            .await?;
+        unbatched_payouts.commit_to_batch(tx_id, batch_id, replaced_vouts.into_iter());
 
         if unbatched_payouts.n_not_batched() > 0 {
             queue_drain_error(unbatched_payouts.n_not_batched());
@@ -169,6 +266,28 @@ pub(super) async fn execute<'a>(
     }
 }
 
+fn replacement_is_valid(
+    replaced_batch: &Batch,
+    replaced_payouts: &HashMap<WalletId, Vec<Payout>>,
+    replacement: &FinishedPsbtBuild,
+) -> bool {
+    let psbt = match replacement.psbt.as_ref() {
+        Some(psbt) => psbt,
+        None => return false,
+    };
+    let included_ids: HashSet<_> = replacement
+        .included_payouts
+        .values()
+        .flat_map(|payouts| payouts.iter().map(|((id, _, _), _)| *id))
+        .collect();
+    replaced_batch.is_replaceable_by(&psbt.unsigned_tx)
+        && replacement.fee_satoshis > replaced_batch.total_fee_sats()
+        && replaced_payouts
+            .values()
+            .flatten()
+            .all(|payout| included_ids.contains(&uuid::Uuid::from(payout.id)))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn construct_psbt(
     pool: &sqlx::Pool<sqlx::Postgres>,
@@ -176,8 +295,9 @@ pub async fn construct_psbt(
     unbatched_payouts: &UnbatchedPayouts,
     utxos: &Utxos,
     wallets: &Wallets,
-    payout_queue: PayoutQueue,
+    payout_queue: &PayoutQueue,
     fee_rate: bitcoin::FeeRate,
+    required_utxos: HashMap<KeychainId, Vec<bitcoin::OutPoint>>,
     for_estimation: bool,
 ) -> Result<FinishedPsbtBuild, JobError> {
     let span = tracing::Span::current();
@@ -187,17 +307,22 @@ pub async fn construct_psbt(
         name: queue_name,
         ..
     } = payout_queue;
-    span.record("payout_queue_name", queue_name);
+    span.record("payout_queue_name", queue_name.as_str());
     span.record("payout_queue_id", tracing::field::display(queue_id));
     span.record("n_unbatched_payouts", unbatched_payouts.n_payouts());
 
     let wallets = wallets.find_all(&unbatched_payouts.wallet_ids()).await?;
-    let reserved_utxos = {
+    let mut reserved_utxos = {
         let keychain_ids = wallets.values().flat_map(|w: &Wallet| w.keychain_ids());
         utxos
             .outpoints_bdk_should_not_select(tx, keychain_ids)
             .await?
     };
+    for (keychain_id, outpoints) in required_utxos.iter() {
+        if let Some(reserved) = reserved_utxos.get_mut(keychain_id) {
+            reserved.retain(|out| !outpoints.contains(out));
+        }
+    }
     span.record(
         "n_reserved_utxos",
         reserved_utxos.values().fold(0, |acc, v| acc + v.len()),
@@ -209,6 +334,7 @@ pub async fn construct_psbt(
         .consolidate_deprecated_keychains(queue_cfg.consolidate_deprecated_keychains)
         .fee_rate(fee_rate)
         .reserved_utxos(reserved_utxos)
+        .required_utxos(required_utxos)
         .force_min_change_output(queue_cfg.force_min_change_sats);
     if !for_estimation && queue_cfg.should_cpfp() {
         let keychain_ids = wallets.values().flat_map(|w| w.keychain_ids());
@@ -216,7 +342,7 @@ pub async fn construct_psbt(
             .find_cpfp_utxos(
                 tx,
                 keychain_ids,
-                queue_id,
+                *queue_id,
                 queue_cfg.cpfp_payouts_detected_before(),
                 queue_cfg
                     .cpfp_payouts_detected_before_block(crate::bdk::last_sync_time(pool).await?),
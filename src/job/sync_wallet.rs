@@ -1,5 +1,4 @@
-use bdk::blockchain::{ElectrumBlockchain, GetHeight};
-use electrum_client::{Client, ConfigBuilder};
+use bdk::blockchain::GetBlockHash;
 use serde::{Deserialize, Serialize};
 use tracing::{info, instrument};
 
@@ -9,13 +8,17 @@ use crate::{
     app::BlockchainConfig,
     batch::*,
     bdk::{
+        chain_source,
         error::BdkError,
-        pg::{ConfirmedIncomeUtxo, ConfirmedSpendTransaction, Transactions, Utxos as BdkUtxos},
+        pg::{
+            BlockHashes, ConfirmedIncomeUtxo, ConfirmedSpendTransaction, KeychainRescans,
+            SyncCursors, Transactions, UnsyncedTransaction, Utxos as BdkUtxos,
+        },
     },
     fees::{self, FeesClient},
     ledger::*,
     primitives::*,
-    utxo::{error::UtxoError, Utxos, WalletUtxo},
+    utxo::{error::UtxoError, ReorgedUtxos, Utxos, WalletUtxo},
     wallet::*,
 };
 use std::collections::HashMap;
@@ -95,17 +98,53 @@ pub async fn execute(
     };
     let mut utxos_to_fetch = HashMap::new();
     let mut income_bria_utxos = Vec::new();
+    let sync_cursors = SyncCursors::new(pool.clone());
+    let rescans = KeychainRescans::new(pool.clone());
+    // The sync cursors only decide which keychains get synced. A dirty keychain is
+    // still synced in full by bdk (which can't sync a subset of its scripts) over
+    // its own chain source connection.
     for keychain_wallet in wallet.keychain_wallets(pool.clone()) {
+        let synced_from = chrono::Utc::now();
+        let rescan = rescans.find_pending(keychain_wallet.keychain_id).await?;
+        if deps.blockchain_cfg.incremental_sync
+            && rescan.is_none()
+            && !sync_cursors.needs_sync(keychain_wallet.keychain_id).await?
+        {
+            continue;
+        }
         info!("Syncing keychain '{}'", keychain_wallet.keychain_id);
         let fees_to_encumber =
             fees::fees_to_encumber(&fees_client, keychain_wallet.max_satisfaction_weight()).await?;
         let keychain_id = keychain_wallet.keychain_id;
         utxos_to_fetch.clear();
         utxos_to_fetch.insert(keychain_id, Vec::<bitcoin::OutPoint>::new());
-        let (blockchain, current_height) = init_electrum(&deps.blockchain_cfg.electrum_url).await?;
+        let (blockchain, current_height) = chain_source::init_blockchain(
+            &deps.blockchain_cfg,
+            keychain_id,
+            wallet.birthday_height,
+            rescan.as_ref(),
+        )
+        .await?;
         span.record("current_height", current_height);
         let latest_change_settle_height = wallet.config.latest_change_settle_height(current_height);
+        let block_hashes = BlockHashes::new(keychain_id, pool.clone());
+        // Must happen before bdk updates the confirmation heights of the reorged txs
+        if let Some(fork_height) = block_hashes
+            .find_fork_height(&blockchain, current_height)
+            .await?
+        {
+            tracing::warn!("Reorg from height {fork_height} detected for keychain '{keychain_id}'");
+            reorg_keychain(&pool, &deps, &wallet, keychain_id, fork_height).await?;
+        }
+        let tip_hash = blockchain
+            .get_block_hash(current_height as u64)
+            .map_err(BdkError::from)?;
         keychain_wallet.sync(blockchain).await?;
+        block_hashes.record_tip(current_height, tip_hash).await?;
+        if let Some(rescan) = rescan {
+            info!("Rescan of keychain '{keychain_id}' completed");
+            rescans.complete(keychain_id, &rescan).await?;
+        }
         let bdk_txs = Transactions::new(keychain_id, pool.clone());
         let bdk_utxos = BdkUtxos::new(keychain_id, pool.clone());
         let mut txs_to_skip = Vec::new();
@@ -117,6 +156,26 @@ pub async fn execute(
             tracing::info!(?unsynced_tx);
             income_bria_utxos.clear();
             trackers.n_found_txs += 1;
+            if let Some(birthday_height) = wallet.birthday_height.filter(|birthday| {
+                matches!(unsynced_tx.confirmation_time, Some(ref t) if t.height < *birthday)
+            }) {
+                record_opening_balances(
+                    &pool,
+                    &deps,
+                    &wallet,
+                    &keychain_wallet,
+                    unsynced_tx,
+                    birthday_height,
+                    fees_to_encumber,
+                    current_height,
+                )
+                .await?;
+                txs_to_skip.clear();
+                if trackers.n_found_txs >= MAX_TXS_PER_SYNC {
+                    break;
+                }
+                continue;
+            }
             let mut change = Vec::new();
             let n_inputs = {
                 let inputs = utxos_to_fetch.get_mut(&keychain_id).unwrap();
@@ -537,6 +596,18 @@ pub async fn execute(
                 break;
             }
         }
+
+        if trackers.n_found_txs < MAX_TXS_PER_SYNC {
+            sync_cursors
+                .advance(
+                    data.account_id,
+                    data.wallet_id,
+                    keychain_id,
+                    current_height,
+                    synced_from,
+                )
+                .await?;
+        }
     }
 
     let has_more = trackers.n_found_txs >= MAX_TXS_PER_SYNC;
@@ -548,13 +619,175 @@ pub async fn execute(
     Ok((has_more, data))
 }
 
-async fn init_electrum(electrum_url: &str) -> Result<(ElectrumBlockchain, u32), BdkError> {
-    let blockchain = ElectrumBlockchain::from(Client::from_config(
-        electrum_url,
-        ConfigBuilder::new().retry(10).timeout(Some(60)).build(),
-    )?);
-    let current_height = blockchain.get_height()?;
-    Ok((blockchain, current_height))
+/// History below the wallet's birthday is not replayed through the ledger. Outputs
+/// of the tx that were still unspent at the birthday get recorded as settled opening
+/// balances, everything else is only marked as synced.
+#[allow(clippy::too_many_arguments)]
+async fn record_opening_balances(
+    pool: &sqlx::PgPool,
+    deps: &Deps,
+    wallet: &Wallet,
+    keychain_wallet: &KeychainWallet,
+    unsynced_tx: UnsyncedTransaction,
+    birthday_height: u32,
+    fees_to_encumber: Satoshis,
+    current_height: u32,
+) -> Result<(), JobError> {
+    let keychain_id = keychain_wallet.keychain_id;
+    let bdk_txs = Transactions::new(keychain_id, pool.clone());
+    let bdk_utxos = BdkUtxos::new(keychain_id, pool.clone());
+    let confirmation_time = unsynced_tx
+        .confirmation_time
+        .expect("opening balances are always confirmed");
+    for (local_utxo, path) in unsynced_tx.outputs {
+        if bdk_txs
+            .is_spent_below(local_utxo.outpoint, birthday_height)
+            .await?
+        {
+            let mut tx = pool.begin().await?;
+            bdk_utxos.mark_as_synced(&mut tx, &local_utxo).await?;
+            bdk_utxos.mark_confirmed(&mut tx, &local_utxo).await?;
+            tx.commit().await?;
+            continue;
+        }
+        let address_info = keychain_wallet
+            .find_address_from_path(path, local_utxo.keychain)
+            .await?;
+        let found_addr = NewAddress::builder()
+            .account_id(wallet.account_id)
+            .wallet_id(wallet.id)
+            .keychain_id(keychain_id)
+            .address(address_info.address.clone().into())
+            .kind(address_info.keychain)
+            .address_idx(address_info.index)
+            .metadata(Some(address_metadata(&unsynced_tx.tx_id)))
+            .build()
+            .expect("Could not build new address in sync wallet");
+        if let Some((opening_id, mut tx)) = deps
+            .bria_utxos
+            .new_utxo_detected(
+                wallet.account_id,
+                wallet.id,
+                keychain_id,
+                &address_info,
+                &local_utxo,
+                unsynced_tx.fee_sats,
+                unsynced_tx.vsize,
+                false,
+                current_height,
+            )
+            .await?
+        {
+            deps.bria_addresses
+                .persist_if_not_present(&mut tx, found_addr)
+                .await?;
+            bdk_utxos.mark_as_synced(&mut tx, &local_utxo).await?;
+            bdk_utxos.mark_confirmed(&mut tx, &local_utxo).await?;
+            deps.bria_utxos
+                .opening_balance_settled(
+                    &mut tx,
+                    keychain_id,
+                    local_utxo.outpoint,
+                    confirmation_time.height,
+                )
+                .await?;
+            deps.ledger
+                .utxo_opening_balance(
+                    tx,
+                    opening_id,
+                    UtxoOpeningBalanceParams {
+                        journal_id: wallet.journal_id,
+                        ledger_account_ids: wallet.ledger_account_ids,
+                        meta: UtxoOpeningBalanceMeta {
+                            account_id: wallet.account_id,
+                            wallet_id: wallet.id,
+                            keychain_id,
+                            outpoint: local_utxo.outpoint,
+                            satoshis: local_utxo.txout.value.into(),
+                            address: address_info.address.into(),
+                            encumbered_spending_fees: std::iter::once((
+                                local_utxo.outpoint,
+                                fees_to_encumber,
+                            ))
+                            .collect(),
+                            confirmation_time: confirmation_time.clone(),
+                            birthday_height,
+                        },
+                    },
+                )
+                .await?;
+        }
+    }
+    let mut tx = pool.begin().await?;
+    bdk_txs.mark_confirmed(&mut tx, unsynced_tx.tx_id).await?;
+    tx.commit().await?;
+    bdk_txs.mark_as_synced(unsynced_tx.tx_id).await?;
+    Ok(())
+}
+
+/// Reverses every settlement recorded at or above `fork_height` and makes the
+/// affected bdk utxos and txs eligible for settling again.
+async fn reorg_keychain(
+    pool: &sqlx::PgPool,
+    deps: &Deps,
+    wallet: &Wallet,
+    keychain_id: KeychainId,
+    fork_height: u32,
+) -> Result<(), JobError> {
+    let ReorgedUtxos {
+        utxo_settled_ledger_tx_ids,
+        spend_settled_ledger_tx_ids,
+    } = deps
+        .bria_utxos
+        .find_reorged(keychain_id, fork_height)
+        .await?;
+    for settled_txn_id in spend_settled_ledger_tx_ids {
+        let mut tx = pool.begin().await?;
+        if deps
+            .bria_utxos
+            .unsettle_spend(&mut tx, keychain_id, settled_txn_id)
+            .await?
+        {
+            deps.ledger
+                .spend_reorged(
+                    tx,
+                    LedgerTransactionId::new(),
+                    settled_txn_id,
+                    wallet.ledger_account_ids,
+                )
+                .await?;
+        }
+    }
+    for settled_txn_id in utxo_settled_ledger_tx_ids {
+        let mut tx = pool.begin().await?;
+        if deps
+            .bria_utxos
+            .unsettle_utxo(&mut tx, keychain_id, settled_txn_id)
+            .await?
+        {
+            deps.ledger
+                .utxo_reorged(
+                    tx,
+                    LedgerTransactionId::new(),
+                    settled_txn_id,
+                    wallet.ledger_account_ids,
+                )
+                .await?;
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    BdkUtxos::new(keychain_id, pool.clone())
+        .mark_reorged(&mut tx, fork_height)
+        .await?;
+    Transactions::new(keychain_id, pool.clone())
+        .mark_reorged(&mut tx, fork_height)
+        .await?;
+    BlockHashes::new(keychain_id, pool.clone())
+        .remove_reorged(&mut tx, fork_height)
+        .await?;
+    tx.commit().await?;
+    Ok(())
 }
 
 fn address_metadata(tx_id: &bitcoin::Txid) -> serde_json::Value {
@@ -37,6 +37,21 @@ pub(super) const BATCH_CREATED_ID: Uuid = uuid!("10000000-0000-0000-0000-0000000
 pub(super) const BATCH_BROADCAST_CODE: &str = "BATCH_BROADCAST";
 pub(super) const BATCH_BROADCAST_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000008");
 
+pub(super) const BATCH_REPLACED_CODE: &str = "BATCH_REPLACED";
+pub(super) const BATCH_REPLACED_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000011");
+
+pub(super) const UTXO_REORGED_CODE: &str = "UTXO_REORGED";
+pub(super) const UTXO_REORGED_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000012");
+
+pub(super) const SPEND_REORGED_CODE: &str = "SPEND_REORGED";
+pub(super) const SPEND_REORGED_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000013");
+
+pub(super) const UTXO_OPENING_BALANCE_CODE: &str = "UTXO_OPENING_BALANCE";
+pub(super) const UTXO_OPENING_BALANCE_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000014");
+
+pub(super) const BATCH_CANCELLED_CODE: &str = "BATCH_CANCELLED";
+pub(super) const BATCH_CANCELLED_ID: Uuid = uuid!("00000000-0000-0000-0000-000000000015");
+
 // Onchain/Omnibus Ledger Accounts
 pub(super) const ONCHAIN_UTXO_INCOMING_CODE: &str = "ONCHAIN_UTXO_INCOMING";
 pub(super) const ONCHAIN_UTXO_INCOMING_ID: Uuid = uuid!("00000000-1910-0000-1000-000000000000");
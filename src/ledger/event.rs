@@ -26,6 +26,11 @@ pub enum JournalEventMetadata {
     PayoutCancelled(PayoutCancelledMeta),
     BatchCreated(BatchCreatedMeta),
     BatchBroadcast(BatchBroadcastMeta),
+    BatchReplaced(BatchReplacedMeta),
+    BatchCancelled(BatchCancelledMeta),
+    UtxoReorged(UtxoReorgedMeta),
+    SpendReorged(SpendReorgedMeta),
+    UtxoOpeningBalance(UtxoOpeningBalanceMeta),
     UnknownTransaction(Option<serde_json::Value>),
 }
 
@@ -82,6 +87,26 @@ impl TryFrom<SqlxLedgerEvent> for MaybeIgnored {
                         tx.metadata::<BatchBroadcastMeta>()?
                             .ok_or(LedgerError::MissingTxMetadata)?,
                     ),
+                    BATCH_REPLACED_ID => JournalEventMetadata::BatchReplaced(
+                        tx.metadata::<BatchReplacedMeta>()?
+                            .ok_or(LedgerError::MissingTxMetadata)?,
+                    ),
+                    BATCH_CANCELLED_ID => JournalEventMetadata::BatchCancelled(
+                        tx.metadata::<BatchCancelledMeta>()?
+                            .ok_or(LedgerError::MissingTxMetadata)?,
+                    ),
+                    UTXO_REORGED_ID => JournalEventMetadata::UtxoReorged(
+                        tx.metadata::<UtxoReorgedMeta>()?
+                            .ok_or(LedgerError::MissingTxMetadata)?,
+                    ),
+                    SPEND_REORGED_ID => JournalEventMetadata::SpendReorged(
+                        tx.metadata::<SpendReorgedMeta>()?
+                            .ok_or(LedgerError::MissingTxMetadata)?,
+                    ),
+                    UTXO_OPENING_BALANCE_ID => JournalEventMetadata::UtxoOpeningBalance(
+                        tx.metadata::<UtxoOpeningBalanceMeta>()?
+                            .ok_or(LedgerError::MissingTxMetadata)?,
+                    ),
                     _ => JournalEventMetadata::UnknownTransaction(tx.metadata_json),
                 },
             ),
@@ -59,6 +59,11 @@ impl Ledger {
             templates::fix::legacy_batch_created(&inner).await?;
         }
         templates::BatchBroadcast::init(&inner).await?;
+        templates::BatchReplaced::init(&inner).await?;
+        templates::BatchCancelled::init(&inner).await?;
+        templates::UtxoReorged::init(&inner).await?;
+        templates::SpendReorged::init(&inner).await?;
+        templates::UtxoOpeningBalance::init(&inner).await?;
 
         Ok(Self {
             inner,
@@ -107,6 +112,19 @@ impl Ledger {
         Ok(())
     }
 
+    #[instrument(name = "ledger.utxo_opening_balance", skip(self, tx))]
+    pub async fn utxo_opening_balance(
+        &self,
+        tx: Transaction<'_, Postgres>,
+        tx_id: LedgerTransactionId,
+        params: UtxoOpeningBalanceParams,
+    ) -> Result<(), LedgerError> {
+        self.inner
+            .post_transaction_in_tx(tx, tx_id, UTXO_OPENING_BALANCE_CODE, Some(params))
+            .await?;
+        Ok(())
+    }
+
     #[instrument(name = "ledger.utxo_dropped", skip(self, tx), err)]
     pub async fn utxo_dropped(
         &self,
@@ -362,6 +380,133 @@ impl Ledger {
         Ok(())
     }
 
+    #[instrument(name = "ledger.batch_replaced", skip(self, tx))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn batch_replaced(
+        &self,
+        tx: Transaction<'_, Postgres>,
+        tx_id: LedgerTransactionId,
+        create_batch_tx_id: LedgerTransactionId,
+        broadcast_batch_tx_id: Option<LedgerTransactionId>,
+        ledger_account_ids: WalletLedgerAccountIds,
+        replaced_by_batch_id: BatchId,
+        replacement_tx_id: bitcoin::Txid,
+    ) -> Result<(), LedgerError> {
+        let txs = self
+            .inner
+            .transactions()
+            .list_by_ids(std::iter::once(create_batch_tx_id))
+            .await?;
+        let txn = txs.first().ok_or(LedgerError::TransactionNotFound)?;
+        let BatchCreatedMeta {
+            batch_info,
+            tx_summary,
+        } = txn.metadata()?.ok_or(LedgerError::MissingTxMetadata)?;
+        let entries = self
+            .inner
+            .entries()
+            .list_by_transaction_ids(std::iter::once(create_batch_tx_id))
+            .await?;
+        let encumbered_fees = entries
+            .into_values()
+            .flatten()
+            .find_map(|entry| match entry.entry_type.as_str() {
+                "BATCH_CREATED_FR_ENC_CR" => Some(Satoshis::from_btc(entry.units)),
+                _ => None,
+            })
+            .ok_or(LedgerError::ExpectedEntryNotFoundInTx(
+                "Encumbered fees not found",
+            ))?;
+
+        let (broadcast_encumbered_spending_fees, broadcast_change) =
+            if let Some(broadcast_batch_tx_id) = broadcast_batch_tx_id {
+                let txs = self
+                    .inner
+                    .transactions()
+                    .list_by_ids(std::iter::once(broadcast_batch_tx_id))
+                    .await?;
+                let txn = txs.first().ok_or(LedgerError::TransactionNotFound)?;
+                let BatchBroadcastMeta {
+                    encumbered_spending_fees,
+                    tx_summary,
+                    ..
+                } = txn.metadata()?.ok_or(LedgerError::MissingTxMetadata)?;
+                (
+                    encumbered_spending_fees.values().copied().sum(),
+                    tx_summary.change_utxos.iter().map(|u| u.satoshis).sum(),
+                )
+            } else {
+                (Satoshis::ZERO, Satoshis::ZERO)
+            };
+
+        let params = BatchReplacedParams {
+            journal_id: txn.journal_id,
+            ledger_account_ids,
+            encumbered_fees,
+            broadcast_encumbered_spending_fees,
+            broadcast_change,
+            meta: BatchReplacedMeta {
+                batch_info,
+                tx_summary,
+                replaced_by_batch_id,
+                replacement_tx_id,
+            },
+        };
+        self.inner
+            .post_transaction_in_tx(tx, tx_id, BATCH_REPLACED_CODE, Some(params))
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "ledger.batch_cancelled", skip(self, tx))]
+    pub async fn batch_cancelled(
+        &self,
+        tx: Transaction<'_, Postgres>,
+        tx_id: LedgerTransactionId,
+        create_batch_tx_id: LedgerTransactionId,
+        ledger_account_ids: WalletLedgerAccountIds,
+    ) -> Result<(), LedgerError> {
+        let txs = self
+            .inner
+            .transactions()
+            .list_by_ids(std::iter::once(create_batch_tx_id))
+            .await?;
+        let txn = txs.first().ok_or(LedgerError::TransactionNotFound)?;
+        let BatchCreatedMeta {
+            batch_info,
+            tx_summary,
+        } = txn.metadata()?.ok_or(LedgerError::MissingTxMetadata)?;
+        let entries = self
+            .inner
+            .entries()
+            .list_by_transaction_ids(std::iter::once(create_batch_tx_id))
+            .await?;
+        let encumbered_fees = entries
+            .into_values()
+            .flatten()
+            .find_map(|entry| match entry.entry_type.as_str() {
+                "BATCH_CREATED_FR_ENC_CR" => Some(Satoshis::from_btc(entry.units)),
+                _ => None,
+            })
+            .ok_or(LedgerError::ExpectedEntryNotFoundInTx(
+                "Encumbered fees not found",
+            ))?;
+
+        let params = BatchCancelledParams {
+            journal_id: txn.journal_id,
+            ledger_account_ids,
+            encumbered_fees,
+            meta: BatchCancelledMeta {
+                batch_info,
+                tx_summary,
+            },
+        };
+        self.inner
+            .post_transaction_in_tx(tx, tx_id, BATCH_CANCELLED_CODE, Some(params))
+            .await?;
+        Ok(())
+    }
+
     #[instrument(name = "ledger.spend_detected", skip(self, tx))]
     pub async fn spend_detected(
         &self,
@@ -424,6 +569,125 @@ impl Ledger {
         Ok(())
     }
 
+    #[instrument(name = "ledger.utxo_reorged", skip(self, tx), err)]
+    pub async fn utxo_reorged(
+        &self,
+        tx: Transaction<'_, Postgres>,
+        tx_id: LedgerTransactionId,
+        settled_txn_id: LedgerTransactionId,
+        ledger_account_ids: WalletLedgerAccountIds,
+    ) -> Result<(), LedgerError> {
+        let txs = self
+            .inner
+            .transactions()
+            .list_by_ids(std::iter::once(settled_txn_id))
+            .await?;
+        let txn = txs.first().ok_or(LedgerError::TransactionNotFound)?;
+        let UtxoSettledMeta {
+            account_id,
+            wallet_id,
+            keychain_id,
+            outpoint,
+            satoshis,
+            address,
+            confirmation_time,
+            ..
+        } = txn
+            .metadata()
+            .map_err(LedgerError::MismatchedTxMetadata)?
+            .ok_or(LedgerError::MissingTxMetadata)?;
+        let entries = self
+            .inner
+            .entries()
+            .list_by_transaction_ids(std::iter::once(settled_txn_id))
+            .await?;
+        let withdraw_from_effective_settled = entries
+            .into_values()
+            .flatten()
+            .find_map(|entry| match entry.entry_type.as_str() {
+                "SPENT_UTXO_SETTLED_LOG_SET_DR"
+                    if entry.account_id == ledger_account_ids.effective_at_rest_id =>
+                {
+                    Some(Satoshis::from_btc(entry.units))
+                }
+                _ => None,
+            })
+            .unwrap_or(Satoshis::ZERO);
+
+        let params = UtxoReorgedParams {
+            journal_id: txn.journal_id,
+            ledger_account_ids,
+            withdraw_from_effective_settled,
+            meta: UtxoReorgedMeta {
+                account_id,
+                wallet_id,
+                keychain_id,
+                outpoint,
+                satoshis,
+                address,
+                reorged_confirmation_time: confirmation_time,
+                settled_txn_id,
+            },
+        };
+        self.inner
+            .post_transaction_in_tx(tx, tx_id, UTXO_REORGED_CODE, Some(params))
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "ledger.spend_reorged", skip(self, tx), err)]
+    pub async fn spend_reorged(
+        &self,
+        tx: Transaction<'_, Postgres>,
+        tx_id: LedgerTransactionId,
+        settled_txn_id: LedgerTransactionId,
+        ledger_account_ids: WalletLedgerAccountIds,
+    ) -> Result<(), LedgerError> {
+        let txs = self
+            .inner
+            .transactions()
+            .list_by_ids(std::iter::once(settled_txn_id))
+            .await?;
+        let txn = txs.first().ok_or(LedgerError::TransactionNotFound)?;
+        let SpendSettledMeta {
+            batch_info,
+            tx_summary,
+            confirmation_time,
+        } = txn
+            .metadata()
+            .map_err(LedgerError::MismatchedTxMetadata)?
+            .ok_or(LedgerError::MissingTxMetadata)?;
+        let entries = self
+            .inner
+            .entries()
+            .list_by_transaction_ids(std::iter::once(settled_txn_id))
+            .await?;
+        let spent_change = entries
+            .into_values()
+            .flatten()
+            .find_map(|entry| match entry.entry_type.as_str() {
+                "SPEND_SETTLED_CHG_SPENT_SET_DR" => Some(Satoshis::from_btc(entry.units)),
+                _ => None,
+            })
+            .unwrap_or(Satoshis::ZERO);
+
+        let params = SpendReorgedParams {
+            journal_id: txn.journal_id,
+            ledger_account_ids,
+            spent_change,
+            meta: SpendReorgedMeta {
+                batch_info,
+                tx_summary,
+                reorged_confirmation_time: confirmation_time,
+                settled_txn_id,
+            },
+        };
+        self.inner
+            .post_transaction_in_tx(tx, tx_id, SPEND_REORGED_CODE, Some(params))
+            .await?;
+        Ok(())
+    }
+
     #[instrument(name = "ledger.get_ledger_entries_for_txns", skip(self, tx_ids))]
     pub async fn sum_reserved_fees_in_txs(
         &self,
@@ -1,25 +1,35 @@
 mod batch_broadcast;
+mod batch_cancelled;
 mod batch_created;
+mod batch_replaced;
 mod payout_cancelled;
 mod payout_submitted;
 mod shared_meta;
 mod spend_detected;
+mod spend_reorged;
 mod spend_settled;
 mod spent_utxo_settled;
 mod utxo_detected;
 mod utxo_dropped;
+mod utxo_opening_balance;
+mod utxo_reorged;
 mod utxo_settled;
 
 pub use batch_broadcast::*;
+pub use batch_cancelled::*;
 pub use batch_created::*;
+pub use batch_replaced::*;
 pub use payout_cancelled::*;
 pub use payout_submitted::*;
 pub use shared_meta::*;
 pub use spend_detected::*;
+pub use spend_reorged::*;
 pub use spend_settled::*;
 pub use spent_utxo_settled::*;
 pub use utxo_detected::*;
 pub use utxo_dropped::*;
+pub use utxo_opening_balance::*;
+pub use utxo_reorged::*;
 pub use utxo_settled::*;
 
 pub mod fix;
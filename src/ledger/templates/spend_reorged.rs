@@ -0,0 +1,276 @@
+use bdk::BlockTime;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx_ledger::{tx_template::*, JournalId, SqlxLedger, SqlxLedgerError};
+use tracing::instrument;
+
+use super::{shared_meta::*, spend_settled::SpendSettledParams};
+use crate::{
+    ledger::{constants::*, error::LedgerError, WalletLedgerAccountIds},
+    primitives::*,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendReorgedMeta {
+    pub batch_info: Option<BatchWalletInfo>,
+    pub tx_summary: WalletTransactionSummary,
+    pub reorged_confirmation_time: BlockTime,
+    pub settled_txn_id: LedgerTransactionId,
+}
+
+#[derive(Debug)]
+pub struct SpendReorgedParams {
+    pub journal_id: JournalId,
+    pub ledger_account_ids: WalletLedgerAccountIds,
+    pub spent_change: Satoshis,
+    pub meta: SpendReorgedMeta,
+}
+
+impl From<SpendReorgedParams> for TxParams {
+    fn from(
+        SpendReorgedParams {
+            journal_id,
+            ledger_account_ids,
+            spent_change,
+            meta,
+        }: SpendReorgedParams,
+    ) -> Self {
+        let WalletTransactionSummary {
+            total_utxo_in_sats,
+            ref change_utxos,
+            fee_sats,
+            ..
+        } = meta.tx_summary;
+        let change = change_utxos
+            .iter()
+            .fold(Satoshis::ZERO, |s, u| s + u.satoshis)
+            .to_btc();
+        let correlation_id = meta.settled_txn_id;
+        let meta = serde_json::to_value(meta).expect("Couldn't serialize meta");
+        let mut params = Self::default();
+        params.insert("journal_id", journal_id);
+        params.insert("meta", meta);
+        params.insert(
+            "effective_outgoing_account_id",
+            ledger_account_ids.effective_outgoing_id,
+        );
+        params.insert("onchain_fee_account_id", ledger_account_ids.fee_id);
+        params.insert(
+            "onchain_at_rest_account_id",
+            ledger_account_ids.onchain_at_rest_id,
+        );
+        params.insert(
+            "onchain_income_account_id",
+            ledger_account_ids.onchain_incoming_id,
+        );
+        params.insert(
+            "onchain_outgoing_account_id",
+            ledger_account_ids.onchain_outgoing_id,
+        );
+        params.insert("fees", fee_sats.to_btc());
+        params.insert("total_utxo_in", total_utxo_in_sats.to_btc());
+        params.insert("change", change);
+        params.insert("spent_change", spent_change.to_btc());
+        params.insert("correlation_id", correlation_id);
+        params.insert("effective", Utc::now().date_naive());
+        params
+    }
+}
+
+pub struct SpendReorged {}
+
+impl SpendReorged {
+    #[instrument(name = "ledger.spend_reorged.init", skip_all)]
+    pub async fn init(ledger: &SqlxLedger) -> Result<(), LedgerError> {
+        let tx_input = TxInput::builder()
+            .journal_id("params.journal_id")
+            .effective("params.effective")
+            .correlation_id("params.correlation_id")
+            .metadata("params.meta")
+            .description("'Spend tx reorged'")
+            .build()
+            .expect("Couldn't build TxInput");
+        let entries = vec![
+            // EFFECTIVE
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_LOG_OUT_PEN_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{EFFECTIVE_OUTGOING_ID}')"))
+                .direction("DEBIT")
+                .layer("PENDING")
+                .units("params.total_utxo_in - params.change - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_LOG_OUT_PEN_CR'")
+                .currency("'BTC'")
+                .account_id("params.effective_outgoing_account_id")
+                .direction("CREDIT")
+                .layer("PENDING")
+                .units("params.total_utxo_in - params.change - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_LOG_OUT_SET_DR'")
+                .currency("'BTC'")
+                .account_id("params.effective_outgoing_account_id")
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.total_utxo_in - params.change - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_LOG_OUT_SET_CR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{EFFECTIVE_OUTGOING_ID}')"))
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.total_utxo_in - params.change - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            // FEES
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_FEE_PEN_DR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_fee_account_id")
+                .direction("DEBIT")
+                .layer("PENDING")
+                .units("params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_FEE_PEN_CR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_FEE_ID}')"))
+                .direction("CREDIT")
+                .layer("PENDING")
+                .units("params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_FEE_SET_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_FEE_ID}')"))
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_FEE_SET_CR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_fee_account_id")
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            // UTXO
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_UTX_OUT_PEN_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_OUTGOING_ID}')"))
+                .direction("DEBIT")
+                .layer("PENDING")
+                .units("params.total_utxo_in - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_UTX_OUT_PEN_CR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_outgoing_account_id")
+                .direction("CREDIT")
+                .layer("PENDING")
+                .units("params.total_utxo_in - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_UTX_OUT_SET_DR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_outgoing_account_id")
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.total_utxo_in - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_UTX_OUT_SET_CR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_OUTGOING_ID}')"))
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.total_utxo_in - params.fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_PEN_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_INCOMING_ID}')"))
+                .direction("DEBIT")
+                .layer("PENDING")
+                .units("params.change")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_PEN_CR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_income_account_id")
+                .direction("CREDIT")
+                .layer("PENDING")
+                .units("params.change")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_SET_DR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_at_rest_account_id")
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.change")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_SET_CR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_AT_REST_ID}')"))
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.change")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_SPENT_SET_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_AT_REST_ID}')"))
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.spent_change")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'SPEND_REORGED_CHG_SPENT_SET_CR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_at_rest_account_id")
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.spent_change")
+                .build()
+                .expect("Couldn't build entry"),
+        ];
+
+        let params = SpendSettledParams::defs();
+        let template = NewTxTemplate::builder()
+            .id(SPEND_REORGED_ID)
+            .code(SPEND_REORGED_CODE)
+            .tx_input(tx_input)
+            .entries(entries)
+            .params(params)
+            .build()
+            .expect("Couldn't build SPEND_REORGED_CODE");
+        match ledger.tx_templates().create(template).await {
+            Err(SqlxLedgerError::DuplicateKey(_)) => Ok(()),
+            Err(e) => Err(e.into()),
+            Ok(_) => Ok(()),
+        }
+    }
+}
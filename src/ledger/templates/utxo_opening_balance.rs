@@ -0,0 +1,201 @@
+use bdk::BlockTime;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use sqlx_ledger::{tx_template::*, JournalId, SqlxLedger, SqlxLedgerError};
+use tracing::instrument;
+
+use super::shared_meta::*;
+use crate::{
+    ledger::{constants::*, error::LedgerError, WalletLedgerAccountIds},
+    primitives::*,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoOpeningBalanceMeta {
+    pub account_id: AccountId,
+    pub wallet_id: WalletId,
+    pub keychain_id: KeychainId,
+    pub outpoint: bitcoin::OutPoint,
+    pub satoshis: Satoshis,
+    pub address: Address,
+    pub encumbered_spending_fees: EncumberedSpendingFees,
+    pub confirmation_time: BlockTime,
+    pub birthday_height: u32,
+}
+
+#[derive(Debug)]
+pub struct UtxoOpeningBalanceParams {
+    pub journal_id: JournalId,
+    pub ledger_account_ids: WalletLedgerAccountIds,
+    pub meta: UtxoOpeningBalanceMeta,
+}
+
+impl UtxoOpeningBalanceParams {
+    pub fn defs() -> Vec<ParamDefinition> {
+        vec![
+            ParamDefinition::builder()
+                .name("journal_id")
+                .r#type(ParamDataType::UUID)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("onchain_at_rest_account_id")
+                .r#type(ParamDataType::UUID)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("effective_at_rest_account_id")
+                .r#type(ParamDataType::UUID)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("onchain_fee_account_id")
+                .r#type(ParamDataType::UUID)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("amount")
+                .r#type(ParamDataType::DECIMAL)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("encumbered_spending_fees")
+                .r#type(ParamDataType::DECIMAL)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("meta")
+                .r#type(ParamDataType::JSON)
+                .build()
+                .unwrap(),
+            ParamDefinition::builder()
+                .name("effective")
+                .r#type(ParamDataType::DATE)
+                .build()
+                .unwrap(),
+        ]
+    }
+}
+
+impl From<UtxoOpeningBalanceParams> for TxParams {
+    fn from(
+        UtxoOpeningBalanceParams {
+            journal_id,
+            ledger_account_ids: accounts,
+            meta,
+        }: UtxoOpeningBalanceParams,
+    ) -> Self {
+        let amount = meta.satoshis.to_btc();
+        let fees = meta
+            .encumbered_spending_fees
+            .values()
+            .fold(Satoshis::ZERO, |s, v| s + *v)
+            .to_btc();
+        let effective = DateTime::from_timestamp(meta.confirmation_time.timestamp as i64, 0)
+            .expect("Couldn't convert blocktime to NaiveDateTime")
+            .date_naive();
+        let meta = serde_json::to_value(meta).expect("Couldn't serialize meta");
+        let mut params = Self::default();
+        params.insert("journal_id", journal_id);
+        params.insert("onchain_at_rest_account_id", accounts.onchain_at_rest_id);
+        params.insert(
+            "effective_at_rest_account_id",
+            accounts.effective_at_rest_id,
+        );
+        params.insert("onchain_fee_account_id", accounts.fee_id);
+        params.insert("amount", amount);
+        params.insert("encumbered_spending_fees", fees);
+        params.insert("meta", meta);
+        params.insert("effective", effective);
+        params
+    }
+}
+
+pub struct UtxoOpeningBalance {}
+
+impl UtxoOpeningBalance {
+    #[instrument(name = "ledger.utxo_opening_balance.init", skip_all)]
+    pub async fn init(ledger: &SqlxLedger) -> Result<(), LedgerError> {
+        let tx_input = TxInput::builder()
+            .journal_id("params.journal_id")
+            .effective("params.effective")
+            .metadata("params.meta")
+            .description("'Onchain opening balance'")
+            .build()
+            .expect("Couldn't build TxInput");
+        let entries = vec![
+            // EFFECTIVE
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_LOG_SET_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{EFFECTIVE_AT_REST_ID}')"))
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.amount")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_LOG_SET_CR'")
+                .currency("'BTC'")
+                .account_id("params.effective_at_rest_account_id")
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.amount")
+                .build()
+                .expect("Couldn't build entry"),
+            // FEE
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_FR_ENC_DR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_fee_account_id")
+                .direction("DEBIT")
+                .layer("ENCUMBERED")
+                .units("params.encumbered_spending_fees")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_FR_ENC_CR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_FEE_ID}')"))
+                .direction("CREDIT")
+                .layer("ENCUMBERED")
+                .units("params.encumbered_spending_fees")
+                .build()
+                .expect("Couldn't build entry"),
+            // UTXO
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_UTX_SET_DR'")
+                .currency("'BTC'")
+                .account_id(format!("uuid('{ONCHAIN_UTXO_AT_REST_ID}')"))
+                .direction("DEBIT")
+                .layer("SETTLED")
+                .units("params.amount")
+                .build()
+                .expect("Couldn't build entry"),
+            EntryInput::builder()
+                .entry_type("'UTXO_OPENING_BALANCE_UTX_SET_CR'")
+                .currency("'BTC'")
+                .account_id("params.onchain_at_rest_account_id")
+                .direction("CREDIT")
+                .layer("SETTLED")
+                .units("params.amount")
+                .build()
+                .expect("Couldn't build entry"),
+        ];
+
+        let params = UtxoOpeningBalanceParams::defs();
+        let template = NewTxTemplate::builder()
+            .id(UTXO_OPENING_BALANCE_ID)
+            .code(UTXO_OPENING_BALANCE_CODE)
+            .tx_input(tx_input)
+            .entries(entries)
+            .params(params)
+            .build()
+            .expect("Couldn't build UTXO_OPENING_BALANCE_CODE");
+        match ledger.tx_templates().create(template).await {
+            Err(SqlxLedgerError::DuplicateKey(_)) => Ok(()),
+            Err(e) => Err(e.into()),
+            Ok(_) => Ok(()),
+        }
+    }
+}
@@ -15,7 +15,7 @@ mod dev_constants;
 pub mod fees;
 mod job;
 pub mod ledger;
-mod outbox;
+pub mod outbox;
 pub mod payout;
 pub mod payout_queue;
 pub mod primitives;
@@ -43,6 +43,12 @@ impl Augmenter {
             }
             | OutboxEventPayload::UtxoDropped {
                 address, wallet_id, ..
+            }
+            | OutboxEventPayload::UtxoReorged {
+                address, wallet_id, ..
+            }
+            | OutboxEventPayload::UtxoOpeningBalance {
+                address, wallet_id, ..
             } => {
                 let address_info = self
                     .addresses
@@ -62,6 +68,7 @@ impl Augmenter {
             | OutboxEventPayload::PayoutCancelled { id, .. }
             | OutboxEventPayload::PayoutCommitted { id, .. }
             | OutboxEventPayload::PayoutBroadcast { id, .. }
+            | OutboxEventPayload::PayoutReplaced { id, .. }
             | OutboxEventPayload::PayoutSettled { id, .. } => {
                 let payout = self
                     .payouts
@@ -76,6 +83,10 @@ impl Augmenter {
                     address: None,
                 })
             }
+            OutboxEventPayload::SpendReorged { .. } => Ok(Augmentation {
+                address: None,
+                payout: None,
+            }),
         }
     }
 }
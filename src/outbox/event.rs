@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     fees,
-    ledger::{BatchBroadcastMeta, BatchCreatedMeta, JournalEventMetadata, SpendSettledMeta},
+    ledger::{
+        BatchBroadcastMeta, BatchCreatedMeta, BatchReplacedMeta, JournalEventMetadata,
+        SpendReorgedMeta, SpendSettledMeta,
+    },
     primitives::*,
 };
 
@@ -81,6 +84,34 @@ pub enum OutboxEventPayload {
         // detected -> dropped -> detected -> dropped sequence
         ledger_event_id: Option<SqlxLedgerEventId>,
     },
+    UtxoReorged {
+        tx_id: bitcoin::Txid,
+        vout: u32,
+        satoshis: Satoshis,
+        address: Address,
+        wallet_id: WalletId,
+        keychain_id: KeychainId,
+        reorged_confirmation_time: bitcoin::BlockTime,
+    },
+    SpendReorged {
+        tx_id: bitcoin::Txid,
+        wallet_id: WalletId,
+        keychain_id: KeychainId,
+        batch_id: Option<BatchId>,
+        total_utxo_in_sats: Satoshis,
+        fee_sats: Satoshis,
+        reorged_confirmation_time: bitcoin::BlockTime,
+    },
+    UtxoOpeningBalance {
+        tx_id: bitcoin::Txid,
+        vout: u32,
+        satoshis: Satoshis,
+        address: Address,
+        wallet_id: WalletId,
+        keychain_id: KeychainId,
+        confirmation_time: bitcoin::BlockTime,
+        birthday_height: u32,
+    },
     PayoutSubmitted {
         id: PayoutId,
         profile_id: ProfileId,
@@ -121,6 +152,19 @@ pub enum OutboxEventPayload {
         destination: PayoutDestination,
         proportional_fee: Satoshis,
     },
+    PayoutReplaced {
+        id: PayoutId,
+        vout: u32,
+        profile_id: ProfileId,
+        wallet_id: WalletId,
+        payout_queue_id: PayoutQueueId,
+        batch_id: BatchId,
+        tx_id: bitcoin::Txid,
+        replaced_batch_id: BatchId,
+        replaced_tx_id: bitcoin::Txid,
+        satoshis: Satoshis,
+        destination: PayoutDestination,
+    },
     PayoutSettled {
         id: PayoutId,
         vout: u32,
@@ -167,6 +211,39 @@ impl From<JournalEventMetadata> for Vec<OutboxEventPayload> {
                 keychain_id: meta.keychain_id,
                 ledger_event_id: Some(ledger_event_id),
             }),
+            UtxoReorged(meta) => res.push(OutboxEventPayload::UtxoReorged {
+                tx_id: meta.outpoint.txid,
+                vout: meta.outpoint.vout,
+                satoshis: meta.satoshis,
+                address: meta.address,
+                wallet_id: meta.wallet_id,
+                keychain_id: meta.keychain_id,
+                reorged_confirmation_time: meta.reorged_confirmation_time,
+            }),
+            SpendReorged(SpendReorgedMeta {
+                batch_info,
+                tx_summary,
+                reorged_confirmation_time,
+                ..
+            }) => res.push(OutboxEventPayload::SpendReorged {
+                tx_id: tx_summary.bitcoin_tx_id,
+                wallet_id: tx_summary.wallet_id,
+                keychain_id: tx_summary.current_keychain_id,
+                batch_id: batch_info.map(|info| info.batch_id),
+                total_utxo_in_sats: tx_summary.total_utxo_in_sats,
+                fee_sats: tx_summary.fee_sats,
+                reorged_confirmation_time,
+            }),
+            UtxoOpeningBalance(meta) => res.push(OutboxEventPayload::UtxoOpeningBalance {
+                tx_id: meta.outpoint.txid,
+                vout: meta.outpoint.vout,
+                satoshis: meta.satoshis,
+                address: meta.address,
+                wallet_id: meta.wallet_id,
+                keychain_id: meta.keychain_id,
+                confirmation_time: meta.confirmation_time,
+                birthday_height: meta.birthday_height,
+            }),
             PayoutSubmitted(meta) => res.push(OutboxEventPayload::PayoutSubmitted {
                 id: meta.payout_id,
                 wallet_id: meta.wallet_id,
@@ -240,6 +317,28 @@ impl From<JournalEventMetadata> for Vec<OutboxEventPayload> {
                     })
                 }
             }
+            BatchReplaced(BatchReplacedMeta {
+                batch_info,
+                tx_summary,
+                replaced_by_batch_id,
+                replacement_tx_id,
+            }) => {
+                for payout in batch_info.included_payouts {
+                    res.push(OutboxEventPayload::PayoutReplaced {
+                        id: payout.id,
+                        vout: payout.vout_in_tx,
+                        wallet_id: batch_info.wallet_id,
+                        payout_queue_id: batch_info.payout_queue_id,
+                        batch_id: replaced_by_batch_id,
+                        profile_id: payout.profile_id,
+                        tx_id: replacement_tx_id,
+                        replaced_batch_id: batch_info.batch_id,
+                        replaced_tx_id: tx_summary.bitcoin_tx_id,
+                        satoshis: payout.satoshis,
+                        destination: payout.destination,
+                    })
+                }
+            }
             SpendSettled(SpendSettledMeta {
                 batch_info: Some(batch_info),
                 tx_summary,
@@ -32,6 +32,14 @@ pub enum PayoutEvent {
     Cancelled {
         executed_by: ProfileId,
     },
+    CommittedToReplacementBatch {
+        batch_id: BatchId,
+        replaced_batch_id: BatchId,
+        outpoint: bitcoin::OutPoint,
+    },
+    BatchCancelled {
+        batch_id: BatchId,
+    },
 }
 
 #[derive(EsEntity, Builder)]
@@ -69,6 +77,29 @@ impl Payout {
         Ok(())
     }
 
+    pub fn commit_to_replacement_batch(&mut self, batch_id: BatchId, outpoint: bitcoin::OutPoint) {
+        let replaced_batch_id = match (self.batch_id, self.outpoint) {
+            (Some(replaced_batch_id), Some(_)) => replaced_batch_id,
+            _ => return,
+        };
+        self.batch_id = Some(batch_id);
+        self.outpoint = Some(outpoint);
+        self.events.push(PayoutEvent::CommittedToReplacementBatch {
+            batch_id,
+            replaced_batch_id,
+            outpoint,
+        });
+    }
+
+    pub fn batch_cancelled(&mut self, batch_id: BatchId) {
+        if self.batch_id != Some(batch_id) {
+            return;
+        }
+        self.batch_id = None;
+        self.outpoint = None;
+        self.events.push(PayoutEvent::BatchCancelled { batch_id });
+    }
+
     pub fn is_cancelled(&self) -> bool {
         for event in self.events.iter_all() {
             if let PayoutEvent::Cancelled { .. } = event {
@@ -117,6 +148,16 @@ impl TryFromEvents<PayoutEvent> for Payout {
                 PayoutEvent::CommittedToBatch { batch_id, outpoint } => {
                     builder = builder.batch_id(*batch_id).outpoint(*outpoint);
                 }
+                PayoutEvent::CommittedToReplacementBatch {
+                    batch_id, outpoint, ..
+                } => {
+                    builder = builder.batch_id(*batch_id).outpoint(*outpoint);
+                }
+                PayoutEvent::BatchCancelled { .. } => {
+                    builder = builder
+                        .batch_id(None::<BatchId>)
+                        .outpoint(None::<bitcoin::OutPoint>);
+                }
                 _ => (),
             }
         }
@@ -174,6 +215,16 @@ impl IntoEvents<PayoutEvent> for NewPayout {
     }
 }
 
+impl From<&Payout> for TxPayout {
+    fn from(payout: &Payout) -> Self {
+        (
+            uuid::Uuid::from(payout.id),
+            payout.destination.onchain_address().clone(),
+            payout.satoshis,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rust_decimal::Decimal;
@@ -244,4 +295,62 @@ mod tests {
         let result = payout.cancel_payout(payout.profile_id);
         assert!(matches!(result, Err(PayoutError::PayoutAlreadyCommitted)));
     }
+
+    #[test]
+    fn batch_cancelled_returns_payout_to_queue() {
+        let mut events = init_events();
+        let batch_id = BatchId::new();
+        events.push(PayoutEvent::CommittedToBatch {
+            batch_id,
+            outpoint: bitcoin::OutPoint {
+                txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+                    .parse()
+                    .unwrap(),
+                vout: 0,
+            },
+        });
+        let mut payout = Payout::try_from_events(events).unwrap();
+
+        payout.batch_cancelled(BatchId::new());
+        assert_eq!(payout.batch_id, Some(batch_id));
+
+        payout.batch_cancelled(batch_id);
+        assert_eq!(payout.batch_id, None);
+        assert_eq!(payout.outpoint, None);
+        assert!(payout.cancel_payout(payout.profile_id).is_ok());
+    }
+
+    #[test]
+    fn commit_to_replacement_batch_updates_outpoint() {
+        let mut events = init_events();
+        let replaced_batch_id = BatchId::new();
+        events.push(PayoutEvent::CommittedToBatch {
+            batch_id: replaced_batch_id,
+            outpoint: bitcoin::OutPoint {
+                txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+                    .parse()
+                    .unwrap(),
+                vout: 3,
+            },
+        });
+        let mut payout = Payout::try_from_events(events).unwrap();
+
+        let batch_id = BatchId::new();
+        let tx_id: bitcoin::Txid =
+            "b9a4b9a0b4c86d1e0ef4e0bd1f3e1b8a4cd1e6d3a4c6b2f0a1d1e3c5f7a9b0c2"
+                .parse()
+                .unwrap();
+        let outpoint = bitcoin::OutPoint {
+            txid: tx_id,
+            vout: 5,
+        };
+        payout.commit_to_replacement_batch(batch_id, outpoint);
+
+        assert_eq!(payout.batch_id, Some(batch_id));
+        assert_eq!(payout.outpoint, Some(outpoint));
+        assert!(matches!(
+            payout.events.iter_all().last().unwrap(),
+            PayoutEvent::CommittedToReplacementBatch { replaced_batch_id: id, .. } if *id == replaced_batch_id
+        ));
+    }
 }
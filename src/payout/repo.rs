@@ -241,6 +241,70 @@ impl Payouts {
         Ok(())
     }
 
+    #[instrument(name = "payouts.move_to_replacement_batch", skip(self, op, payouts))]
+    pub async fn move_to_replacement_batch(
+        &self,
+        op: &mut impl es_entity::AtomicOperation,
+        payouts: impl IntoIterator<Item = (Payout, bitcoin::OutPoint)>,
+        batch_id: BatchId,
+    ) -> Result<(), PayoutError> {
+        let mut ids = Vec::new();
+        let mut all_events: Vec<EntityEvents<PayoutEvent>> = payouts
+            .into_iter()
+            .map(|(mut p, outpoint)| {
+                p.commit_to_replacement_batch(batch_id, outpoint);
+                ids.push(uuid::Uuid::from(p.id));
+                p.events
+            })
+            .collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.persist_events_batch(op, &mut all_events).await?;
+
+        sqlx::query!(
+            r#"UPDATE bria_payouts SET batch_id = $1 WHERE id = ANY($2)"#,
+            batch_id as BatchId,
+            &ids[..],
+        )
+        .execute(op.as_executor())
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "payouts.return_to_queue", skip(self, op, payouts))]
+    pub async fn return_to_queue(
+        &self,
+        op: &mut impl es_entity::AtomicOperation,
+        payouts: impl IntoIterator<Item = Payout>,
+        cancelled_batch_id: BatchId,
+    ) -> Result<(), PayoutError> {
+        let mut ids = Vec::new();
+        let mut all_events: Vec<EntityEvents<PayoutEvent>> = payouts
+            .into_iter()
+            .map(|mut p| {
+                p.batch_cancelled(cancelled_batch_id);
+                ids.push(uuid::Uuid::from(p.id));
+                p.events
+            })
+            .collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.persist_events_batch(op, &mut all_events).await?;
+
+        sqlx::query!(
+            r#"UPDATE bria_payouts SET batch_id = NULL WHERE id = ANY($1) AND batch_id = $2"#,
+            &ids[..],
+            cancelled_batch_id as BatchId,
+        )
+        .execute(op.as_executor())
+        .await?;
+        Ok(())
+    }
+
     pub async fn average_payout_per_batch(
         &self,
         wallet_id: WalletId,
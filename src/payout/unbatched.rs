@@ -10,6 +10,7 @@ pub struct UnbatchedPayouts {
     inner: HashMap<WalletId, Vec<UnbatchedPayout>>,
     shifted: HashMap<PayoutId, UnbatchedPayout>,
     simulated_payout: Option<(WalletId, TxPayout)>,
+    replaced_payouts: HashMap<WalletId, Vec<TxPayout>>,
     pub(super) batch_id: Option<BatchId>,
     pub(super) batched: Vec<UnbatchedPayout>,
 }
@@ -20,6 +21,7 @@ impl UnbatchedPayouts {
             inner,
             batch_id: None,
             simulated_payout: None,
+            replaced_payouts: HashMap::new(),
             shifted: HashMap::new(),
             batched: Vec::new(),
         }
@@ -28,7 +30,12 @@ impl UnbatchedPayouts {
     pub fn wallet_ids(&self) -> Vec<WalletId> {
         std::iter::once(self.simulated_payout.as_ref())
             .filter_map(|p| p.map(|(id, _)| id))
-            .chain(self.inner.keys())
+            .chain(self.replaced_payouts.keys())
+            .chain(
+                self.inner
+                    .keys()
+                    .filter(|id| !self.replaced_payouts.contains_key(id)),
+            )
             .copied()
             .collect()
     }
@@ -41,6 +48,13 @@ impl UnbatchedPayouts {
         self.simulated_payout = Some((wallet_id, payout));
     }
 
+    pub fn include_replaced_payouts(&mut self, payouts: &HashMap<WalletId, Vec<Payout>>) {
+        self.replaced_payouts = payouts
+            .iter()
+            .map(|(wallet_id, payouts)| (*wallet_id, payouts.iter().map(TxPayout::from).collect()))
+            .collect();
+    }
+
     pub fn n_not_batched(&self) -> usize {
         self.shifted.len() + self.inner.values().fold(0, |acc, v| acc + v.len())
     }
@@ -82,6 +96,10 @@ impl UnbatchedPayouts {
             .iter()
             .map(|(wallet_id, payouts)| (*wallet_id, payouts.iter().map(TxPayout::from).collect()))
             .collect();
+        for (wallet_id, payouts) in self.replaced_payouts.iter() {
+            let entry = ret.entry(*wallet_id).or_default();
+            entry.splice(0..0, payouts.iter().cloned());
+        }
         if let Some((wallet_id, payout)) = &self.simulated_payout {
             let entry = ret.entry(*wallet_id).or_default();
             entry.insert(0, payout.clone());
@@ -1,16 +1,32 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use crate::primitives::{Satoshis, TxPriority};
+use super::error::PayoutQueueError;
+use crate::primitives::{bitcoin::FeeRate, Satoshis, TxPriority};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PayoutQueueConfig {
     pub tx_priority: TxPriority,
     #[serde(default)]
     pub cpfp_payouts_after_mins: Option<u32>,
     #[serde(default)]
     pub cpfp_payouts_after_blocks: Option<u32>,
+    #[serde(default)]
+    pub rbf_after_mins: Option<u32>,
+    #[serde(default)]
+    pub rbf_after_blocks: Option<u32>,
+    #[serde(default)]
+    pub add_payouts_to_unconfirmed_batch: bool,
     pub force_min_change_sats: Option<Satoshis>,
+    /// Lower bound (sats/vbyte) the estimated fee rate is raised to
+    #[serde(default)]
+    pub min_fee_rate: Option<f64>,
+    /// Upper bound (sats/vbyte) the estimated fee rate is capped at
+    #[serde(default)]
+    pub max_fee_rate: Option<f64>,
+    /// Wait for fees to drop instead of capping them at `max_fee_rate`
+    #[serde(default)]
+    pub defer_above_max_fee_rate: bool,
     pub consolidate_deprecated_keychains: bool,
     pub trigger: PayoutQueueTrigger,
 }
@@ -32,6 +48,57 @@ impl PayoutQueueConfig {
     pub fn should_cpfp(&self) -> bool {
         self.cpfp_payouts_after_mins.is_some() || self.cpfp_payouts_after_blocks.is_some()
     }
+
+    pub fn rbf_batches_created_before(&self) -> chrono::DateTime<chrono::Utc> {
+        let now = chrono::Utc::now();
+        self.rbf_after_mins
+            .map(|mins| now - Duration::from_secs(mins as u64 * 60))
+            .unwrap_or(now)
+    }
+
+    pub fn rbf_batches_detected_before_block(&self, current_height: u32) -> u32 {
+        self.rbf_after_blocks
+            .map(|blocks| (current_height + 1).max(blocks) - blocks)
+            .unwrap_or(current_height + 1)
+    }
+
+    pub fn should_rbf(&self) -> bool {
+        self.rbf_after_mins.is_some() || self.rbf_after_blocks.is_some()
+    }
+
+    /// Rejects fee rate bounds that `bounded_fee_rate` can't apply consistently.
+    pub fn validate(&self) -> Result<(), PayoutQueueError> {
+        for rate in [self.min_fee_rate, self.max_fee_rate].into_iter().flatten() {
+            if !rate.is_finite() || rate < 0.0 {
+                return Err(PayoutQueueError::InvalidFeeRate(rate));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_fee_rate, self.max_fee_rate) {
+            if min > max {
+                return Err(PayoutQueueError::MinFeeRateAboveMax(min, max));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn bounded_fee_rate(&self, fee_rate: FeeRate) -> FeeRate {
+        let mut sats_per_vb = fee_rate.as_sat_per_vb() as f64;
+        if let Some(max) = self.max_fee_rate {
+            sats_per_vb = sats_per_vb.min(max);
+        }
+        if let Some(min) = self.min_fee_rate {
+            sats_per_vb = sats_per_vb.max(min);
+        }
+        FeeRate::from_sat_per_vb(sats_per_vb as f32)
+    }
+
+    pub fn should_defer_batch(&self, fee_rate: FeeRate) -> bool {
+        self.defer_above_max_fee_rate
+            && self
+                .max_fee_rate
+                .map(|max| fee_rate.as_sat_per_vb() as f64 > max)
+                .unwrap_or(false)
+    }
 }
 
 #[serde_with::serde_as]
@@ -56,7 +123,13 @@ impl Default for PayoutQueueConfig {
             },
             cpfp_payouts_after_mins: None,
             cpfp_payouts_after_blocks: None,
+            rbf_after_mins: None,
+            rbf_after_blocks: None,
+            add_payouts_to_unconfirmed_batch: false,
             force_min_change_sats: None,
+            min_fee_rate: None,
+            max_fee_rate: None,
+            defer_above_max_fee_rate: false,
         }
     }
 }
@@ -64,3 +137,66 @@ impl Default for PayoutQueueConfig {
 fn default_interval() -> Duration {
     Duration::from_secs(60)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_rate_is_clamped_into_bounds() {
+        let config = PayoutQueueConfig {
+            min_fee_rate: Some(2.0),
+            max_fee_rate: Some(50.0),
+            ..PayoutQueueConfig::default()
+        };
+        assert_eq!(
+            config.bounded_fee_rate(FeeRate::from_sat_per_vb(1.0)),
+            FeeRate::from_sat_per_vb(2.0)
+        );
+        assert_eq!(
+            config.bounded_fee_rate(FeeRate::from_sat_per_vb(10.0)),
+            FeeRate::from_sat_per_vb(10.0)
+        );
+        assert_eq!(
+            config.bounded_fee_rate(FeeRate::from_sat_per_vb(300.0)),
+            FeeRate::from_sat_per_vb(50.0)
+        );
+        assert!(!config.should_defer_batch(FeeRate::from_sat_per_vb(300.0)));
+    }
+
+    #[test]
+    fn batch_is_deferred_above_max_fee_rate() {
+        let config = PayoutQueueConfig {
+            max_fee_rate: Some(50.0),
+            defer_above_max_fee_rate: true,
+            ..PayoutQueueConfig::default()
+        };
+        assert!(config.should_defer_batch(FeeRate::from_sat_per_vb(51.0)));
+        assert!(!config.should_defer_batch(FeeRate::from_sat_per_vb(50.0)));
+        assert!(!PayoutQueueConfig::default().should_defer_batch(FeeRate::from_sat_per_vb(1000.0)));
+    }
+
+    #[test]
+    fn inconsistent_fee_rate_bounds_are_rejected() {
+        let config = |min_fee_rate, max_fee_rate| PayoutQueueConfig {
+            min_fee_rate,
+            max_fee_rate,
+            ..PayoutQueueConfig::default()
+        };
+        assert!(config(Some(2.0), Some(50.0)).validate().is_ok());
+        assert!(config(Some(50.0), Some(50.0)).validate().is_ok());
+        assert!(config(None, None).validate().is_ok());
+        assert!(matches!(
+            config(Some(51.0), Some(50.0)).validate(),
+            Err(PayoutQueueError::MinFeeRateAboveMax(_, _))
+        ));
+        assert!(matches!(
+            config(Some(-1.0), None).validate(),
+            Err(PayoutQueueError::InvalidFeeRate(_))
+        ));
+        assert!(matches!(
+            config(None, Some(f64::NAN)).validate(),
+            Err(PayoutQueueError::InvalidFeeRate(_))
+        ));
+    }
+}
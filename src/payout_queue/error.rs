@@ -8,6 +8,10 @@ pub enum PayoutQueueError {
     EsEntityError(es_entity::EsEntityError),
     #[error("PayoutQueueError - CursorDestructureError: {0}")]
     CursorDestructureError(#[from] es_entity::CursorDestructureError),
+    #[error("PayoutQueueError - InvalidFeeRate: {0} is not a valid fee rate")]
+    InvalidFeeRate(f64),
+    #[error("PayoutQueueError - MinFeeRateAboveMax: min fee rate {0} is above max fee rate {1}")]
+    MinFeeRateAboveMax(f64, f64),
 }
 
 es_entity::from_es_entity_error!(PayoutQueueError);
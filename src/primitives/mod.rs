@@ -74,10 +74,10 @@ pub mod bitcoin {
             bip32::{self, DerivationPath, ExtendedPubKey, Fingerprint},
             blockdata::{
                 script::{Script, ScriptBuf},
-                transaction::{OutPoint, Transaction, TxOut},
+                transaction::{OutPoint, Sequence, Transaction, TxIn, TxOut},
             },
             consensus,
-            hash_types::Txid,
+            hash_types::{BlockHash, Txid},
             psbt, Address as BdkAddress, Network,
         },
         descriptor::ExtendedDescriptor,
@@ -112,7 +112,7 @@ pub mod bitcoin {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, clap::ValueEnum, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize, clap::ValueEnum, PartialEq, Eq, Hash, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum TxPriority {
     NextBlock,
@@ -5,6 +5,7 @@ use thiserror::Error;
 
 use std::collections::HashMap;
 
+use super::error::SigningSessionError;
 use crate::{
     primitives::{bitcoin::psbt, *},
     xpub::SigningClientError,
@@ -30,13 +31,49 @@ pub enum SigningSessionEvent {
     RemoteSigningCompleted {
         signed_psbt: psbt::PartiallySignedTransaction,
     },
+    SigningNoLongerNeeded {},
+    BatchCancelled {},
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SigningSessionState {
     Initialized,
     Failed,
     Complete,
+    NoLongerNeeded,
+    Cancelled,
+}
+
+impl SigningSessionState {
+    /// Types of the events that leave a session in this state when they are the latest one.
+    pub fn event_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Initialized => &["initialized"],
+            Self::Failed => &["signing_attempt_failed"],
+            Self::Complete => &[
+                "remote_signing_completed",
+                "externally_signed_psbt_submitted",
+            ],
+            Self::NoLongerNeeded => &["signing_no_longer_needed"],
+        }
+    }
+}
+
+impl std::str::FromStr for SigningSessionState {
+    type Err = SigningSessionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace(['_', '-'], "").as_str() {
+            "initialized" => Ok(Self::Initialized),
+            "failed" => Ok(Self::Failed),
+            "complete" => Ok(Self::Complete),
+            "nolongerneeded" => Ok(Self::NoLongerNeeded),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(SigningSessionError::UnknownSigningSessionState(
+                s.to_string(),
+            )),
+        }
+    }
 }
 
 #[derive(EsEntity, Builder)]
@@ -67,10 +104,33 @@ impl SigningSession {
             .push(SigningSessionEvent::ExternallySignedPsbtSubmitted { signed_psbt })
     }
 
+    pub fn signing_no_longer_needed(&mut self) {
+        if self.is_completed() || self.is_no_longer_needed() {
+            return;
+        }
+        self.events
+            .push(SigningSessionEvent::SigningNoLongerNeeded {});
+    }
+
+    pub fn batch_cancelled(&mut self) {
+        if self.is_cancelled() {
+            return;
+        }
+        self.events.push(SigningSessionEvent::BatchCancelled {});
+    }
+
     pub fn is_completed(&self) -> bool {
         self.signed_psbt().is_some()
     }
 
+    pub fn is_no_longer_needed(&self) -> bool {
+        matches!(self.state(), SigningSessionState::NoLongerNeeded)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.state(), SigningSessionState::Cancelled)
+    }
+
     pub fn signed_psbt(&self) -> Option<&psbt::PartiallySignedTransaction> {
         let mut ret = None;
         for event in self.events.iter_all() {
@@ -96,6 +156,28 @@ impl SigningSession {
         ret
     }
 
+    pub fn failures(
+        &self,
+    ) -> impl Iterator<Item = (chrono::DateTime<chrono::Utc>, &SigningFailureReason)> + '_ {
+        self.events.iter_persisted().filter_map(|e| match &e.event {
+            SigningSessionEvent::SigningAttemptFailed { reason } => Some((e.recorded_at, reason)),
+            _ => None,
+        })
+    }
+
+    pub fn attempt_count(&self) -> usize {
+        self.events
+            .iter_all()
+            .filter(|e| {
+                matches!(
+                    e,
+                    SigningSessionEvent::SigningAttemptFailed { .. }
+                        | SigningSessionEvent::RemoteSigningCompleted { .. }
+                )
+            })
+            .count()
+    }
+
     pub fn state(&self) -> SigningSessionState {
         let mut ret = SigningSessionState::Initialized;
         for event in self.events.iter_all() {
@@ -105,6 +187,10 @@ impl SigningSession {
                 SigningSessionEvent::ExternallySignedPsbtSubmitted { .. } => {
                     SigningSessionState::Complete
                 }
+                SigningSessionEvent::SigningNoLongerNeeded {} => {
+                    SigningSessionState::NoLongerNeeded
+                }
+                SigningSessionEvent::BatchCancelled {} => SigningSessionState::Cancelled,
                 _ => ret,
             };
         }
@@ -133,6 +219,33 @@ pub struct BatchSigningSession {
     pub xpub_sessions: HashMap<XPubFingerprint, SigningSession>,
 }
 
+pub struct SigningRequirement {
+    threshold: usize,
+    xpubs: Vec<XPubFingerprint>,
+}
+
+impl SigningRequirement {
+    pub fn new(threshold: usize, xpubs: impl IntoIterator<Item = XPubFingerprint>) -> Self {
+        Self {
+            threshold,
+            xpubs: xpubs.into_iter().collect(),
+        }
+    }
+
+    pub fn is_met(&self, sessions: &HashMap<XPubFingerprint, SigningSession>) -> bool {
+        self.xpubs
+            .iter()
+            .filter(|xpub| {
+                sessions
+                    .get(xpub)
+                    .map(|session| session.is_completed())
+                    .unwrap_or(false)
+            })
+            .count()
+            >= self.threshold
+    }
+}
+
 #[derive(Builder, Clone, Debug)]
 pub struct NewSigningSession {
     #[builder(private)]
@@ -187,3 +300,139 @@ impl TryFromEvents<SigningSessionEvent> for SigningSession {
         builder.events(events).build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(xpub_fingerprint: XPubFingerprint) -> SigningSession {
+        let unsigned_psbt =
+            psbt::PartiallySignedTransaction::from_unsigned_tx(bitcoin::Transaction {
+                version: 2,
+                lock_time: bdk::bitcoin::absolute::LockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            })
+            .unwrap();
+        let new_session = NewSigningSession::builder()
+            .account_id(AccountId::new())
+            .batch_id(BatchId::new())
+            .xpub_fingerprint(xpub_fingerprint)
+            .unsigned_psbt(unsigned_psbt)
+            .build()
+            .unwrap();
+        SigningSession::try_from_events(new_session.into_events()).unwrap()
+    }
+
+    #[test]
+    fn signing_requirement_is_met_at_threshold() {
+        let fingerprints: Vec<XPubFingerprint> = ["6f2fa1b2", "492ef832", "1ff51810"]
+            .iter()
+            .map(|fp| fp.parse().unwrap())
+            .collect();
+        let mut sessions: HashMap<_, _> =
+            fingerprints.iter().map(|fp| (*fp, session(*fp))).collect();
+        let requirement = SigningRequirement::new(2, fingerprints.clone());
+        assert!(!requirement.is_met(&sessions));
+
+        let signed_psbt = sessions[&fingerprints[0]].unsigned_psbt.clone();
+        sessions
+            .get_mut(&fingerprints[0])
+            .unwrap()
+            .remote_signing_complete(signed_psbt.clone());
+        assert!(!requirement.is_met(&sessions));
+
+        sessions
+            .get_mut(&fingerprints[2])
+            .unwrap()
+            .submit_externally_signed_psbt(signed_psbt);
+        assert!(requirement.is_met(&sessions));
+    }
+
+    #[test]
+    fn attempt_count_includes_failed_and_successful_remote_signing() {
+        let mut session = session("6f2fa1b2".parse().unwrap());
+        assert_eq!(session.attempt_count(), 0);
+        session.attempt_failed(SigningFailureReason::SignerConfigMissing);
+        session.attempt_failed(SigningFailureReason::SignerConfigMissing);
+        let signed_psbt = session.unsigned_psbt.clone();
+        session.remote_signing_complete(signed_psbt);
+        assert_eq!(session.attempt_count(), 3);
+    }
+
+    #[test]
+    fn parse_signing_session_state() {
+        assert_eq!(
+            "NoLongerNeeded".parse::<SigningSessionState>().unwrap(),
+            SigningSessionState::NoLongerNeeded
+        );
+        assert_eq!(
+            "no_longer_needed".parse::<SigningSessionState>().unwrap(),
+            SigningSessionState::NoLongerNeeded
+        );
+        assert_eq!(
+            "complete".parse::<SigningSessionState>().unwrap(),
+            SigningSessionState::Complete
+        );
+        assert!("unknown".parse::<SigningSessionState>().is_err());
+    }
+
+    #[test]
+    fn latest_event_type_matches_state() {
+        let assert_latest_event_matches = |session: &SigningSession| {
+            let event = serde_json::to_value(session.events.iter_all().last().unwrap()).unwrap();
+            assert!(session
+                .state()
+                .event_types()
+                .contains(&event["type"].as_str().unwrap()));
+        };
+        let mut signed = session("6f2fa1b2".parse().unwrap());
+        assert_latest_event_matches(&signed);
+        signed.attempt_failed(SigningFailureReason::SignerConfigMissing);
+        assert_latest_event_matches(&signed);
+        let signed_psbt = signed.unsigned_psbt.clone();
+        signed.submit_externally_signed_psbt(signed_psbt.clone());
+        assert_latest_event_matches(&signed);
+        signed.remote_signing_complete(signed_psbt);
+        assert_latest_event_matches(&signed);
+
+        let mut not_needed = session("6f2fa1b2".parse().unwrap());
+        not_needed.signing_no_longer_needed();
+        assert_latest_event_matches(&not_needed);
+    }
+
+    #[test]
+    fn signing_no_longer_needed_only_applies_to_pending_sessions() {
+        let fingerprint: XPubFingerprint = "6f2fa1b2".parse().unwrap();
+        let mut pending = session(fingerprint);
+        pending.signing_no_longer_needed();
+        assert!(pending.is_no_longer_needed());
+        assert!(!pending.is_completed());
+
+        let mut completed = session(fingerprint);
+        let signed_psbt = completed.unsigned_psbt.clone();
+        completed.remote_signing_complete(signed_psbt);
+        completed.signing_no_longer_needed();
+        assert!(!completed.is_no_longer_needed());
+        assert!(completed.is_completed());
+    }
+
+    #[test]
+    fn batch_cancelled_invalidates_partially_signed_sessions() {
+        let fingerprint: XPubFingerprint = "6f2fa1b2".parse().unwrap();
+        let mut completed = session(fingerprint);
+        let signed_psbt = completed.unsigned_psbt.clone();
+        completed.remote_signing_complete(signed_psbt);
+        completed.batch_cancelled();
+        completed.batch_cancelled();
+        assert!(completed.is_cancelled());
+        assert_eq!(
+            completed
+                .events
+                .iter_all()
+                .filter(|e| matches!(e, SigningSessionEvent::BatchCancelled {}))
+                .count(),
+            1
+        );
+    }
+}
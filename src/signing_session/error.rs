@@ -8,5 +8,7 @@ pub enum SigningSessionError {
     EsEntityError(es_entity::EsEntityError),
     #[error("SigningSessionError - CursorDestructureError: {0}")]
     CursorDestructureError(#[from] es_entity::CursorDestructureError),
+    #[error("SigningSessionError - UnknownSigningSessionState: {0}")]
+    UnknownSigningSessionState(String),
 }
 es_entity::from_es_entity_error!(SigningSessionError);
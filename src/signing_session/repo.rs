@@ -6,6 +6,13 @@ use super::{entity::*, error::SigningSessionError};
 use crate::primitives::*;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Default)]
+pub struct SigningSessionsListFilter {
+    pub batch_id: Option<BatchId>,
+    pub xpub_fingerprint: Option<XPubFingerprint>,
+    pub state: Option<SigningSessionState>,
+}
+
 #[derive(EsRepo, Clone)]
 #[es_repo(
     entity = "SigningSession",
@@ -58,52 +65,26 @@ impl SigningSessions {
         account_id: AccountId,
         batch_id: BatchId,
     ) -> Result<Option<BatchSigningSession>, SigningSessionError> {
-        let mut signing_sessions = Vec::new();
-        let mut query = es_entity::PaginatedQueryArgs::<
-            signing_session_cursor::SigningSessionsByCreatedAtCursor,
-        > {
-            first: Default::default(),
-            after: None,
+        let filter = SigningSessionsListFilter {
+            batch_id: Some(batch_id),
+            ..Default::default()
         };
-
+        let mut query = es_entity::PaginatedQueryArgs::default();
+        let mut xpub_sessions = HashMap::new();
         loop {
-            let es_entity::PaginatedQueryArgs { first, after } = query;
-            let (id, created_at) = if let Some(after) = after {
-                (Some(after.id), Some(after.created_at))
-            } else {
-                (None, None)
-            };
-
-            let (entities, has_next_page) = es_entity::es_query!(
-                tbl_prefix = "bria",
-                r#"
-                SELECT *
-                FROM bria_signing_sessions
-                WHERE account_id = $1 AND batch_id = $2
-                AND (COALESCE((created_at, id) > ($4, $3), $3 IS NULL))
-                ORDER BY created_at, id"#,
-                account_id as AccountId,
-                batch_id as BatchId,
-                id as Option<SigningSessionId>,
-                created_at as Option<chrono::DateTime<chrono::Utc>>,
-            )
-            .fetch_n(self.pool(), first)
-            .await?;
-
-            signing_sessions.extend(entities);
-
-            if !has_next_page {
+            let ret = self
+                .list_for_account(account_id, filter.clone(), query)
+                .await?;
+            for session in ret.entities {
+                xpub_sessions.insert(session.xpub_fingerprint, session);
+            }
+            if !ret.has_next_page {
                 break;
             }
-            let end_cursor = signing_sessions
-                .last()
-                .map(signing_session_cursor::SigningSessionsByCreatedAtCursor::from);
-
-            query.after = end_cursor;
-        }
-        let mut xpub_sessions = HashMap::new();
-        for session in signing_sessions {
-            xpub_sessions.insert(session.xpub_fingerprint, session);
+            query = es_entity::PaginatedQueryArgs {
+                after: ret.end_cursor,
+                ..Default::default()
+            };
         }
         if xpub_sessions.is_empty() {
             Ok(None)
@@ -112,6 +93,88 @@ impl SigningSessions {
         }
     }
 
+    pub async fn find_by_account_id_and_id(
+        &self,
+        account_id: AccountId,
+        id: SigningSessionId,
+    ) -> Result<SigningSession, SigningSessionError> {
+        let session = self.find_by_id(id).await?;
+        if session.account_id != account_id {
+            return Err(SigningSessionError::EsEntityError(EsEntityError::NotFound));
+        }
+        Ok(session)
+    }
+
+    pub async fn list_for_account(
+        &self,
+        account_id: AccountId,
+        filter: SigningSessionsListFilter,
+        query: es_entity::PaginatedQueryArgs<
+            signing_session_cursor::SigningSessionsByCreatedAtCursor,
+        >,
+    ) -> Result<
+        es_entity::PaginatedQueryRet<
+            SigningSession,
+            signing_session_cursor::SigningSessionsByCreatedAtCursor,
+        >,
+        SigningSessionError,
+    > {
+        let es_entity::PaginatedQueryArgs { first, after } = query;
+        let (id, created_at) = if let Some(after) = after {
+            (Some(after.id), Some(after.created_at))
+        } else {
+            (None, None)
+        };
+        let event_types = filter.state.map(|state| {
+            state
+                .event_types()
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+        });
+
+        // The state of a session is determined by its latest event
+        let (entities, has_next_page) = es_entity::es_query!(
+            tbl_prefix = "bria",
+            r#"
+            SELECT *
+            FROM bria_signing_sessions
+            WHERE account_id = $1
+            AND ($2::uuid IS NULL OR batch_id = $2)
+            AND ($3::bytea IS NULL OR xpub_fingerprint = $3)
+            AND ($4::text[] IS NULL OR EXISTS (
+              SELECT 1 FROM bria_signing_session_events latest
+              WHERE latest.id = bria_signing_sessions.id
+              AND latest.event_type = ANY($4)
+              AND latest.sequence = (
+                SELECT MAX(sequence) FROM bria_signing_session_events
+                WHERE id = bria_signing_sessions.id
+              )
+            ))
+            AND (COALESCE((created_at, id) > ($6, $5), $5 IS NULL))
+            ORDER BY created_at, id
+            LIMIT $7"#,
+            account_id as AccountId,
+            filter.batch_id as Option<BatchId>,
+            filter.xpub_fingerprint as Option<XPubFingerprint>,
+            event_types.as_deref(),
+            id as Option<SigningSessionId>,
+            created_at as Option<chrono::DateTime<chrono::Utc>>,
+            (first + 1) as i64,
+        )
+        .fetch_n(self.pool(), first)
+        .await?;
+
+        let end_cursor = entities
+            .last()
+            .map(signing_session_cursor::SigningSessionsByCreatedAtCursor::from);
+        Ok(es_entity::PaginatedQueryRet {
+            entities,
+            has_next_page,
+            end_cursor,
+        })
+    }
+
     pub async fn list_batch_ids_for(
         &self,
         op: &mut impl es_entity::AtomicOperation,
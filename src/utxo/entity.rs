@@ -27,6 +27,13 @@ pub struct SettledUtxo {
     pub spend_detected_ledger_tx_id: Option<LedgerTransactionId>,
 }
 
+/// Settlements that were recorded for blocks that got reorged out.
+#[derive(Debug)]
+pub struct ReorgedUtxos {
+    pub utxo_settled_ledger_tx_ids: Vec<LedgerTransactionId>,
+    pub spend_settled_ledger_tx_ids: Vec<LedgerTransactionId>,
+}
+
 #[derive(Debug)]
 pub(super) struct SpentUtxo {
     pub outpoint: bitcoin::OutPoint,
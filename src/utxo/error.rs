@@ -8,6 +8,10 @@ pub enum UtxoError {
     UtxoAlreadySettledError,
     #[error("UtxoError - Utxo does not exist")]
     UtxoDoesNotExistError,
+    #[error("UtxoError - Change of the batch has already been spent")]
+    ChangeAlreadySpent,
+    #[error("UtxoError - Could not parse txid: {0}")]
+    TxIdParseError(#[from] bdk::bitcoin::hashes::hex::Error),
     #[error("UtxoError - EsEntityError: {0}")]
     EsEntityError(es_entity::EsEntityError),
 }
@@ -80,6 +80,19 @@ impl Utxos {
             .await
     }
 
+    #[instrument(name = "utxos.opening_balance_settled", skip(self, tx), err)]
+    pub async fn opening_balance_settled(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        outpoint: OutPoint,
+        block_height: u32,
+    ) -> Result<(), UtxoError> {
+        self.utxos
+            .mark_opening_balance(tx, keychain_id, outpoint, block_height)
+            .await
+    }
+
     #[instrument(name = "utxos.spend_detected", skip(self, inputs_iter), err)]
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
@@ -177,11 +190,45 @@ impl Utxos {
         };
         let pending_spend_tx_id = self
             .utxos
-            .settle_utxo(tx, keychain_id, inputs, spend_tx_id)
+            .settle_utxo(tx, keychain_id, inputs, spend_tx_id, block_height)
             .await?;
         Ok(pending_spend_tx_id.map(|id| (id, spend_tx_id, change_spent)))
     }
 
+    /// Lists the settlements that were recorded at or above `fork_height`.
+    #[instrument(name = "utxos.find_reorged", skip(self), err)]
+    pub async fn find_reorged(
+        &self,
+        keychain_id: KeychainId,
+        fork_height: u32,
+    ) -> Result<ReorgedUtxos, UtxoError> {
+        self.utxos.find_reorged(keychain_id, fork_height).await
+    }
+
+    #[instrument(name = "utxos.unsettle_utxo", skip(self, tx), err)]
+    pub async fn unsettle_utxo(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        utxo_settled_ledger_tx_id: LedgerTransactionId,
+    ) -> Result<bool, UtxoError> {
+        self.utxos
+            .unsettle_utxo(tx, keychain_id, utxo_settled_ledger_tx_id)
+            .await
+    }
+
+    #[instrument(name = "utxos.unsettle_spend", skip(self, tx), err)]
+    pub async fn unsettle_spend(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        spend_settled_ledger_tx_id: LedgerTransactionId,
+    ) -> Result<bool, UtxoError> {
+        self.utxos
+            .unsettle_spend(tx, keychain_id, spend_settled_ledger_tx_id)
+            .await
+    }
+
     #[instrument(name = "utxos.find_keychain_utxos", skip_all, err)]
     pub async fn find_keychain_utxos(
         &self,
@@ -219,10 +266,14 @@ impl Utxos {
 
         // We need to tell bdk which utxos not to select.
         // If we have included it in a batch OR
+        // it is being swept via the recovery key OR
         // it isn't confirmed / settled yet
         // we need to flag it to bdk
         let filtered_utxos = reservable_utxos.into_iter().filter_map(|utxo| {
-            if utxo.spending_batch_id.is_some() || utxo.utxo_settled_ledger_tx_id.is_none() {
+            if utxo.spending_batch_id.is_some()
+                || utxo.reserved_for_recovery_sweep
+                || utxo.utxo_settled_ledger_tx_id.is_none()
+            {
                 Some((utxo.keychain_id, utxo.outpoint))
             } else {
                 None
@@ -255,6 +306,94 @@ impl Utxos {
             .await
     }
 
+    /// Reserves the unbatched utxos whose recovery timelock has matured so that
+    /// no batch spends them while the sweep is pending. Utxos that were already
+    /// reserved by an earlier sweep are included again.
+    #[instrument(name = "utxos.release_recovery_sweep", skip_all, err)]
+    pub async fn release_recovery_sweep(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ids: impl Iterator<Item = KeychainId>,
+    ) -> Result<u64, UtxoError> {
+        self.utxos.release_recovery_sweep(tx, ids).await
+    }
+
+    #[instrument(name = "utxos.reserve_matured_for_recovery_sweep", skip_all, err)]
+    pub async fn reserve_matured_for_recovery_sweep(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        recovery_after_blocks: HashMap<KeychainId, u32>,
+        current_height: u32,
+    ) -> Result<HashMap<KeychainId, Vec<OutPoint>>, UtxoError> {
+        let reservable_utxos = self
+            .utxos
+            .find_reservable_utxos(tx, recovery_after_blocks.keys().copied())
+            .await?;
+        let matured_utxos: Vec<_> = reservable_utxos
+            .into_iter()
+            .filter(|utxo| {
+                utxo.spending_batch_id.is_none()
+                    && utxo.block_height.is_some_and(|height| {
+                        current_height + 1 >= height + recovery_after_blocks[&utxo.keychain_id]
+                    })
+            })
+            .map(|utxo| (utxo.keychain_id, utxo.outpoint))
+            .collect();
+        if matured_utxos.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.utxos
+            .reserve_utxos_for_recovery_sweep(tx, matured_utxos.iter().copied())
+            .await?;
+
+        let mut outpoints_map = HashMap::new();
+        for (keychain_id, outpoint) in matured_utxos {
+            outpoints_map
+                .entry(keychain_id)
+                .or_insert_with(Vec::new)
+                .push(outpoint);
+        }
+        Ok(outpoints_map)
+    }
+
+    #[instrument(name = "utxos.find_batch_inputs", skip(self, tx), err)]
+    pub async fn find_batch_inputs(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        batch_id: BatchId,
+    ) -> Result<HashMap<KeychainId, Vec<OutPoint>>, UtxoError> {
+        let mut outpoints_map = HashMap::new();
+        for (keychain_id, outpoint) in self.utxos.find_batch_inputs(tx, batch_id).await? {
+            outpoints_map
+                .entry(keychain_id)
+                .or_insert_with(Vec::new)
+                .push(outpoint);
+        }
+        Ok(outpoints_map)
+    }
+
+    #[instrument(name = "utxos.move_utxos_to_replacement_batch", skip(self, tx), err)]
+    pub async fn move_utxos_to_replacement_batch(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        replaced_batch_id: BatchId,
+        batch_id: BatchId,
+        fee_rate: bitcoin::FeeRate,
+    ) -> Result<(), UtxoError> {
+        self.utxos
+            .move_utxos_to_replacement_batch(tx, replaced_batch_id, batch_id, fee_rate)
+            .await
+    }
+
+    #[instrument(name = "utxos.release_batch_utxos", skip(self, tx), err)]
+    pub async fn release_batch_utxos(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        batch_id: BatchId,
+    ) -> Result<(), UtxoError> {
+        self.utxos.release_batch_utxos(tx, batch_id).await
+    }
+
     pub async fn average_utxo_value(
         &self,
         wallet_id: WalletId,
@@ -13,6 +13,8 @@ pub struct ReservableUtxo {
     pub outpoint: OutPoint,
     pub spending_batch_id: Option<BatchId>,
     pub utxo_settled_ledger_tx_id: Option<LedgerTransactionId>,
+    pub block_height: Option<u32>,
+    pub reserved_for_recovery_sweep: bool,
 }
 
 #[derive(Clone)]
@@ -113,6 +115,32 @@ impl UtxoRepo {
         })
     }
 
+    /// Opening balances are settled by the same ledger tx that recorded them.
+    pub async fn mark_opening_balance(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        outpoint: OutPoint,
+        block_height: u32,
+    ) -> Result<(), UtxoError> {
+        sqlx::query!(
+            r#"UPDATE bria_utxos
+            SET block_height = $1,
+                income_settled_ledger_tx_id = income_detected_ledger_tx_id,
+                modified_at = NOW()
+            WHERE keychain_id = $2
+              AND tx_id = $3
+              AND vout = $4"#,
+            block_height as i32,
+            keychain_id as KeychainId,
+            outpoint.txid.to_string(),
+            outpoint.vout as i32,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     pub async fn mark_spent(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -167,12 +195,15 @@ impl UtxoRepo {
         keychain_id: KeychainId,
         utxos: impl Iterator<Item = &OutPoint>,
         tx_id: LedgerTransactionId,
+        block_height: u32,
     ) -> Result<Option<LedgerTransactionId>, UtxoError> {
         let keychain_id = Uuid::from(keychain_id);
         let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
             r#"UPDATE bria_utxos
-            SET modified_at = NOW(), spend_settled_ledger_tx_id = "#,
+            SET modified_at = NOW(), spend_settled_block_height = "#,
         );
+        query_builder.push_bind(block_height as i32);
+        query_builder.push(", spend_settled_ledger_tx_id = ");
         query_builder.push_bind(tx_id);
         query_builder
             .push(" WHERE spend_settled_ledger_tx_id IS NULL AND (keychain_id, tx_id, vout) IN");
@@ -195,6 +226,91 @@ impl UtxoRepo {
         })
     }
 
+    pub async fn find_reorged(
+        &self,
+        keychain_id: KeychainId,
+        fork_height: u32,
+    ) -> Result<ReorgedUtxos, UtxoError> {
+        let spends = sqlx::query!(
+            r#"SELECT DISTINCT spend_settled_ledger_tx_id AS "spend_settled_ledger_tx_id!"
+            FROM bria_utxos
+            WHERE keychain_id = $1 AND spend_settled_block_height >= $2
+              AND spend_settled_ledger_tx_id IS NOT NULL"#,
+            keychain_id as KeychainId,
+            fork_height as i32,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        // change outputs are settled as part of the spend that created them
+        // and opening balances lie below the wallet's birthday
+        let incomes = sqlx::query!(
+            r#"SELECT income_settled_ledger_tx_id AS "income_settled_ledger_tx_id!"
+            FROM bria_utxos
+            WHERE keychain_id = $1 AND block_height >= $2
+              AND income_settled_ledger_tx_id IS NOT NULL
+              AND income_settled_ledger_tx_id != income_detected_ledger_tx_id
+              AND income_settled_ledger_tx_id NOT IN (
+                SELECT spend_settled_ledger_tx_id FROM bria_utxos
+                WHERE keychain_id = $1 AND spend_settled_ledger_tx_id IS NOT NULL
+              )"#,
+            keychain_id as KeychainId,
+            fork_height as i32,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ReorgedUtxos {
+            utxo_settled_ledger_tx_ids: incomes
+                .into_iter()
+                .map(|row| LedgerTransactionId::from(row.income_settled_ledger_tx_id))
+                .collect(),
+            spend_settled_ledger_tx_ids: spends
+                .into_iter()
+                .map(|row| LedgerTransactionId::from(row.spend_settled_ledger_tx_id))
+                .collect(),
+        })
+    }
+
+    pub async fn unsettle_utxo(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        utxo_settled_ledger_tx_id: LedgerTransactionId,
+    ) -> Result<bool, UtxoError> {
+        let result = sqlx::query!(
+            r#"UPDATE bria_utxos
+            SET income_settled_ledger_tx_id = NULL, block_height = NULL, modified_at = NOW()
+            WHERE keychain_id = $1 AND income_settled_ledger_tx_id = $2"#,
+            keychain_id as KeychainId,
+            utxo_settled_ledger_tx_id as LedgerTransactionId,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn unsettle_spend(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        keychain_id: KeychainId,
+        spend_settled_ledger_tx_id: LedgerTransactionId,
+    ) -> Result<bool, UtxoError> {
+        let result = sqlx::query!(
+            r#"UPDATE bria_utxos
+            SET spend_settled_ledger_tx_id = NULL, spend_settled_block_height = NULL, modified_at = NOW()
+            WHERE keychain_id = $1 AND spend_settled_ledger_tx_id = $2"#,
+            keychain_id as KeychainId,
+            spend_settled_ledger_tx_id as LedgerTransactionId,
+        )
+        .execute(&mut **tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        self.unsettle_utxo(tx, keychain_id, spend_settled_ledger_tx_id)
+            .await?;
+        Ok(true)
+    }
+
     pub async fn find_keychain_utxos(
         &self,
         keychain_ids: impl Iterator<Item = KeychainId>,
@@ -265,7 +381,8 @@ impl UtxoRepo {
         let rows = sqlx::query!(
             r#"SELECT keychain_id,
                CASE WHEN kind = 'external' THEN true ELSE false END as income_address,
-               tx_id, vout, spending_batch_id, income_settled_ledger_tx_id
+               tx_id, vout, spending_batch_id, income_settled_ledger_tx_id, block_height,
+               recovery_sweep_reserved_at IS NOT NULL as "reserved_for_recovery_sweep!"
                FROM bria_utxos
                WHERE keychain_id = ANY($1) AND bdk_spent = false
                FOR UPDATE"#,
@@ -287,12 +404,58 @@ impl UtxoRepo {
                 utxo_settled_ledger_tx_id: row
                     .income_settled_ledger_tx_id
                     .map(LedgerTransactionId::from),
+                block_height: row.block_height.map(|v| v as u32),
+                reserved_for_recovery_sweep: row.reserved_for_recovery_sweep,
             })
             .collect();
 
         Ok(reservable_utxos)
     }
 
+    pub async fn release_recovery_sweep(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ids: impl Iterator<Item = KeychainId>,
+    ) -> Result<u64, UtxoError> {
+        let uuids = ids.into_iter().map(Uuid::from).collect::<Vec<_>>();
+        let result = sqlx::query!(
+            r#"UPDATE bria_utxos
+               SET recovery_sweep_reserved_at = NULL
+               WHERE keychain_id = ANY($1) AND bdk_spent = false
+               AND recovery_sweep_reserved_at IS NOT NULL"#,
+            &uuids[..]
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn reserve_utxos_for_recovery_sweep(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        utxos: impl IntoIterator<Item = (KeychainId, OutPoint)>,
+    ) -> Result<(), UtxoError> {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"UPDATE bria_utxos
+            SET recovery_sweep_reserved_at = NOW()
+            WHERE spending_batch_id IS NULL AND (keychain_id, tx_id, vout) IN"#,
+        );
+        query_builder.push_tuples(
+            utxos
+                .into_iter()
+                .map(|(keychain_id, utxo)| (keychain_id, utxo.txid.to_string(), utxo.vout as i32)),
+            |mut builder, (keychain_id, tx_id, vout)| {
+                builder.push_bind(keychain_id);
+                builder.push_bind(tx_id);
+                builder.push_bind(vout);
+            },
+        );
+
+        let query = query_builder.build();
+        query.execute(&mut **tx).await?;
+        Ok(())
+    }
+
     pub async fn reserve_utxos_in_batch(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -330,6 +493,105 @@ impl UtxoRepo {
         Ok(())
     }
 
+    pub async fn find_batch_inputs(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        batch_id: BatchId,
+    ) -> Result<Vec<(KeychainId, OutPoint)>, UtxoError> {
+        let rows = sqlx::query!(
+            r#"SELECT keychain_id, tx_id, vout
+               FROM bria_utxos
+               WHERE spending_batch_id = $1
+               FOR UPDATE"#,
+            batch_id as BatchId,
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok((
+                    KeychainId::from(row.keychain_id),
+                    OutPoint {
+                        txid: row.tx_id.parse()?,
+                        vout: row.vout as u32,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    pub async fn move_utxos_to_replacement_batch(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        replaced_batch_id: BatchId,
+        batch_id: BatchId,
+        fee_rate: bitcoin::FeeRate,
+    ) -> Result<(), UtxoError> {
+        let spent_change = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM bria_utxos
+               WHERE origin_tx_batch_id = $1 AND spending_batch_id IS NOT NULL"#,
+            replaced_batch_id as BatchId,
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        if spent_change.count > 0 {
+            return Err(UtxoError::ChangeAlreadySpent);
+        }
+
+        sqlx::query!(
+            r#"DELETE FROM bria_utxos WHERE origin_tx_batch_id = $1"#,
+            replaced_batch_id as BatchId,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        // The spend_detected entries being cleared here are the batch_broadcast entries of the
+        // replaced batch, which get reversed by the batch_replaced ledger template.
+        sqlx::query!(
+            r#"UPDATE bria_utxos
+               SET spending_batch_id = $1, spending_sats_per_vbyte = $2, spend_detected_ledger_tx_id = NULL
+               WHERE spending_batch_id = $3
+                 AND spend_settled_ledger_tx_id IS NULL
+                 AND (spend_detected_ledger_tx_id IS NULL OR spend_detected_ledger_tx_id IN (
+                   SELECT batch_broadcast_ledger_tx_id FROM bria_batch_wallet_summaries
+                   WHERE batch_id = $3 AND batch_broadcast_ledger_tx_id IS NOT NULL))"#,
+            batch_id as BatchId,
+            fee_rate.as_sat_per_vb(),
+            replaced_batch_id as BatchId,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let not_moved = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM bria_utxos WHERE spending_batch_id = $1"#,
+            replaced_batch_id as BatchId,
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        if not_moved.count > 0 {
+            return Err(UtxoError::UtxoAlreadySettledError);
+        }
+
+        Ok(())
+    }
+
+    pub async fn release_batch_utxos(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        batch_id: BatchId,
+    ) -> Result<(), UtxoError> {
+        sqlx::query!(
+            r#"UPDATE bria_utxos
+               SET spending_batch_id = NULL, spending_payout_queue_id = NULL, spending_sats_per_vbyte = NULL
+               WHERE spending_batch_id = $1"#,
+            batch_id as BatchId,
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
     pub async fn accounting_info_for_batch(
         &self,
         batch_id: BatchId,
@@ -26,6 +26,8 @@ pub enum WalletEvent {
         effective_at_rest_ledger_account_id: LedgerAccountId,
         effective_outgoing_ledger_account_id: LedgerAccountId,
         dust_ledger_account_id: LedgerAccountId,
+        #[serde(default)]
+        birthday_height: Option<u32>,
     },
     NameUpdated {
         name: String,
@@ -53,6 +55,8 @@ pub struct Wallet {
     pub config: WalletConfig,
     pub network: bitcoin::Network,
     pub name: String,
+    #[builder(default)]
+    pub birthday_height: Option<u32>,
 
     events: EntityEvents<WalletEvent>,
 }
@@ -106,7 +110,20 @@ impl Wallet {
         let mut ret = HashMap::new();
         for find_id in keychain_ids {
             if let Some((_, cfg)) = self.iter_keychains().find(|(id, _)| id == &find_id) {
-                ret.insert(*find_id, cfg.xpubs());
+                ret.insert(*find_id, cfg.signing_xpubs());
+            }
+        }
+        ret
+    }
+
+    pub fn signing_thresholds_for_keychains<'a>(
+        &self,
+        keychain_ids: impl IntoIterator<Item = &'a KeychainId>,
+    ) -> HashMap<KeychainId, usize> {
+        let mut ret = HashMap::new();
+        for find_id in keychain_ids {
+            if let Some((_, cfg)) = self.iter_keychains().find(|(id, _)| id == &find_id) {
+                ret.insert(*find_id, cfg.signing_threshold());
             }
         }
         ret
@@ -127,6 +144,8 @@ pub struct NewWallet {
     keychain: KeychainConfig,
     #[builder(default)]
     config: WalletConfig,
+    #[builder(default)]
+    birthday_height: Option<u32>,
 }
 
 impl TryFromEvents<WalletEvent> for Wallet {
@@ -148,10 +167,11 @@ impl TryFromEvents<WalletEvent> for Wallet {
                     effective_at_rest_ledger_account_id,
                     effective_outgoing_ledger_account_id,
                     dust_ledger_account_id,
-                    ..
+                    birthday_height,
                 } => {
                     builder = builder
                         .id(*id)
+                        .birthday_height(*birthday_height)
                         .account_id(*account_id)
                         .network(*network)
                         .journal_id(*journal_id)
@@ -214,6 +234,7 @@ impl IntoEvents<WalletEvent> for NewWallet {
                         .ledger_account_ids
                         .effective_outgoing_id,
                     dust_ledger_account_id: self.ledger_account_ids.dust_id,
+                    birthday_height: self.birthday_height,
                 },
                 WalletEvent::NameUpdated { name: self.name },
                 WalletEvent::ConfigUpdated {
@@ -16,6 +16,10 @@ pub enum WalletError {
     PsbtDoesNotHaveValidSignatures,
     #[error("WalletError - Unsigned txn in signed and unsigned psbt don't match")]
     UnsignedTxnMismatch,
+    #[error("WalletError - Wallet does not have a recovery path")]
+    NoRecoveryPath,
+    #[error("WalletError - No utxos have passed the recovery timelock")]
+    NoMaturedRecoveryUtxos,
 }
 
 es_entity::from_es_entity_error!(WalletError);
@@ -1,9 +1,20 @@
-use bdk::{descriptor::DescriptorPublicKey, miniscript::ForEachKey};
+use bdk::{
+    bitcoin::{
+        bip32::{ChainCode, ChildNumber, ExtendedPubKey, Fingerprint},
+        hashes::{sha256, Hash, HashEngine},
+        secp256k1::{PublicKey, Scalar, Secp256k1},
+    },
+    descriptor::DescriptorPublicKey,
+    miniscript::ForEachKey,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, str::FromStr};
 
 use crate::{primitives::bitcoin::ExtendedDescriptor, xpub::*};
 
+/// The BIP341 NUMS point `H = lift_x(sha256(G))`. Nobody knows its discrete log.
+const NUMS_H: &str = "0250929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
 #[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[allow(clippy::large_enum_variant)]
@@ -11,6 +22,9 @@ pub enum KeychainConfig {
     Wpkh {
         xpub: XPub,
     },
+    Tr {
+        xpub: XPub,
+    },
     Descriptors {
         internal: ExtendedDescriptor,
         external: ExtendedDescriptor,
@@ -19,6 +33,21 @@ pub enum KeychainConfig {
         xpub: Vec<XPub>,
         threshold: u32,
     },
+    /// `tr(H+rG,multi_a(...))` over the xpubs in the order they are stored, see
+    /// [`taproot_multisig_internal_key`]. This is not `sortedmulti_a`: the keys are
+    /// not re-sorted per derived address.
+    TaprootMultisig {
+        xpub: Vec<XPub>,
+        threshold: u32,
+    },
+    /// `wsh(or_d(multi(...),and_v(v:pk(recovery),older(N))))` with the xpubs in the order
+    /// they are stored.
+    SortedMultisigWithRecovery {
+        xpub: Vec<XPub>,
+        threshold: u32,
+        recovery_xpub: XPub,
+        recovery_after_blocks: u32,
+    },
 }
 
 impl KeychainConfig {
@@ -26,13 +55,72 @@ impl KeychainConfig {
         Self::Wpkh { xpub }
     }
 
+    pub fn tr(xpub: XPub) -> Self {
+        Self::Tr { xpub }
+    }
+
     pub fn sorted_multisig(xpub: Vec<XPub>, threshold: u32) -> Self {
         Self::SortedMultisig { xpub, threshold }
     }
 
+    pub fn taproot_multisig(mut xpub: Vec<XPub>, threshold: u32) -> Self {
+        // The miniscript version bdk depends on can't parse sortedmulti_a. multi_a is
+        // order sensitive, so the xpubs get a fixed order that doesn't depend on the
+        // order they were passed in. Other wallets must import the exported multi_a
+        // descriptor; rebuilding it as sortedmulti_a derives different addresses.
+        xpub.sort_by_key(|xpub| xpub.to_string());
+        Self::TaprootMultisig { xpub, threshold }
+    }
+
+    pub fn sorted_multisig_with_recovery(
+        mut xpub: Vec<XPub>,
+        threshold: u32,
+        recovery_xpub: XPub,
+        recovery_after_blocks: u32,
+    ) -> Self {
+        // sortedmulti can't be nested inside or_d so the descriptor uses a plain multi.
+        // Its keys are in a fixed order that doesn't depend on the order the xpubs were
+        // passed in. This is not BIP67, which would re-sort the keys for every derived address.
+        xpub.sort_by_key(|xpub| xpub.to_string());
+        Self::SortedMultisigWithRecovery {
+            xpub,
+            threshold,
+            recovery_xpub,
+            recovery_after_blocks,
+        }
+    }
+
+    pub fn recovery_after_blocks(&self) -> Option<u32> {
+        match self {
+            Self::SortedMultisigWithRecovery {
+                recovery_after_blocks,
+                ..
+            } => Some(*recovery_after_blocks),
+            _ => None,
+        }
+    }
+
+    pub fn signing_threshold(&self) -> usize {
+        match self {
+            Self::SortedMultisig { threshold, .. }
+            | Self::TaprootMultisig { threshold, .. }
+            | Self::SortedMultisigWithRecovery { threshold, .. } => *threshold as usize,
+            _ => self.xpubs().len(),
+        }
+    }
+
+    /// The xpubs that are expected to co-sign batches. The recovery key of a
+    /// wallet is only used when sweeping via the timelocked branch.
+    pub fn signing_xpubs(&self) -> Vec<XPub> {
+        match self {
+            Self::SortedMultisigWithRecovery { xpub, .. } => xpub.clone(),
+            _ => self.xpubs(),
+        }
+    }
+
     pub fn xpubs(&self) -> Vec<XPub> {
         match self {
-            Self::Wpkh { xpub } => vec![xpub.clone()],
+            Self::Wpkh { xpub } | Self::Tr { xpub } => vec![xpub.clone()],
             Self::Descriptors { internal, external } => {
                 let mut ret = HashMap::new();
                 internal.for_each_key(|key| {
@@ -47,7 +135,16 @@ impl KeychainConfig {
                 });
                 ret.into_values().collect()
             }
-            Self::SortedMultisig { xpub, .. } => xpub.clone(),
+            Self::SortedMultisig { xpub, .. } | Self::TaprootMultisig { xpub, .. } => xpub.clone(),
+            Self::SortedMultisigWithRecovery {
+                xpub,
+                recovery_xpub,
+                ..
+            } => {
+                let mut ret = xpub.clone();
+                ret.push(recovery_xpub.clone());
+                ret
+            }
         }
     }
 
@@ -56,6 +153,9 @@ impl KeychainConfig {
             Self::Wpkh { xpub } => format!("wpkh({}/0/*)", xpub)
                 .parse()
                 .expect("Couldn't create internal wpkh descriptor"),
+            Self::Tr { xpub } => format!("tr({}/0/*)", xpub)
+                .parse()
+                .expect("Couldn't create external tr descriptor"),
             Self::Descriptors { external, .. } => external.clone(),
             Self::SortedMultisig { xpub, threshold } => {
                 let keys = xpub
@@ -68,6 +168,21 @@ impl KeychainConfig {
                     .parse()
                     .expect("Couldn't create external sorted multisig descriptor")
             }
+            Self::TaprootMultisig { xpub, threshold } => {
+                taproot_multisig_descriptor(xpub, *threshold, 0)
+            }
+            Self::SortedMultisigWithRecovery {
+                xpub,
+                threshold,
+                recovery_xpub,
+                recovery_after_blocks,
+            } => multisig_with_recovery_descriptor(
+                xpub,
+                *threshold,
+                recovery_xpub,
+                *recovery_after_blocks,
+                0,
+            ),
         }
     }
 
@@ -76,6 +191,9 @@ impl KeychainConfig {
             Self::Wpkh { xpub } => format!("wpkh({}/1/*)", xpub)
                 .parse()
                 .expect("Couldn't create internal wpkh descriptor"),
+            Self::Tr { xpub } => format!("tr({}/1/*)", xpub)
+                .parse()
+                .expect("Couldn't create internal tr descriptor"),
             Self::Descriptors { internal, .. } => internal.clone(),
             Self::SortedMultisig { xpub, threshold } => {
                 let keys = xpub
@@ -87,10 +205,94 @@ impl KeychainConfig {
                     .parse()
                     .expect("Couldn't create internal sorted multisig descriptor")
             }
+            Self::TaprootMultisig { xpub, threshold } => {
+                taproot_multisig_descriptor(xpub, *threshold, 1)
+            }
+            Self::SortedMultisigWithRecovery {
+                xpub,
+                threshold,
+                recovery_xpub,
+                recovery_after_blocks,
+            } => multisig_with_recovery_descriptor(
+                xpub,
+                *threshold,
+                recovery_xpub,
+                *recovery_after_blocks,
+                1,
+            ),
         }
     }
 }
 
+/// Unspendable internal key for a taproot multisig wallet (BIP341 `H + rG`).
+///
+/// `r` is the sha256 of the wallet's xpubs so the key differs between wallets but can be
+/// recomputed by every co-signer. It is also used as the chain code, which lets anyone
+/// holding the descriptor check that the internal key is `H + chain_code * G`. Children
+/// are derived unhardened so every address gets its own, equally unspendable, key.
+fn taproot_multisig_internal_key(xpub: &[XPub]) -> ExtendedPubKey {
+    let mut engine = sha256::Hash::engine();
+    for xpub in xpub {
+        engine.input(&xpub.inner().encode());
+    }
+    let r = sha256::Hash::from_engine(engine).to_byte_array();
+    let tweak = Scalar::from_be_bytes(r).expect("sha256 output is not a valid scalar");
+    let public_key = PublicKey::from_str(NUMS_H)
+        .expect("Couldn't parse NUMS point")
+        .add_exp_tweak(&Secp256k1::verification_only(), &tweak)
+        .expect("Couldn't tweak NUMS point");
+    ExtendedPubKey {
+        network: xpub
+            .first()
+            .map(|xpub| xpub.inner().network)
+            .expect("Taproot multisig without xpubs"),
+        depth: 0,
+        parent_fingerprint: Fingerprint::default(),
+        child_number: ChildNumber::from_normal_idx(0).expect("0 is a normal index"),
+        public_key,
+        chain_code: ChainCode::from(r),
+    }
+}
+
+fn taproot_multisig_descriptor(xpub: &[XPub], threshold: u32, kind: u32) -> ExtendedDescriptor {
+    let keys = xpub
+        .iter()
+        .map(|xpub| format!("{}/{}/*", xpub, kind))
+        .collect::<Vec<_>>();
+    format!(
+        "tr({}/{}/*,multi_a({},{}))",
+        taproot_multisig_internal_key(xpub),
+        kind,
+        threshold,
+        keys.join(",")
+    )
+    .parse()
+    .expect("Couldn't create taproot multisig descriptor")
+}
+
+fn multisig_with_recovery_descriptor(
+    xpub: &[XPub],
+    threshold: u32,
+    recovery_xpub: &XPub,
+    recovery_after_blocks: u32,
+    kind: u32,
+) -> ExtendedDescriptor {
+    let keys = xpub
+        .iter()
+        .map(|xpub| format!("{}/{}/*", xpub, kind))
+        .collect::<Vec<_>>();
+    format!(
+        "wsh(or_d(multi({},{}),and_v(v:pk({}/{}/*),older({}))))",
+        threshold,
+        keys.join(","),
+        recovery_xpub,
+        kind,
+        recovery_after_blocks
+    )
+    .parse()
+    .expect("Couldn't create multisig with recovery descriptor")
+}
+
 impl TryFrom<(&str, &str)> for KeychainConfig {
     type Error = crate::wallet::error::WalletError;
 
@@ -108,3 +310,40 @@ impl TryFrom<(&str, &str)> for KeychainConfig {
         Ok(Self::Descriptors { internal, external })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::{bip32::ExtendedPrivKey, Network};
+
+    use super::*;
+
+    fn xpubs(seeds: &[u8]) -> Vec<XPub> {
+        let secp = Secp256k1::new();
+        seeds
+            .iter()
+            .map(|seed| {
+                let xpriv = ExtendedPrivKey::new_master(Network::Regtest, &[*seed; 32]).unwrap();
+                let xpub = ExtendedPubKey::from_priv(&secp, &xpriv);
+                XPub::try_from((xpub.to_string(), None::<String>)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn taproot_multisig_internal_key_is_tweaked_nums() {
+        let xpub = xpubs(&[1, 2, 3]);
+        let key = taproot_multisig_internal_key(&xpub);
+        let tweak = Scalar::from_be_bytes(key.chain_code.to_bytes()).unwrap();
+        let expected = PublicKey::from_str(NUMS_H)
+            .unwrap()
+            .add_exp_tweak(&Secp256k1::verification_only(), &tweak)
+            .unwrap();
+        assert_eq!(key.public_key, expected);
+
+        let other = taproot_multisig_internal_key(&xpubs(&[1, 2, 4]));
+        assert_ne!(key.public_key, other.public_key);
+
+        let config = KeychainConfig::taproot_multisig(xpub, 2);
+        assert_ne!(config.external_descriptor(), config.internal_descriptor());
+    }
+}
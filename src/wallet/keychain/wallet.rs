@@ -5,6 +5,7 @@ use bdk::{
     Wallet,
 };
 use sqlx::PgPool;
+use std::collections::BTreeMap;
 use tracing::instrument;
 
 use super::config::*;
@@ -125,6 +126,42 @@ impl KeychainWallet {
             .expect("max_satisfaction_weight")
     }
 
+    pub fn recovery_after_blocks(&self) -> Option<u32> {
+        self.config.recovery_after_blocks()
+    }
+
+    /// Builds an unsigned psbt that drains the given utxos to `destination`
+    /// via the timelocked recovery branch of the descriptor.
+    #[instrument(name = "keychain_wallet.build_recovery_sweep", skip_all)]
+    pub async fn build_recovery_sweep(
+        &self,
+        utxos: Vec<OutPoint>,
+        destination: Address,
+        fee_rate: FeeRate,
+    ) -> Result<psbt::PartiallySignedTransaction, BdkError> {
+        let psbt = self
+            .with_wallet(move |wallet| {
+                let mut builder = wallet.build_tx();
+                for kind in [KeychainKind::External, KeychainKind::Internal] {
+                    if let Some(policy) = wallet.policies(kind)? {
+                        // The root or_d has the multisig at index 0 and the recovery key at 1
+                        let mut path = BTreeMap::new();
+                        path.insert(policy.id, vec![1]);
+                        builder.policy_path(path, kind);
+                    }
+                }
+                builder
+                    .add_utxos(&utxos)?
+                    .manually_selected_only()
+                    .fee_rate(fee_rate)
+                    .drain_to(destination.script_pubkey());
+                let (psbt, _) = builder.finish()?;
+                Ok::<_, BdkError>(psbt)
+            })
+            .await??;
+        Ok(psbt)
+    }
+
     async fn with_wallet<F, R>(&self, f: F) -> Result<R, tokio::task::JoinError>
     where
         F: 'static + Send + FnOnce(Wallet<SqlxWalletDb>) -> R,
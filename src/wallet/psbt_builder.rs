@@ -73,6 +73,8 @@ pub struct PsbtBuilderConfig {
     #[builder(default)]
     cpfp_utxos: HashMap<KeychainId, Vec<CpfpUtxo>>,
     #[builder(default)]
+    required_utxos: HashMap<KeychainId, Vec<OutPoint>>,
+    #[builder(default)]
     for_estimation: bool,
     #[builder(default)]
     force_min_change_output: Option<Satoshis>,
@@ -386,6 +388,7 @@ impl BdkWalletVisitor for PsbtBuilder<AcceptingCurrentKeychainState> {
         builder.drain_to(change_address.script_pubkey());
         builder.sighash(DEFAULT_SIGHASH_TYPE.into());
         builder.manually_selected_only();
+        builder.enable_rbf();
         builder.add_utxos(&inputs)?;
 
         if let Some(cpfp) = self.cfg.cpfp_utxos.get(&current_keychain_id) {
@@ -576,6 +579,12 @@ impl PsbtBuilder<AcceptingCurrentKeychainState> {
             }
         }
 
+        if let Some(required_utxos) = self.cfg.required_utxos.get(&keychain_id) {
+            for out in required_utxos {
+                builder.add_utxo(*out)?;
+            }
+        }
+
         let mut cpfp_fees = 0;
         if let Some(cpfp) = self.cfg.cpfp_utxos.get(&keychain_id) {
             for utxo in cpfp {
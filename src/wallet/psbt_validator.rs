@@ -19,6 +19,14 @@ pub fn validate_psbt(
             (fingerprint == &xpub.inner().parent_fingerprint).then_some(pk)
         })
         .collect();
+    let tap_set: HashSet<_> = signed_psbt
+        .inputs
+        .iter()
+        .flat_map(|inp| &inp.tap_key_origins)
+        .filter_map(|(pk, (_, (fingerprint, _)))| {
+            (fingerprint == &xpub.inner().parent_fingerprint).then_some(pk)
+        })
+        .collect();
 
     if unsigned_psbt.unsigned_tx != signed_psbt.unsigned_tx {
         return Err(WalletError::UnsignedTxnMismatch);
@@ -32,7 +40,14 @@ pub fn validate_psbt(
             .inputs
             .iter()
             .flat_map(|inp| &inp.partial_sigs)
-            .any(|(pk, _)| set.contains(&pk.inner)))
+            .any(|(pk, _)| set.contains(&pk.inner))
+        || signed_psbt.inputs.iter().any(|inp| {
+            inp.tap_key_sig.is_some()
+                && inp
+                    .tap_internal_key
+                    .map(|key| tap_set.contains(&key))
+                    .unwrap_or(false)
+        }))
     {
         return Err(WalletError::PsbtDoesNotHaveValidSignatures);
     }
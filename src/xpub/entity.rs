@@ -78,6 +78,10 @@ impl AccountXPub {
                 let client = BitcoindRemoteSigner::connect(cfg).await?;
                 Some(Box::new(client) as Box<dyn RemoteSigningClient + 'static>)
             }
+            Some(SignerConfig::Webhook(ref cfg)) => {
+                let client = WebhookRemoteSigner::connect(cfg).await?;
+                Some(Box::new(client) as Box<dyn RemoteSigningClient + 'static>)
+            }
             None => None,
         };
         Ok(client)
@@ -23,6 +23,7 @@ pub struct SignerEncryptionConfig {
 pub enum SignerConfig {
     Lnd(LndSignerConfig),
     Bitcoind(BitcoindSignerConfig),
+    Webhook(WebhookSignerConfig),
 }
 
 impl SignerConfig {
@@ -86,6 +87,9 @@ impl std::fmt::Debug for SignerConfig {
             SignerConfig::Bitcoind(config) => {
                 write!(f, "SignerConfig::Bitcoind(endpoint={})", config.endpoint)
             }
+            SignerConfig::Webhook(config) => {
+                write!(f, "SignerConfig::Webhook(endpoint={})", config.endpoint)
+            }
         }
     }
 }
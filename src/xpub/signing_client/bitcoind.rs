@@ -3,7 +3,7 @@ use base64::{engine::general_purpose, Engine};
 use bitcoincore_rpc::{Auth, Client, RpcApi};
 use serde::{Deserialize, Serialize};
 
-use super::{error::*, r#trait::*};
+use super::{error::*, r#trait::*, taproot};
 use crate::{primitives::bitcoin::psbt, wallet::DEFAULT_SIGHASH_TYPE};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -37,7 +37,7 @@ impl RemoteSigningClient for BitcoindRemoteSigner {
         &mut self,
         psbt: &psbt::PartiallySignedTransaction,
     ) -> Result<psbt::PartiallySignedTransaction, SigningClientError> {
-        let raw_psbt = psbt.serialize();
+        let raw_psbt = taproot::prepare_for_key_path_signing(psbt).serialize();
         let hex_psbt = general_purpose::STANDARD.encode(raw_psbt);
         let sighash_type = Some(DEFAULT_SIGHASH_TYPE.into());
         let response = self
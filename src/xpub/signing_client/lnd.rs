@@ -4,7 +4,7 @@ use tonic_lnd::walletrpc::SignPsbtRequest;
 
 use std::fs;
 
-use super::{error::*, r#trait::*};
+use super::{error::*, r#trait::*, taproot};
 use crate::primitives::bitcoin::psbt;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -55,7 +55,7 @@ impl RemoteSigningClient for LndRemoteSigner {
         &mut self,
         psbt: &psbt::PartiallySignedTransaction,
     ) -> Result<psbt::PartiallySignedTransaction, SigningClientError> {
-        let serialized_psbt = psbt.serialize();
+        let serialized_psbt = taproot::prepare_for_key_path_signing(psbt).serialize();
         let response = self
             .inner
             .wallet()
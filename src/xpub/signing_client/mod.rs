@@ -1,9 +1,12 @@
 mod bitcoind;
 mod error;
 mod lnd;
+mod taproot;
 mod r#trait;
+mod webhook;
 
 pub use bitcoind::*;
 pub use error::*;
 pub use lnd::*;
 pub use r#trait::*;
+pub use webhook::*;
@@ -0,0 +1,96 @@
+use crate::primitives::bitcoin::psbt;
+
+/// Taproot sighashes commit to the previous outputs of every input in the tx.
+/// Remote signers can only produce key-path signatures if each input carries a
+/// `witness_utxo`, so fill it in from `non_witness_utxo` where it is missing.
+pub(super) fn prepare_for_key_path_signing(
+    psbt: &psbt::PartiallySignedTransaction,
+) -> psbt::PartiallySignedTransaction {
+    let mut psbt = psbt.clone();
+    if !psbt.inputs.iter().any(|inp| inp.tap_internal_key.is_some()) {
+        return psbt;
+    }
+    for (input, psbt_input) in psbt.unsigned_tx.input.iter().zip(psbt.inputs.iter_mut()) {
+        if psbt_input.witness_utxo.is_some() {
+            continue;
+        }
+        psbt_input.witness_utxo = psbt_input
+            .non_witness_utxo
+            .as_ref()
+            .and_then(|tx| tx.output.get(input.previous_output.vout as usize))
+            .cloned();
+    }
+    psbt
+}
+
+#[cfg(test)]
+mod tests {
+    use bdk::bitcoin::{
+        absolute::LockTime, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    };
+
+    use super::*;
+
+    fn psbt_spending(prev_tx: &Transaction) -> psbt::PartiallySignedTransaction {
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: prev_tx.txid(),
+                    vout: 1,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        };
+        let mut psbt = psbt::PartiallySignedTransaction::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].non_witness_utxo = Some(prev_tx.clone());
+        psbt
+    }
+
+    fn prev_tx() -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                TxOut {
+                    value: 1_000,
+                    script_pubkey: ScriptBuf::new(),
+                },
+                TxOut {
+                    value: 2_000,
+                    script_pubkey: ScriptBuf::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn fills_witness_utxo_for_taproot_inputs() {
+        let prev_tx = prev_tx();
+        let mut psbt = psbt_spending(&prev_tx);
+        psbt.inputs[0].tap_internal_key = Some(
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+                .parse()
+                .unwrap(),
+        );
+
+        let prepared = prepare_for_key_path_signing(&psbt);
+        assert_eq!(
+            prepared.inputs[0].witness_utxo,
+            Some(prev_tx.output[1].clone())
+        );
+    }
+
+    #[test]
+    fn leaves_non_taproot_psbts_untouched() {
+        let psbt = psbt_spending(&prev_tx());
+
+        let prepared = prepare_for_key_path_signing(&psbt);
+        assert_eq!(prepared.inputs[0].witness_utxo, None);
+    }
+}
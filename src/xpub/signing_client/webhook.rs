@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine};
+use bdk::bitcoin::hashes::{cmp, hmac, sha256, Hash, HashEngine};
+use serde::{Deserialize, Serialize};
+
+use super::{error::*, r#trait::*, taproot};
+use crate::primitives::bitcoin::psbt;
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-bria-signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-bria-timestamp";
+/// Maximum age (and clock skew) in seconds of a request a receiver should accept.
+pub const WEBHOOK_TIMESTAMP_TOLERANCE_SECS: u64 = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookSignerConfig {
+    pub endpoint: String,
+    pub hmac_secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WebhookSigningPayload {
+    psbt: String,
+}
+
+/// Posts the psbt to an external signing service. A failed request is
+/// recorded on the signing session and retried by the batch_signing job.
+///
+/// Every request carries the unix time it was sent at in `x-bria-timestamp` and
+/// `sha256=<hex hmac>` of `<timestamp>.<body>` in `x-bria-signature`. Receivers
+/// should recompute the hmac and reject requests whose timestamp is more than
+/// `WEBHOOK_TIMESTAMP_TOLERANCE_SECS` away from their own clock so that captured
+/// requests can't be replayed later on.
+pub struct WebhookRemoteSigner {
+    client: reqwest::Client,
+    endpoint: reqwest::Url,
+    hmac_secret: String,
+}
+
+impl WebhookRemoteSigner {
+    pub async fn connect(cfg: &WebhookSignerConfig) -> Result<Self, SigningClientError> {
+        let endpoint = reqwest::Url::parse(&cfg.endpoint).map_err(|e| {
+            SigningClientError::CouldNotConnect(format!(
+                "Invalid webhook endpoint {}: {e}",
+                cfg.endpoint
+            ))
+        })?;
+        if endpoint.scheme() != "https" {
+            return Err(SigningClientError::CouldNotConnect(format!(
+                "Webhook endpoint {} must use https",
+                cfg.endpoint
+            )));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| {
+                SigningClientError::CouldNotConnect(format!(
+                    "Failed to build webhook client for {}: {e}",
+                    cfg.endpoint
+                ))
+            })?;
+        Ok(Self {
+            client,
+            endpoint,
+            hmac_secret: cfg.hmac_secret.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl RemoteSigningClient for WebhookRemoteSigner {
+    async fn sign_psbt(
+        &mut self,
+        psbt: &psbt::PartiallySignedTransaction,
+    ) -> Result<psbt::PartiallySignedTransaction, SigningClientError> {
+        let raw_psbt = taproot::prepare_for_key_path_signing(psbt).serialize();
+        let body = serde_json::to_vec(&WebhookSigningPayload {
+            psbt: general_purpose::STANDARD.encode(raw_psbt),
+        })
+        .expect("Couldn't serialize webhook payload");
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_secs();
+        let signature = sign_body(&self.hmac_secret, timestamp, &body);
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={signature}"))
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                SigningClientError::RemoteCallFailure(format!(
+                    "Failed to sign psbt via webhook: {e}"
+                ))
+            })?;
+        let payload: WebhookSigningPayload = response.json().await.map_err(|e| {
+            SigningClientError::RemoteCallFailure(format!(
+                "Failed to read webhook signing response: {e}"
+            ))
+        })?;
+        let signed_psbt = general_purpose::STANDARD.decode(payload.psbt)?;
+        Ok(psbt::PartiallySignedTransaction::deserialize(&signed_psbt)?)
+    }
+}
+
+fn sign_body(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    hmac_sha256(secret, &[format!("{timestamp}.").as_bytes(), body].concat())
+}
+
+fn hmac_sha256(secret: &str, data: &[u8]) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+    engine.input(data);
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_string()
+}
+
+/// Checks the headers of a webhook request the way a receiver is expected to.
+pub fn verify_webhook_signature(
+    secret: &str,
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+    now: u64,
+) -> bool {
+    let Ok(timestamp) = timestamp.parse::<u64>() else {
+        return false;
+    };
+    if now.abs_diff(timestamp) > WEBHOOK_TIMESTAMP_TOLERANCE_SECS {
+        return false;
+    }
+    let Some(signature) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let expected = sign_body(secret, timestamp, body);
+    // the length of a hex encoded hmac isn't secret, its content is compared in constant time
+    signature.len() == expected.len()
+        && cmp::fixed_time_eq(signature.as_bytes(), expected.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_sha256_matches_rfc_4231() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn sign_body_covers_timestamp() {
+        assert_eq!(
+            sign_body("secret", 1_700_000_000, b"{}"),
+            hmac_sha256("secret", b"1700000000.{}")
+        );
+        assert_ne!(
+            sign_body("secret", 1_700_000_000, b"{}"),
+            sign_body("secret", 1_700_000_001, b"{}")
+        );
+    }
+
+    #[test]
+    fn verify_rejects_wrong_signatures() {
+        let body = br#"{"psbt":"cHNidP8="}"#;
+        let signature = sign_body("secret", 1_700_000_000, body);
+        let verify = |signature: &str| {
+            verify_webhook_signature("secret", "1700000000", signature, body, 1_700_000_000)
+        };
+        assert!(verify(&format!("sha256={signature}")));
+        assert!(!verify(&signature));
+        assert!(!verify(&format!(
+            "sha256={}",
+            sign_body("other", 1_700_000_000, body)
+        )));
+        assert!(!verify(&format!("sha256={}", &signature[1..])));
+    }
+
+    #[test]
+    fn verify_rejects_stale_timestamps() {
+        let body = br#"{"psbt":"cHNidP8="}"#;
+        let signature = format!("sha256={}", sign_body("secret", 1_700_000_000, body));
+        assert!(verify_webhook_signature(
+            "secret",
+            "1700000000",
+            &signature,
+            body,
+            1_700_000_000 + WEBHOOK_TIMESTAMP_TOLERANCE_SECS
+        ));
+        assert!(!verify_webhook_signature(
+            "secret",
+            "1700000000",
+            &signature,
+            body,
+            1_700_000_001 + WEBHOOK_TIMESTAMP_TOLERANCE_SECS
+        ));
+        assert!(!verify_webhook_signature(
+            "secret",
+            "1700000001",
+            &signature,
+            body,
+            1_700_000_000
+        ));
+    }
+
+    #[tokio::test]
+    async fn connect_requires_https() {
+        let cfg = WebhookSignerConfig {
+            endpoint: "http://signer.example.com/sign".to_string(),
+            hmac_secret: "secret".to_string(),
+        };
+        assert!(WebhookRemoteSigner::connect(&cfg).await.is_err());
+    }
+}
@@ -42,7 +42,7 @@ async fn external_id_already_exists() -> anyhow::Result<()> {
     let app = App::run(pool, AppConfig::default()).await?;
     let wallet_name = "test_import_descriptor".to_owned();
     let _ = app
-        .create_descriptors_wallet(&profile, wallet_name.clone(), external, internal)
+        .create_descriptors_wallet(&profile, wallet_name.clone(), external, internal, None)
         .await?;
 
     let external_id = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
@@ -125,7 +125,7 @@ async fn payout_queue_name_not_found() -> anyhow::Result<()> {
     let app = App::run(pool, AppConfig::default()).await?;
     let wallet_name = "test_wallet".to_owned();
     let _ = app
-        .create_descriptors_wallet(&profile, wallet_name.clone(), external, internal)
+        .create_descriptors_wallet(&profile, wallet_name.clone(), external, internal, None)
         .await?;
     let address = Address::parse_from_trusted_source("3EZQk4F8GURH5sqVMLTFisD17yNeKa7Dfs");
     let queue_name = "test".to_string();
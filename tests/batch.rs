@@ -0,0 +1,693 @@
+mod helpers;
+
+use bdk::{
+    bitcoin::{
+        absolute::LockTime,
+        address::NetworkUnchecked,
+        bip32::{ExtendedPrivKey, ExtendedPubKey},
+        hashes::Hash,
+        secp256k1::Secp256k1,
+        Address, Network, Witness,
+    },
+    wallet::AddressInfo,
+    LocalUtxo,
+};
+use futures::StreamExt;
+use rand::distributions::{Alphanumeric, DistString};
+
+use std::collections::HashMap;
+
+use bria::{
+    app::*, batch::*, ledger::*, outbox::*, payout::*, payout_queue::*, primitives::*,
+    profile::Profile, signing_session::*, utxo::*, wallet::*, xpub::*,
+};
+
+#[tokio::test]
+async fn replace_stuck_batch() -> anyhow::Result<()> {
+    let BroadcastBatch {
+        pool,
+        app,
+        profile,
+        payout_queue_id,
+        keychain_id,
+        batch_id,
+        tx_id,
+        input,
+        ..
+    } = broadcast_batch().await?;
+    let batches = Batches::new(&pool);
+    let utxos = Utxos::new(&pool);
+
+    let older_than = chrono::Utc::now() + chrono::Duration::minutes(1);
+    let candidates = batches
+        .find_rbf_candidates(profile.account_id, payout_queue_id, older_than, 102)
+        .await?;
+    assert_eq!(candidates, vec![batch_id]);
+    let candidates = batches
+        .find_rbf_candidates(profile.account_id, payout_queue_id, older_than, 101)
+        .await?;
+    assert!(candidates.is_empty());
+
+    let (new_batch_id, new_tx_id) = app.bump_batch_fee(&profile, batch_id, Some(10.0)).await?;
+    assert_ne!(new_tx_id, tx_id);
+
+    let replaced = batches.find_by_id(profile.account_id, batch_id).await?;
+    assert_eq!(replaced.superseded_by_batch_id, Some(new_batch_id));
+    let replacement = batches.find_by_id(profile.account_id, new_batch_id).await?;
+    assert_eq!(replacement.replaces_batch_id, Some(batch_id));
+    assert!(replacement.total_fee_sats() > replaced.total_fee_sats());
+
+    let inputs = utxos
+        .list_utxos_by_outpoint(&[(keychain_id, vec![input])].into_iter().collect())
+        .await?;
+    assert_eq!(inputs[0].spending_batch_id, Some(new_batch_id));
+
+    let candidates = batches
+        .find_rbf_candidates(profile.account_id, payout_queue_id, older_than, 102)
+        .await?;
+    assert!(candidates.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_unconfirmed_batch_to_add_payouts_to() -> anyhow::Result<()> {
+    let BroadcastBatch {
+        pool,
+        app,
+        profile,
+        payout_queue_id,
+        keychain_id,
+        batch_id,
+        input,
+        ..
+    } = broadcast_batch().await?;
+    let batches = Batches::new(&pool);
+    let utxos = Utxos::new(&pool);
+
+    let unconfirmed = batches
+        .find_unconfirmed_for_queue(profile.account_id, payout_queue_id)
+        .await?;
+    assert_eq!(unconfirmed, Some(batch_id));
+    let mut tx = pool.begin().await?;
+    let inputs = utxos.find_batch_inputs(&mut tx, batch_id).await?;
+    assert_eq!(inputs.get(&keychain_id), Some(&vec![input]));
+    tx.commit().await?;
+
+    // the replacement is not signed yet so there is nothing to add payouts to
+    app.bump_batch_fee(&profile, batch_id, Some(10.0)).await?;
+    let unconfirmed = batches
+        .find_unconfirmed_for_queue(profile.account_id, payout_queue_id)
+        .await?;
+    assert_eq!(unconfirmed, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_signing_sessions_by_state() -> anyhow::Result<()> {
+    let BroadcastBatch {
+        pool,
+        profile,
+        batch_id,
+        tx_id,
+        ..
+    } = broadcast_batch().await?;
+    let xpubs = XPubs::new(&pool);
+    let signing_sessions = SigningSessions::new(&pool);
+
+    let unsigned_psbt = Batches::new(&pool)
+        .find_by_id(profile.account_id, batch_id)
+        .await?
+        .unsigned_psbt;
+    assert_eq!(unsigned_psbt.unsigned_tx.txid(), tx_id);
+    let mut new_sessions = HashMap::new();
+    for (original, derivation) in [
+        ("tpubDDdzmt7vndmNywiVAeBPuhYLTFa7hmtfaqUxxTv5iLy7bxU93B62M9WKFSmn1BEN2vte8GDD3SUNKbupRajFW4RK8hd3i6W15pvTRQfo1fK", "m/84'/0'/0'"),
+        ("tpubDE8HT914zGpxhJhgoMX35xgNyjHy5d1neGXHjTLAtuUssTA7tNWNs177JsFPbJwD5FBXCHJYbwUC9AzSEpYHC4hKgaCvZyZTuCbWfNUWXoM", "m/48'/1'/0'/2'"),
+    ] {
+        let fingerprint = xpubs
+            .create(
+                NewAccountXPub::builder()
+                    .account_id(profile.account_id)
+                    .original(original.to_owned())
+                    .key_name(Alphanumeric.sample_string(&mut rand::thread_rng(), 32))
+                    .value(XPub::try_from((original, Some(derivation)))?)
+                    .build()
+                    .unwrap(),
+            )
+            .await?
+            .fingerprint();
+        let session = NewSigningSession::builder()
+            .account_id(profile.account_id)
+            .batch_id(batch_id)
+            .xpub_fingerprint(fingerprint)
+            .unsigned_psbt(unsigned_psbt.clone())
+            .build()?;
+        new_sessions.insert(fingerprint, session);
+    }
+    let mut sessions = signing_sessions.persist_sessions(new_sessions).await?;
+    let not_needed = *sessions.xpub_sessions.keys().next().unwrap();
+    sessions
+        .xpub_sessions
+        .get_mut(&not_needed)
+        .unwrap()
+        .signing_no_longer_needed();
+    let mut op = signing_sessions.begin_op().await?;
+    signing_sessions
+        .update_sessions(&mut op, &sessions.xpub_sessions)
+        .await?;
+    op.commit().await?;
+
+    let filter = |state| SigningSessionsListFilter {
+        batch_id: Some(batch_id),
+        state: Some(state),
+        ..Default::default()
+    };
+    let ret = signing_sessions
+        .list_for_account(
+            profile.account_id,
+            filter(SigningSessionState::NoLongerNeeded),
+            es_entity::PaginatedQueryArgs::default(),
+        )
+        .await?;
+    assert_eq!(ret.entities.len(), 1);
+    assert_eq!(ret.entities[0].xpub_fingerprint, not_needed);
+
+    let ret = signing_sessions
+        .list_for_account(
+            profile.account_id,
+            filter(SigningSessionState::Initialized),
+            es_entity::PaginatedQueryArgs {
+                first: 1,
+                after: None,
+            },
+        )
+        .await?;
+    assert_eq!(ret.entities.len(), 1);
+    assert_ne!(ret.entities[0].xpub_fingerprint, not_needed);
+    assert!(!ret.has_next_page);
+
+    let ret = signing_sessions
+        .list_for_account(
+            profile.account_id,
+            SigningSessionsListFilter {
+                batch_id: Some(batch_id),
+                ..Default::default()
+            },
+            es_entity::PaginatedQueryArgs {
+                first: 1,
+                after: None,
+            },
+        )
+        .await?;
+    assert_eq!(ret.entities.len(), 1);
+    assert!(ret.has_next_page);
+    let next = signing_sessions
+        .list_for_account(
+            profile.account_id,
+            SigningSessionsListFilter {
+                batch_id: Some(batch_id),
+                ..Default::default()
+            },
+            es_entity::PaginatedQueryArgs {
+                first: 1,
+                after: ret.end_cursor,
+            },
+        )
+        .await?;
+    assert_eq!(next.entities.len(), 1);
+    assert_ne!(next.entities[0].id, ret.entities[0].id);
+    assert!(!next.has_next_page);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_payout_queue_replaces_stuck_batches() -> anyhow::Result<()> {
+    // Detected at height 0 so the batch is old enough whatever height was synced last
+    let BroadcastBatch {
+        pool,
+        app,
+        profile,
+        payout_queue_id,
+        payout_queue_name,
+        payout_id,
+        batch_id,
+        tx_id,
+        ..
+    } = broadcast_batch_detected_at(0).await?;
+    app.update_payout_queue(
+        &profile,
+        payout_queue_id,
+        None,
+        Some(PayoutQueueConfig {
+            trigger: PayoutQueueTrigger::Manual,
+            rbf_after_mins: Some(0),
+            rbf_after_blocks: Some(0),
+            ..Default::default()
+        }),
+    )
+    .await?;
+    let mut events = app.subscribe_all(&profile, None, false).await?;
+    app.trigger_payout_queue(&profile, payout_queue_name)
+        .await?;
+
+    let replaced = tokio::time::timeout(std::time::Duration::from_secs(60), async {
+        while let Some(event) = events.next().await {
+            if let OutboxEventPayload::PayoutReplaced {
+                id,
+                batch_id,
+                replaced_batch_id,
+                replaced_tx_id,
+                ..
+            } = event.payload
+            {
+                return Some((id, batch_id, replaced_batch_id, replaced_tx_id));
+            }
+        }
+        None
+    })
+    .await?
+    .expect("no PayoutReplaced event");
+
+    let replacement = Batches::new(&pool)
+        .find_by_id(profile.account_id, batch_id)
+        .await?
+        .superseded_by_batch_id
+        .expect("batch was not replaced");
+    assert_eq!(replaced, (payout_id, replacement, batch_id, tx_id));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_payouts_to_unconfirmed_batch() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let bitcoind = helpers::bitcoind_client().await?;
+    let electrum_host = std::env::var("ELECTRUM_HOST").unwrap_or("localhost".to_string());
+    let app = App::run(
+        pool.clone(),
+        AppConfig {
+            blockchain: BlockchainConfig {
+                electrum_url: format!("{electrum_host}:50001"),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let xpriv = ExtendedPrivKey::new_master(Network::Regtest, &rand::random::<[u8; 32]>())?;
+    let xpub = ExtendedPubKey::from_priv(&Secp256k1::new(), &xpriv);
+    let wallet_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    app.create_wpkh_wallet(&profile, wallet_name.clone(), xpub.to_string(), None, None)
+        .await?;
+    let (_, address) = app
+        .new_address(&profile, wallet_name.clone(), None, None)
+        .await?;
+    let address = address
+        .to_string()
+        .parse::<Address<NetworkUnchecked>>()?
+        .assume_checked();
+    helpers::fund_addr(&bitcoind, &address, 1_000_000)?;
+    helpers::gen_blocks(&bitcoind, 6)?;
+    for _ in 0..300 {
+        let summary = app
+            .get_wallet_balance_summary(&profile, wallet_name.clone())
+            .await?;
+        if summary.utxo_settled > Satoshis::ZERO {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    let payout_queue_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    app.create_payout_queue(
+        &profile,
+        payout_queue_name.clone(),
+        None,
+        Some(PayoutQueueConfig {
+            trigger: PayoutQueueTrigger::Manual,
+            add_payouts_to_unconfirmed_batch: true,
+            ..Default::default()
+        }),
+    )
+    .await?;
+    let submit_payout = || {
+        app.submit_payout_to_address(
+            &profile,
+            wallet_name.clone(),
+            payout_queue_name.clone(),
+            "bcrt1q208tuy5rd3kvy8xdpv6yrczg7f3mnlk3lql7ej".to_string(),
+            Satoshis::from(100_000),
+            None,
+            None,
+        )
+    };
+    let payouts = Payouts::new(&pool);
+    let batches = Batches::new(&pool);
+    let utxos = Utxos::new(&pool);
+
+    let (first_payout_id, _) = submit_payout().await?;
+    app.trigger_payout_queue(&profile, payout_queue_name.clone())
+        .await?;
+    let replaced = wait_for_batch(&payouts, &batches, first_payout_id).await?;
+    // Stands in for the signing so the batch counts as unconfirmed
+    batches
+        .set_signed_tx(replaced.id, replaced.unsigned_psbt.unsigned_tx.clone())
+        .await?;
+    let mut tx = pool.begin().await?;
+    let replaced_inputs = utxos.find_batch_inputs(&mut tx, replaced.id).await?;
+    tx.commit().await?;
+
+    let (second_payout_id, _) = submit_payout().await?;
+    app.trigger_payout_queue(&profile, payout_queue_name)
+        .await?;
+    let replacement = wait_for_batch(&payouts, &batches, second_payout_id).await?;
+    assert_ne!(replacement.id, replaced.id);
+    assert_eq!(replacement.replaces_batch_id, Some(replaced.id));
+    assert_eq!(
+        payouts.find_by_id(first_payout_id).await?.batch_id,
+        Some(replacement.id)
+    );
+    assert_eq!(
+        batches
+            .find_by_id(profile.account_id, replaced.id)
+            .await?
+            .superseded_by_batch_id,
+        Some(replacement.id)
+    );
+    let mut tx = pool.begin().await?;
+    assert!(utxos
+        .find_batch_inputs(&mut tx, replaced.id)
+        .await?
+        .is_empty());
+    let replacement_inputs = utxos.find_batch_inputs(&mut tx, replacement.id).await?;
+    tx.commit().await?;
+    for (keychain_id, inputs) in replaced_inputs {
+        assert!(inputs
+            .iter()
+            .all(|input| replacement_inputs[&keychain_id].contains(input)));
+    }
+
+    Ok(())
+}
+
+async fn wait_for_batch(
+    payouts: &Payouts,
+    batches: &Batches,
+    payout_id: PayoutId,
+) -> anyhow::Result<Batch> {
+    for _ in 0..300 {
+        let payout = payouts.find_by_id(payout_id).await?;
+        if let Some(batch_id) = payout.batch_id {
+            let batch = batches.find_by_id(payout.account_id, batch_id).await?;
+            if batch.accounting_complete() {
+                return Ok(batch);
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    Err(anyhow::anyhow!("payout {payout_id} was not batched"))
+}
+
+struct BroadcastBatch {
+    pool: sqlx::PgPool,
+    app: App,
+    profile: Profile,
+    payout_queue_id: PayoutQueueId,
+    payout_queue_name: String,
+    payout_id: PayoutId,
+    keychain_id: KeychainId,
+    batch_id: BatchId,
+    tx_id: bitcoin::Txid,
+    input: bitcoin::OutPoint,
+}
+
+async fn broadcast_batch() -> anyhow::Result<BroadcastBatch> {
+    broadcast_batch_detected_at(101).await
+}
+
+async fn broadcast_batch_detected_at(height: u32) -> anyhow::Result<BroadcastBatch> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+
+    let original = "tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4";
+    let xpub = XPub::try_from((original, Some("m/84'/0'/0'"))).unwrap();
+    let wallet_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let id = XPubs::new(&pool)
+        .create(
+            NewAccountXPub::builder()
+                .account_id(profile.account_id)
+                .original(original.to_owned())
+                .key_name(wallet_name.clone())
+                .value(xpub)
+                .build()
+                .unwrap(),
+        )
+        .await?
+        .fingerprint();
+
+    let app = App::run(pool.clone(), AppConfig::default()).await?;
+    let (wallet_id, _) = app
+        .create_wpkh_wallet(&profile, wallet_name.clone(), id.to_string(), None, None)
+        .await?;
+    let payout_queue_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let payout_queue_id = app
+        .create_payout_queue(
+            &profile,
+            payout_queue_name.clone(),
+            None,
+            Some(PayoutQueueConfig {
+                trigger: PayoutQueueTrigger::Manual,
+                ..Default::default()
+            }),
+        )
+        .await?;
+    let wallet = Wallets::new(&pool)
+        .find_by_account_id_and_id(profile.account_id, wallet_id)
+        .await?;
+    let keychain_id = wallet.keychain_ids().next().unwrap();
+    let (payout_id, _) = app
+        .submit_payout_to_address(
+            &profile,
+            wallet_name,
+            payout_queue_name.clone(),
+            "bcrt1q208tuy5rd3kvy8xdpv6yrczg7f3mnlk3lql7ej".to_string(),
+            Satoshis::from(100_000),
+            None,
+            None,
+        )
+        .await?;
+
+    let utxos = Utxos::new(&pool);
+    let batches = Batches::new(&pool);
+    let address = "bcrt1q208tuy5rd3kvy8xdpv6yrczg7f3mnlk3lql7ej"
+        .parse::<Address<NetworkUnchecked>>()?
+        .assume_checked();
+
+    let income = LocalUtxo {
+        outpoint: bitcoin::OutPoint {
+            txid: bitcoin::Txid::hash(&rand::random::<[u8; 32]>()),
+            vout: 0,
+        },
+        txout: bitcoin::TxOut {
+            value: 200_000,
+            script_pubkey: address.script_pubkey(),
+        },
+        keychain: bitcoin::KeychainKind::External,
+        is_spent: false,
+    };
+    let income_address = AddressInfo {
+        index: 0,
+        address: address.clone(),
+        keychain: bitcoin::KeychainKind::External,
+    };
+    let (_, tx) = utxos
+        .new_utxo_detected(
+            profile.account_id,
+            wallet_id,
+            keychain_id,
+            &income_address,
+            &income,
+            Satoshis::from(1_000),
+            200,
+            false,
+            height.saturating_sub(1),
+        )
+        .await?
+        .unwrap();
+    tx.commit().await?;
+
+    let unsigned_tx = bitcoin::Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: vec![bitcoin::TxIn {
+            previous_output: income.outpoint,
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            bitcoin::TxOut {
+                value: 100_000,
+                script_pubkey: address.script_pubkey(),
+            },
+            bitcoin::TxOut {
+                value: 99_900,
+                script_pubkey: address.script_pubkey(),
+            },
+        ],
+    };
+    let tx_id = unsigned_tx.txid();
+    let change_outpoint = bitcoin::OutPoint {
+        txid: tx_id,
+        vout: 1,
+    };
+    let batch_id = BatchId::new();
+    let created_ledger_tx_id = LedgerTransactionId::new();
+    let summary = WalletSummary {
+        wallet_id,
+        current_keychain_id: keychain_id,
+        signing_keychains: vec![keychain_id],
+        total_in_sats: Satoshis::from(200_000),
+        total_spent_sats: Satoshis::from(100_000),
+        total_fee_sats: Satoshis::from(100),
+        cpfp_fee_sats: Satoshis::ZERO,
+        cpfp_details: HashMap::new(),
+        change_sats: Satoshis::from(99_900),
+        change_address: Some(address.clone().into()),
+        change_outpoint: Some(change_outpoint),
+        batch_created_ledger_tx_id: Some(created_ledger_tx_id),
+        batch_broadcast_ledger_tx_id: None,
+    };
+    let new_batch = NewBatch::builder()
+        .id(batch_id)
+        .account_id(profile.account_id)
+        .payout_queue_id(payout_queue_id)
+        .tx_id(tx_id)
+        .total_fee_sats(Satoshis::from(100))
+        .unsigned_psbt(bitcoin::psbt::PartiallySignedTransaction::from_unsigned_tx(
+            unsigned_tx.clone(),
+        )?)
+        .wallet_summaries([(wallet_id, summary)].into_iter().collect())
+        .build()?;
+    let mut tx = pool.begin().await?;
+    batches.create_in_op(&mut tx, new_batch).await?;
+    utxos
+        .reserve_utxos_in_batch(
+            &mut tx,
+            profile.account_id,
+            batch_id,
+            payout_queue_id,
+            bitcoin::FeeRate::from_sat_per_vb(5.0),
+            [(keychain_id, income.outpoint)],
+        )
+        .await?;
+    let payouts = Payouts::new(&pool);
+    let mut unbatched = payouts
+        .list_unbatched(&mut tx, profile.account_id, payout_queue_id)
+        .await?;
+    unbatched.commit_to_batch(tx_id, batch_id, std::iter::once((payout_id, 0)));
+    payouts.update_unbatched(&mut tx, unbatched).await?;
+    tx.commit().await?;
+    batches.set_signed_tx(batch_id, unsigned_tx).await?;
+
+    let ledger = Ledger::init(&pool).await?;
+    ledger
+        .batch_created(
+            pool.begin().await?,
+            created_ledger_tx_id,
+            BatchCreatedParams {
+                journal_id: wallet.journal_id,
+                ledger_account_ids: wallet.ledger_account_ids,
+                encumbered_fees: Satoshis::from(1_000),
+                meta: BatchCreatedMeta {
+                    batch_info: BatchWalletInfo {
+                        account_id: profile.account_id,
+                        wallet_id,
+                        batch_id,
+                        payout_queue_id,
+                        included_payouts: vec![payouts.find_by_id(payout_id).await?.into()],
+                    },
+                    tx_summary: WalletTransactionSummary {
+                        account_id: profile.account_id,
+                        wallet_id,
+                        current_keychain_id: keychain_id,
+                        bitcoin_tx_id: tx_id,
+                        total_utxo_in_sats: Satoshis::from(200_000),
+                        total_utxo_settled_in_sats: Satoshis::from(200_000),
+                        fee_sats: Satoshis::from(100),
+                        change_utxos: vec![ChangeOutput {
+                            outpoint: change_outpoint,
+                            address: address.clone().into(),
+                            satoshis: Satoshis::from(99_900),
+                        }],
+                        cpfp_details: None,
+                        cpfp_fee_sats: None,
+                    },
+                },
+            },
+        )
+        .await?;
+
+    let (mut tx, _, ledger_tx_id) = batches
+        .set_batch_broadcast_ledger_tx_id(tx_id, wallet_id)
+        .await?
+        .unwrap();
+    let change = LocalUtxo {
+        outpoint: change_outpoint,
+        txout: bitcoin::TxOut {
+            value: 99_900,
+            script_pubkey: address.script_pubkey(),
+        },
+        keychain: bitcoin::KeychainKind::Internal,
+        is_spent: false,
+    };
+    let change_address = AddressInfo {
+        index: 0,
+        address,
+        keychain: bitcoin::KeychainKind::Internal,
+    };
+    utxos
+        .spend_detected(
+            &mut tx,
+            profile.account_id,
+            wallet_id,
+            keychain_id,
+            ledger_tx_id,
+            std::iter::once(&income.outpoint),
+            &vec![(&change, change_address)],
+            Some((batch_id, payout_queue_id)),
+            Satoshis::from(100),
+            200,
+            height,
+        )
+        .await?
+        .unwrap();
+    tx.commit().await?;
+    ledger
+        .batch_broadcast(
+            pool.begin().await?,
+            created_ledger_tx_id,
+            ledger_tx_id,
+            Satoshis::ZERO,
+            wallet.ledger_account_ids,
+        )
+        .await?;
+
+    Ok(BroadcastBatch {
+        pool,
+        app,
+        profile,
+        payout_queue_id,
+        payout_queue_name,
+        payout_id,
+        keychain_id,
+        batch_id,
+        tx_id,
+        input: income.outpoint,
+    })
+}
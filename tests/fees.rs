@@ -1,4 +1,6 @@
-use bria::{fees::*, primitives::TxPriority};
+mod helpers;
+
+use bria::{app::*, fees::*, primitives::TxPriority};
 
 #[tokio::test]
 async fn mempool_space() -> anyhow::Result<()> {
@@ -17,3 +19,26 @@ async fn blockstream() -> anyhow::Result<()> {
     assert!(fee_rate.as_sat_per_vb() > 0.0);
     Ok(())
 }
+
+#[tokio::test]
+async fn app_reports_fee_estimator_health() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let app = App::run(
+        pool,
+        AppConfig {
+            fees: FeesConfig {
+                estimators: vec![FeeEstimatorKind::Static, FeeEstimatorKind::MempoolSpace],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+    let health = app.fee_estimator_health();
+    assert_eq!(
+        health.iter().map(|h| h.estimator).collect::<Vec<_>>(),
+        vec![FeeEstimatorKind::Static, FeeEstimatorKind::MempoolSpace]
+    );
+    assert!(health.iter().all(|h| h.successes == 0 && h.failures == 0));
+    Ok(())
+}
@@ -29,3 +29,120 @@ async fn new_address() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[cfg(feature = "compact-filters")]
+async fn sync_via_compact_filters() -> anyhow::Result<()> {
+    use bdk::bitcoin::{
+        bip32::{ExtendedPrivKey, ExtendedPubKey},
+        secp256k1::Secp256k1,
+    };
+    use bria::{
+        app::BlockchainConfig,
+        bdk::chain_source::{self, *},
+    };
+
+    let pool = helpers::init_pool().await?;
+    let bitcoind = helpers::bitcoind_client().await?;
+
+    let xpriv = ExtendedPrivKey::new_master(Network::Regtest, &rand::random::<[u8; 32]>())?;
+    let xpub = ExtendedPubKey::from_priv(&Secp256k1::new(), &xpriv);
+    let external = format!("wpkh({xpub}/0/*)");
+    let internal = format!("wpkh({xpub}/1/*)");
+    let keychain_cfg = KeychainConfig::try_from((external.as_ref(), internal.as_ref()))?;
+    let keychain_id = KeychainId::new();
+    let wallet = KeychainWallet::new(pool.clone(), Network::Regtest, keychain_id, keychain_cfg);
+
+    let addr = wallet.new_external_address().await?;
+    helpers::fund_addr(&bitcoind, &addr, 50_000)?;
+    helpers::gen_blocks(&bitcoind, 1)?;
+
+    let bitcoind_host = std::env::var("BITCOIND_HOST").unwrap_or("localhost".to_string());
+    let storage_dir = tempfile::tempdir()?;
+    let blockchain_cfg = BlockchainConfig {
+        chain_source: ChainSourceConfig::CompactFilters(CompactFiltersChainSourceConfig {
+            peers: vec![format!("{bitcoind_host}:18444")],
+            storage_dir: storage_dir.path().display().to_string(),
+            skip_blocks: None,
+        }),
+        ..Default::default()
+    };
+    let (blockchain, _) =
+        chain_source::init_blockchain(&blockchain_cfg, keychain_id, None, None).await?;
+    wallet.sync(blockchain).await?;
+
+    assert_eq!(wallet.balance().await?.confirmed, 50_000);
+
+    // keychains of the same network share one filter store
+    chain_source::init_blockchain(&blockchain_cfg, KeychainId::new(), None, None).await?;
+    let stores: Vec<_> = std::fs::read_dir(storage_dir.path())?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(stores, vec!["regtest"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_recovery_sweep() -> anyhow::Result<()> {
+    use bdk::bitcoin::{
+        bip32::{ExtendedPrivKey, ExtendedPubKey},
+        secp256k1::Secp256k1,
+        Sequence,
+    };
+    use bria::xpub::XPub;
+
+    let pool = helpers::init_pool().await?;
+    let bitcoind = helpers::bitcoind_client().await?;
+
+    let mut xpubs = Vec::new();
+    for _ in 0..3 {
+        let xpriv = ExtendedPrivKey::new_master(Network::Regtest, &rand::random::<[u8; 32]>())?;
+        let xpub = ExtendedPubKey::from_priv(&Secp256k1::new(), &xpriv);
+        xpubs.push(XPub::try_from((xpub.to_string(), None::<String>))?);
+    }
+    let recovery_xpub = xpubs.pop().unwrap();
+    let recovery_after_blocks = 5;
+    let keychain_cfg = KeychainConfig::sorted_multisig_with_recovery(
+        xpubs,
+        2,
+        recovery_xpub,
+        recovery_after_blocks,
+    );
+    let keychain_id = KeychainId::new();
+    let wallet = KeychainWallet::new(pool.clone(), Network::Regtest, keychain_id, keychain_cfg);
+
+    let addr = wallet.new_external_address().await?;
+    let tx_id = helpers::fund_addr(&bitcoind, &addr, 50_000)?;
+    let (outpoint, _, _) = helpers::lookup_tx_info(&bitcoind, tx_id, 50_000)?;
+    helpers::gen_blocks(&bitcoind, recovery_after_blocks as u64)?;
+    while wallet.balance().await?.confirmed == 0 {
+        let blockchain = helpers::electrum_blockchain().await?;
+        wallet.sync(blockchain).await?;
+    }
+
+    let destination =
+        Address::parse_from_trusted_source("bcrt1q6q79yce8vutqzpnwkxr5x8p5kxw5rc0hqqzwym");
+    let psbt = wallet
+        .build_recovery_sweep(
+            vec![outpoint],
+            destination.clone(),
+            bdk::FeeRate::from_sat_per_vb(1.0),
+        )
+        .await?;
+
+    // Spending via the recovery branch sets the relative timelock of older()
+    assert_eq!(psbt.unsigned_tx.input.len(), 1);
+    assert_eq!(psbt.unsigned_tx.input[0].previous_output, outpoint);
+    assert_eq!(
+        psbt.unsigned_tx.input[0].sequence,
+        Sequence::from_height(recovery_after_blocks as u16)
+    );
+    assert_eq!(psbt.unsigned_tx.output.len(), 1);
+    assert_eq!(
+        psbt.unsigned_tx.output[0].script_pubkey,
+        destination.script_pubkey()
+    );
+
+    Ok(())
+}
@@ -431,6 +431,239 @@ async fn create_batch() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn batch_replaced() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+
+    let ledger = Ledger::init(&pool).await?;
+
+    let account_id = AccountId::new();
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let mut tx = pool.begin().await?;
+    let journal_id = ledger
+        .create_journal_for_account(&mut tx, account_id, name.clone())
+        .await?;
+    let wallet_id = WalletId::new();
+    let wallet_ledger_accounts = ledger
+        .create_ledger_accounts_for_wallet(&mut tx, wallet_id)
+        .await?;
+
+    tx.commit().await?;
+
+    let batch_id = BatchId::new();
+    let fee_sats = Satoshis::from(2_346);
+    let total_spent_sats = Satoshis::from(100_000_000);
+    let total_utxo_in_sats = Satoshis::from(200_000_000);
+    let total_utxo_settled_in_sats = Satoshis::from(200_000_000);
+    let change_sats = total_utxo_in_sats - total_spent_sats - fee_sats;
+    let address = Address::parse_from_trusted_source("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    let outpoint = OutPoint {
+        txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+            .parse()
+            .unwrap(),
+        vout: 0,
+    };
+    let encumbered_fees = Satoshis::from(12_346);
+
+    let created_tx_id = LedgerTransactionId::new();
+    let tx = pool.begin().await?;
+    ledger
+        .batch_created(
+            tx,
+            created_tx_id,
+            BatchCreatedParams {
+                journal_id,
+                ledger_account_ids: wallet_ledger_accounts,
+                encumbered_fees,
+                meta: BatchCreatedMeta {
+                    batch_info: BatchWalletInfo {
+                        account_id,
+                        wallet_id,
+                        batch_id,
+                        payout_queue_id: PayoutQueueId::new(),
+                        included_payouts: Vec::new(),
+                    },
+                    tx_summary: WalletTransactionSummary {
+                        account_id,
+                        wallet_id,
+                        bitcoin_tx_id: outpoint.txid,
+                        total_utxo_settled_in_sats,
+                        total_utxo_in_sats,
+                        fee_sats,
+                        change_utxos: std::iter::once(ChangeOutput {
+                            outpoint,
+                            satoshis: change_sats,
+                            address,
+                        })
+                        .collect(),
+                        current_keychain_id: KeychainId::new(),
+                        cpfp_details: None,
+                        cpfp_fee_sats: None,
+                    },
+                },
+            },
+        )
+        .await?;
+
+    let broadcast_tx_id = LedgerTransactionId::new();
+    let tx = pool.begin().await?;
+    ledger
+        .batch_broadcast(
+            tx,
+            created_tx_id,
+            broadcast_tx_id,
+            Satoshis::from(1_000),
+            wallet_ledger_accounts,
+        )
+        .await?;
+
+    let tx = pool.begin().await?;
+    ledger
+        .batch_replaced(
+            tx,
+            LedgerTransactionId::new(),
+            created_tx_id,
+            Some(broadcast_tx_id),
+            wallet_ledger_accounts,
+            BatchId::new(),
+            "5010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+                .parse()
+                .unwrap(),
+        )
+        .await?;
+
+    let balances = ledger
+        .get_wallet_ledger_account_balances(journal_id, wallet_ledger_accounts)
+        .await?;
+    let summary = WalletBalanceSummary::from(balances);
+
+    assert_eq!(summary.effective_settled, Satoshis::ZERO);
+    assert_eq!(summary.effective_pending_outgoing, Satoshis::ZERO);
+    assert_eq!(summary.effective_encumbered_outgoing, Satoshis::ZERO);
+    assert_eq!(summary.fees_pending, Satoshis::ZERO);
+    assert_eq!(summary.fees_encumbered, Satoshis::ZERO);
+    assert_eq!(summary.utxo_settled, Satoshis::ZERO);
+    assert_eq!(summary.utxo_pending_incoming, Satoshis::ZERO);
+    assert_eq!(summary.utxo_encumbered_incoming, Satoshis::ZERO);
+    assert_eq!(summary.utxo_pending_outgoing, Satoshis::ZERO);
+
+    let account_balances = ledger
+        .get_account_ledger_account_balances(journal_id)
+        .await?;
+    let account_summary = AccountBalanceSummary::from(account_balances);
+    assert_summaries_match(summary, account_summary);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_cancelled() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+
+    let ledger = Ledger::init(&pool).await?;
+
+    let account_id = AccountId::new();
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let mut tx = pool.begin().await?;
+    let journal_id = ledger
+        .create_journal_for_account(&mut tx, account_id, name.clone())
+        .await?;
+    let wallet_id = WalletId::new();
+    let wallet_ledger_accounts = ledger
+        .create_ledger_accounts_for_wallet(&mut tx, wallet_id)
+        .await?;
+
+    tx.commit().await?;
+
+    let batch_id = BatchId::new();
+    let fee_sats = Satoshis::from(2_346);
+    let total_spent_sats = Satoshis::from(100_000_000);
+    let total_utxo_in_sats = Satoshis::from(200_000_000);
+    let total_utxo_settled_in_sats = Satoshis::from(200_000_000);
+    let change_sats = total_utxo_in_sats - total_spent_sats - fee_sats;
+    let address = Address::parse_from_trusted_source("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    let outpoint = OutPoint {
+        txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+            .parse()
+            .unwrap(),
+        vout: 0,
+    };
+    let encumbered_fees = Satoshis::from(12_346);
+
+    let created_tx_id = LedgerTransactionId::new();
+    let tx = pool.begin().await?;
+    ledger
+        .batch_created(
+            tx,
+            created_tx_id,
+            BatchCreatedParams {
+                journal_id,
+                ledger_account_ids: wallet_ledger_accounts,
+                encumbered_fees,
+                meta: BatchCreatedMeta {
+                    batch_info: BatchWalletInfo {
+                        account_id,
+                        wallet_id,
+                        batch_id,
+                        payout_queue_id: PayoutQueueId::new(),
+                        included_payouts: Vec::new(),
+                    },
+                    tx_summary: WalletTransactionSummary {
+                        account_id,
+                        wallet_id,
+                        bitcoin_tx_id: outpoint.txid,
+                        total_utxo_settled_in_sats,
+                        total_utxo_in_sats,
+                        fee_sats,
+                        change_utxos: std::iter::once(ChangeOutput {
+                            outpoint,
+                            satoshis: change_sats,
+                            address,
+                        })
+                        .collect(),
+                        current_keychain_id: KeychainId::new(),
+                        cpfp_details: None,
+                        cpfp_fee_sats: None,
+                    },
+                },
+            },
+        )
+        .await?;
+
+    let tx = pool.begin().await?;
+    ledger
+        .batch_cancelled(
+            tx,
+            LedgerTransactionId::new(),
+            created_tx_id,
+            wallet_ledger_accounts,
+        )
+        .await?;
+
+    let balances = ledger
+        .get_wallet_ledger_account_balances(journal_id, wallet_ledger_accounts)
+        .await?;
+    let summary = WalletBalanceSummary::from(balances);
+
+    assert_eq!(summary.effective_settled, Satoshis::ZERO);
+    assert_eq!(summary.effective_pending_outgoing, Satoshis::ZERO);
+    assert_eq!(summary.effective_encumbered_outgoing, Satoshis::ZERO);
+    assert_eq!(summary.fees_pending, Satoshis::ZERO);
+    assert_eq!(summary.fees_encumbered, Satoshis::ZERO);
+    assert_eq!(summary.utxo_settled, Satoshis::ZERO);
+    assert_eq!(summary.utxo_pending_incoming, Satoshis::ZERO);
+    assert_eq!(summary.utxo_encumbered_incoming, Satoshis::ZERO);
+    assert_eq!(summary.utxo_pending_outgoing, Satoshis::ZERO);
+
+    let account_balances = ledger
+        .get_account_ledger_account_balances(journal_id)
+        .await?;
+    let account_summary = AccountBalanceSummary::from(account_balances);
+    assert_summaries_match(summary, account_summary);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn spend_detected() -> anyhow::Result<()> {
     let pool = helpers::init_pool().await?;
@@ -787,6 +1020,192 @@ async fn utxo_dropped() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn utxo_reorged() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+
+    let ledger = Ledger::init(&pool).await?;
+
+    let account_id = AccountId::new();
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let mut tx = pool.begin().await?;
+    let journal_id = ledger
+        .create_journal_for_account(&mut tx, account_id, name.clone())
+        .await?;
+    let wallet_id = WalletId::new();
+    let wallet_ledger_accounts = ledger
+        .create_ledger_accounts_for_wallet(&mut tx, wallet_id)
+        .await?;
+
+    let one_btc = Satoshis::from(100_000_000);
+    let one_sat = Satoshis::from(1);
+    let address = Address::parse_from_trusted_source("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    let outpoint = OutPoint {
+        txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+            .parse()
+            .unwrap(),
+        vout: 0,
+    };
+
+    let keychain_id = KeychainId::new();
+    let pending_id = LedgerTransactionId::new();
+
+    ledger
+        .utxo_detected(
+            tx,
+            pending_id,
+            UtxoDetectedParams {
+                journal_id,
+                onchain_incoming_account_id: wallet_ledger_accounts.onchain_incoming_id,
+                onchain_fee_account_id: wallet_ledger_accounts.fee_id,
+                effective_incoming_account_id: wallet_ledger_accounts.effective_incoming_id,
+                meta: UtxoDetectedMeta {
+                    account_id,
+                    wallet_id,
+                    keychain_id,
+                    outpoint,
+                    satoshis: one_btc,
+                    address: address.clone(),
+                    encumbered_spending_fees: std::iter::once((outpoint, one_sat)).collect(),
+                    confirmation_time: None,
+                },
+            },
+        )
+        .await?;
+
+    let confirmed_id = LedgerTransactionId::new();
+    let tx = pool.begin().await?;
+    ledger
+        .utxo_settled(
+            tx,
+            confirmed_id,
+            UtxoSettledParams {
+                journal_id,
+                ledger_account_ids: wallet_ledger_accounts,
+                pending_id,
+                meta: UtxoSettledMeta {
+                    account_id,
+                    wallet_id,
+                    keychain_id,
+                    outpoint,
+                    satoshis: one_btc,
+                    address,
+                    confirmation_time: BlockTime {
+                        height: 1,
+                        timestamp: 123409,
+                    },
+                    already_spent_tx_id: None,
+                },
+            },
+        )
+        .await?;
+
+    let tx = pool.begin().await?;
+    ledger
+        .utxo_reorged(
+            tx,
+            LedgerTransactionId::new(),
+            confirmed_id,
+            wallet_ledger_accounts,
+        )
+        .await?;
+
+    let summary = WalletBalanceSummary::from(
+        ledger
+            .get_wallet_ledger_account_balances(journal_id, wallet_ledger_accounts)
+            .await?,
+    );
+
+    assert_eq!(summary.utxo_pending_incoming, one_btc);
+    assert_eq!(summary.effective_pending_income, one_btc);
+    assert_eq!(summary.utxo_settled, Satoshis::ZERO);
+    assert_eq!(summary.effective_settled, Satoshis::ZERO);
+    assert_eq!(summary.fees_encumbered, one_sat);
+
+    let account_summary = AccountBalanceSummary::from(
+        ledger
+            .get_account_ledger_account_balances(journal_id)
+            .await?,
+    );
+    assert_summaries_match(summary, account_summary);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn utxo_opening_balance() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+
+    let ledger = Ledger::init(&pool).await?;
+
+    let account_id = AccountId::new();
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let mut tx = pool.begin().await?;
+    let journal_id = ledger
+        .create_journal_for_account(&mut tx, account_id, name.clone())
+        .await?;
+    let wallet_id = WalletId::new();
+    let wallet_ledger_accounts = ledger
+        .create_ledger_accounts_for_wallet(&mut tx, wallet_id)
+        .await?;
+
+    let one_btc = Satoshis::from(100_000_000);
+    let one_sat = Satoshis::from(1);
+    let address = Address::parse_from_trusted_source("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    let outpoint = OutPoint {
+        txid: "4010e27ff7dc6d9c66a5657e6b3d94b4c4e394d968398d16fefe4637463d194d"
+            .parse()
+            .unwrap(),
+        vout: 0,
+    };
+
+    ledger
+        .utxo_opening_balance(
+            tx,
+            LedgerTransactionId::new(),
+            UtxoOpeningBalanceParams {
+                journal_id,
+                ledger_account_ids: wallet_ledger_accounts,
+                meta: UtxoOpeningBalanceMeta {
+                    account_id,
+                    wallet_id,
+                    keychain_id: KeychainId::new(),
+                    outpoint,
+                    satoshis: one_btc,
+                    address,
+                    encumbered_spending_fees: std::iter::once((outpoint, one_sat)).collect(),
+                    confirmation_time: BlockTime {
+                        height: 1,
+                        timestamp: 123409,
+                    },
+                    birthday_height: 10,
+                },
+            },
+        )
+        .await?;
+
+    let summary = WalletBalanceSummary::from(
+        ledger
+            .get_wallet_ledger_account_balances(journal_id, wallet_ledger_accounts)
+            .await?,
+    );
+
+    assert_eq!(summary.utxo_pending_incoming, Satoshis::ZERO);
+    assert_eq!(summary.effective_pending_income, Satoshis::ZERO);
+    assert_eq!(summary.utxo_settled, one_btc);
+    assert_eq!(summary.effective_settled, one_btc);
+    assert_eq!(summary.fees_encumbered, one_sat);
+
+    let account_summary = AccountBalanceSummary::from(
+        ledger
+            .get_account_ledger_account_balances(journal_id)
+            .await?,
+    );
+    assert_summaries_match(summary, account_summary);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn payout_cancelled() -> anyhow::Result<()> {
     let pool = helpers::init_pool().await?;
@@ -33,7 +33,7 @@ async fn payout() -> anyhow::Result<()> {
         .fingerprint();
 
     let app = App::run(pool, AppConfig::default()).await?;
-    app.create_wpkh_wallet(&profile, wallet_name.clone(), id.to_string(), None)
+    app.create_wpkh_wallet(&profile, wallet_name.clone(), id.to_string(), None, None)
         .await?;
 
     let group_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
@@ -81,7 +81,7 @@ async fn spending_policy() -> anyhow::Result<()> {
         .fingerprint();
 
     let app = App::run(pool, AppConfig::default()).await?;
-    app.create_wpkh_wallet(&profile, wallet_name.clone(), id.to_string(), None)
+    app.create_wpkh_wallet(&profile, wallet_name.clone(), id.to_string(), None, None)
         .await?;
 
     let queue_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
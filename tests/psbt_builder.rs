@@ -445,3 +445,70 @@ async fn find_tx_id(
     .await?;
     Ok(utxos.count != 0)
 }
+
+#[tokio::test]
+#[serial]
+async fn build_psbt_with_required_utxos() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+
+    let domain_current_keychain_id = Uuid::new_v4();
+    let xpub = XPub::try_from(("tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4", Some("m/84'/0'/0'"))).unwrap();
+    let keychain_cfg = KeychainConfig::wpkh(xpub);
+    let domain_current_keychain = KeychainWallet::new(
+        pool.clone(),
+        Network::Regtest,
+        domain_current_keychain_id.into(),
+        keychain_cfg,
+    );
+    let domain_addr = domain_current_keychain.new_external_address().await?;
+
+    let bitcoind = helpers::bitcoind_client().await?;
+    let large_funding = 100_000_000;
+    let small_funding = 50_000;
+    helpers::fund_addr(&bitcoind, &domain_addr, large_funding)?;
+    let small_tx_id = helpers::fund_addr(&bitcoind, &domain_addr, small_funding)?;
+    let (required_outpoint, _, _) = helpers::lookup_tx_info(&bitcoind, small_tx_id, small_funding)?;
+    helpers::gen_blocks(&bitcoind, 10)?;
+
+    let fee = FeeRate::from_sat_per_vb(1.0);
+    let cfg = PsbtBuilderConfig::builder()
+        .consolidate_deprecated_keychains(true)
+        .fee_rate(fee)
+        .required_utxos(
+            [(
+                KeychainId::from(domain_current_keychain_id),
+                vec![required_outpoint],
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .build()
+        .unwrap();
+    let builder = PsbtBuilder::new(cfg);
+
+    let domain_wallet_id = WalletId::new();
+    let send_amount = Satoshis::from(10_000_000);
+    let destination = Address::parse_from_trusted_source("mgWUuj1J1N882jmqFxtDepEC73Rr22E9GU");
+    let payouts = vec![(Uuid::new_v4(), destination, send_amount)];
+
+    let builder = builder
+        .wallet_payouts(domain_wallet_id, payouts)
+        .accept_current_keychain();
+    while !find_tx_id(&pool, domain_current_keychain_id, small_tx_id).await? {
+        let blockchain = helpers::electrum_blockchain().await?;
+        domain_current_keychain.sync(blockchain).await?;
+    }
+    let builder = domain_current_keychain
+        .dispatch_bdk_wallet(builder)
+        .await?
+        .next_wallet();
+    let FinishedPsbtBuild { psbt, .. } = builder.finish();
+    let psbt = psbt.expect("psbt should be built");
+    assert!(psbt
+        .unsigned_tx
+        .input
+        .iter()
+        .any(|input| input.previous_output == required_outpoint));
+
+    Ok(())
+}
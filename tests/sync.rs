@@ -0,0 +1,264 @@
+mod helpers;
+
+use bdk::{
+    bitcoin::{
+        bip32::{ChildNumber, ExtendedPrivKey, ExtendedPubKey},
+        secp256k1::Secp256k1,
+        Address, Network, PublicKey,
+    },
+    database::SyncTime,
+    electrum_client::{Client, ElectrumApi},
+    BlockTime,
+};
+use rand::distributions::{Alphanumeric, DistString};
+
+use std::time::Duration;
+
+use bria::{
+    app::*,
+    bdk::pg::{KeychainRescans, SyncCursors, SyncTimes},
+    primitives::*,
+    wallet::*,
+};
+
+const XPUB: &str = "tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4";
+
+async fn create_wallet(app: &App, profile: &bria::profile::Profile) -> anyhow::Result<WalletId> {
+    let (wallet_id, _) = app
+        .create_wpkh_wallet(
+            profile,
+            Alphanumeric.sample_string(&mut rand::thread_rng(), 32),
+            XPUB.to_owned(),
+            Some("m/84'/0'/0'".to_owned()),
+            None,
+        )
+        .await?;
+    Ok(wallet_id)
+}
+
+#[tokio::test]
+async fn sync_cursor_keeps_marks_that_arrive_during_a_sync() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let app = App::run(pool.clone(), AppConfig::default()).await?;
+    let wallet_id = create_wallet(&app, &profile).await?;
+    // Not one of the wallet's keychains so the sync jobs of the app leave it alone
+    let keychain_id = KeychainId::new();
+    let cursors = SyncCursors::new(pool.clone());
+    let pause = || tokio::time::sleep(Duration::from_millis(10));
+
+    cursors
+        .advance(
+            profile.account_id,
+            wallet_id,
+            keychain_id,
+            100,
+            chrono::Utc::now(),
+        )
+        .await?;
+    assert!(!cursors.needs_sync(keychain_id).await?);
+
+    cursors.mark_dirty(std::iter::once(keychain_id)).await?;
+    pause().await;
+    let synced_from = chrono::Utc::now();
+    pause().await;
+    cursors.mark_dirty(std::iter::once(keychain_id)).await?;
+    cursors
+        .advance(profile.account_id, wallet_id, keychain_id, 101, synced_from)
+        .await?;
+    assert!(cursors.needs_sync(keychain_id).await?);
+
+    pause().await;
+    cursors
+        .advance(
+            profile.account_id,
+            wallet_id,
+            keychain_id,
+            101,
+            chrono::Utc::now(),
+        )
+        .await?;
+    assert!(!cursors.needs_sync(keychain_id).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rescan_request_resets_sync_time_and_stores_overrides() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let app = App::run(pool.clone(), AppConfig::default()).await?;
+    let wallet_id = create_wallet(&app, &profile).await?;
+    // Not one of the wallet's keychains so the sync jobs of the app leave it alone
+    let keychain_id = KeychainId::new();
+    let sync_times = SyncTimes::new(keychain_id, pool.clone());
+    sync_times
+        .persist(SyncTime {
+            block_time: BlockTime {
+                height: 100,
+                timestamp: 1_700_000_000,
+            },
+        })
+        .await?;
+
+    let rescans = KeychainRescans::new(pool.clone());
+    let mut tx = pool.begin().await?;
+    rescans
+        .request(
+            &mut tx,
+            profile.account_id,
+            wallet_id,
+            keychain_id,
+            Some(90),
+            Some(50),
+        )
+        .await?;
+    tx.commit().await?;
+
+    assert!(sync_times.get().await?.is_none());
+    let rescan = rescans
+        .find_pending(keychain_id)
+        .await?
+        .expect("rescan not stored");
+    assert_eq!(rescan.birthday_height, Some(90));
+    assert_eq!(rescan.gap_limit, Some(50));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rescan_complete_keeps_rescans_requested_again() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let app = App::run(pool.clone(), AppConfig::default()).await?;
+    let wallet_id = create_wallet(&app, &profile).await?;
+    let keychain_id = KeychainId::new();
+    let rescans = KeychainRescans::new(pool.clone());
+    let request = |gap_limit| {
+        let pool = pool.clone();
+        let rescans = rescans.clone();
+        async move {
+            let mut tx = pool.begin().await?;
+            rescans
+                .request(
+                    &mut tx,
+                    profile.account_id,
+                    wallet_id,
+                    keychain_id,
+                    None,
+                    Some(gap_limit),
+                )
+                .await?;
+            tx.commit().await?;
+            anyhow::Ok(())
+        }
+    };
+
+    request(50).await?;
+    let running = rescans.find_pending(keychain_id).await?.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    request(100).await?;
+    rescans.complete(keychain_id, &running).await?;
+    let pending = rescans.find_pending(keychain_id).await?.unwrap();
+    assert_eq!(pending.gap_limit, Some(100));
+
+    rescans.complete(keychain_id, &pending).await?;
+    assert!(rescans.find_pending(keychain_id).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rescan_with_gap_limit_finds_utxo_beyond_stop_gap() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let bitcoind = helpers::bitcoind_client().await?;
+    let electrum_host = std::env::var("ELECTRUM_HOST").unwrap_or("localhost".to_string());
+    let electrum_url = format!("{electrum_host}:50001");
+    let app = App::run(
+        pool.clone(),
+        AppConfig {
+            blockchain: BlockchainConfig {
+                electrum_url: electrum_url.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // bdk stops scanning electrum after 20 unused addresses
+    let secp = Secp256k1::new();
+    let xpriv = ExtendedPrivKey::new_master(Network::Regtest, &rand::random::<[u8; 32]>())?;
+    let xpub = ExtendedPubKey::from_priv(&secp, &xpriv);
+    let child = xpub.derive_pub(
+        &secp,
+        &[
+            ChildNumber::from_normal_idx(0)?,
+            ChildNumber::from_normal_idx(30)?,
+        ],
+    )?;
+    let addr = Address::p2wpkh(&PublicKey::new(child.public_key), Network::Regtest)?;
+    helpers::fund_addr(&bitcoind, &addr, 50_000)?;
+    helpers::gen_blocks(&bitcoind, 1)?;
+    let electrum = Client::new(&electrum_url)?;
+    for _ in 0..300 {
+        if !electrum
+            .script_get_history(&addr.script_pubkey())?
+            .is_empty()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let wallet_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let (wallet_id, _) = app
+        .create_wpkh_wallet(&profile, wallet_name.clone(), xpub.to_string(), None, None)
+        .await?;
+    let keychain_id = Wallets::new(&pool)
+        .find_by_account_id_and_id(profile.account_id, wallet_id)
+        .await?
+        .keychain_ids()
+        .next()
+        .unwrap();
+    let sync_times = SyncTimes::new(keychain_id, pool.clone());
+    for _ in 0..300 {
+        if sync_times.get().await?.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(sync_times.get().await?.is_some());
+    let (_, utxos) = app.list_utxos(&profile, wallet_name.clone()).await?;
+    assert!(utxos.iter().all(|keychain| keychain.utxos.is_empty()));
+
+    app.rescan_wallet(&profile, wallet_name.clone(), None, Some(50))
+        .await?;
+    let mut detected = Satoshis::ZERO;
+    for _ in 0..300 {
+        let summary = app
+            .get_wallet_balance_summary(&profile, wallet_name.clone())
+            .await?;
+        detected = summary.utxo_pending_incoming + summary.utxo_settled;
+        if detected > Satoshis::ZERO {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(detected, Satoshis::from(50_000));
+    let (_, utxos) = app.list_utxos(&profile, wallet_name).await?;
+    assert_eq!(
+        utxos
+            .iter()
+            .map(|keychain| keychain.utxos.len())
+            .sum::<usize>(),
+        1
+    );
+    assert!(KeychainRescans::new(pool)
+        .find_pending(keychain_id)
+        .await?
+        .is_none());
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+mod helpers;
+
+use bdk::{
+    bitcoin::{address::NetworkUnchecked, hashes::Hash, Address},
+    wallet::AddressInfo,
+    LocalUtxo,
+};
+use rand::distributions::{Alphanumeric, DistString};
+
+use std::collections::HashMap;
+
+use bria::{app::*, primitives::*, utxo::*, wallet::*};
+
+#[tokio::test]
+async fn recovery_sweep_reserves_matured_utxos() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let app = App::run(pool.clone(), AppConfig::default()).await?;
+
+    let original = "tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4";
+    let wallet_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let (wallet_id, _) = app
+        .create_wpkh_wallet(
+            &profile,
+            wallet_name,
+            original.to_owned(),
+            Some("m/84'/0'/0'".to_owned()),
+            None,
+        )
+        .await?;
+    let keychain_id = Wallets::new(&pool)
+        .find_by_account_id_and_id(profile.account_id, wallet_id)
+        .await?
+        .keychain_ids()
+        .next()
+        .unwrap();
+
+    let utxos = Utxos::new(&pool);
+    let address = "bcrt1q208tuy5rd3kvy8xdpv6yrczg7f3mnlk3lql7ej"
+        .parse::<Address<NetworkUnchecked>>()?
+        .assume_checked();
+    let address_info = AddressInfo {
+        index: 0,
+        address: address.clone(),
+        keychain: bitcoin::KeychainKind::External,
+    };
+    let mut outpoints = Vec::new();
+    for block_height in [100, 110] {
+        let utxo = LocalUtxo {
+            outpoint: bitcoin::OutPoint {
+                txid: bitcoin::Txid::hash(&rand::random::<[u8; 32]>()),
+                vout: 0,
+            },
+            txout: bitcoin::TxOut {
+                value: 100_000,
+                script_pubkey: address.script_pubkey(),
+            },
+            keychain: bitcoin::KeychainKind::External,
+            is_spent: false,
+        };
+        let (_, mut tx) = utxos
+            .new_utxo_detected(
+                profile.account_id,
+                wallet_id,
+                keychain_id,
+                &address_info,
+                &utxo,
+                Satoshis::from(1_000),
+                200,
+                false,
+                block_height,
+            )
+            .await?
+            .unwrap();
+        utxos
+            .settle_utxo(&mut tx, keychain_id, utxo.outpoint, false, block_height)
+            .await?;
+        tx.commit().await?;
+        outpoints.push(utxo.outpoint);
+    }
+
+    let mut tx = pool.begin().await?;
+    let reserved = utxos
+        .reserve_matured_for_recovery_sweep(&mut tx, HashMap::from([(keychain_id, 10)]), 109)
+        .await?;
+    assert_eq!(reserved.get(&keychain_id), Some(&vec![outpoints[0]]));
+    let not_selectable = utxos
+        .outpoints_bdk_should_not_select(&mut tx, std::iter::once(keychain_id))
+        .await?;
+    assert_eq!(not_selectable.get(&keychain_id), Some(&vec![outpoints[0]]));
+    tx.commit().await?;
+
+    let mut tx = pool.begin().await?;
+    let released = utxos
+        .release_recovery_sweep(&mut tx, std::iter::once(keychain_id))
+        .await?;
+    assert_eq!(released, 1);
+    let not_selectable = utxos
+        .outpoints_bdk_should_not_select(&mut tx, std::iter::once(keychain_id))
+        .await?;
+    assert!(!not_selectable.contains_key(&keychain_id));
+    tx.commit().await?;
+
+    Ok(())
+}
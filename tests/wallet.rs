@@ -3,7 +3,7 @@ mod helpers;
 use rand::distributions::{Alphanumeric, DistString};
 use serde_json::json;
 
-use bria::{app::*, xpub::*};
+use bria::{app::*, fees::*, primitives::*, wallet::*, xpub::*};
 
 #[tokio::test]
 async fn create_wpkh_wallet() -> anyhow::Result<()> {
@@ -29,7 +29,7 @@ async fn create_wpkh_wallet() -> anyhow::Result<()> {
         .await?
         .fingerprint();
     let app = App::run(pool, AppConfig::default()).await?;
-    app.create_wpkh_wallet(&profile, name.clone(), id.to_string(), None)
+    app.create_wpkh_wallet(&profile, name.clone(), id.to_string(), None, None)
         .await?;
 
     let (_, addr) = app.new_address(&profile, name.clone(), None, None).await?;
@@ -49,6 +49,154 @@ async fn create_wpkh_wallet() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn create_taproot_wallet() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+
+    let original = "tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4";
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let app = App::run(pool, AppConfig::default()).await?;
+    let (_, xpub_ids) = app
+        .create_taproot_wallet(
+            &profile,
+            name.clone(),
+            original.to_owned(),
+            Some("m/86'/0'/0'".to_owned()),
+            None,
+        )
+        .await?;
+    assert_eq!(xpub_ids.len(), 1);
+
+    let (_, addr) = app.new_address(&profile, name, None, None).await?;
+    assert!(addr.to_string().starts_with("bcrt1p"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_sorted_multisig_with_recovery_wallet() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+    let app = App::run(pool, AppConfig::default()).await?;
+
+    let mut xpub_ids = Vec::new();
+    for (original, derivation) in [
+        ("tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4", "m/84'/0'/0'"),
+        ("tpubDDdzmt7vndmNywiVAeBPuhYLTFa7hmtfaqUxxTv5iLy7bxU93B62M9WKFSmn1BEN2vte8GDD3SUNKbupRajFW4RK8hd3i6W15pvTRQfo1fK", "m/84'/0'/0'"),
+        ("tpubDE8HT914zGpxhJhgoMX35xgNyjHy5d1neGXHjTLAtuUssTA7tNWNs177JsFPbJwD5FBXCHJYbwUC9AzSEpYHC4hKgaCvZyZTuCbWfNUWXoM", "m/48'/1'/0'/2'"),
+    ] {
+        let key_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+        let id = app
+            .import_xpub(
+                &profile,
+                key_name,
+                original.to_owned(),
+                Some(derivation.to_owned()),
+            )
+            .await?;
+        xpub_ids.push(id.to_string());
+    }
+    let recovery_xpub = xpub_ids.pop().unwrap();
+
+    let name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let (_, xpub_fingerprints) = app
+        .create_sorted_multisig_with_recovery_wallet(
+            &profile,
+            name.clone(),
+            xpub_ids,
+            2,
+            recovery_xpub,
+            144,
+            None,
+        )
+        .await?;
+    assert_eq!(xpub_fingerprints.len(), 3);
+
+    let (_, addr) = app.new_address(&profile, name.clone(), None, None).await?;
+    assert!(addr.to_string().starts_with("bcrt1q"));
+
+    let destination = "bcrt1q6q79yce8vutqzpnwkxr5x8p5kxw5rc0hqqzwym".to_owned();
+    let res = app
+        .sweep_with_recovery_key(&profile, name, destination, Some(1.0))
+        .await;
+    assert!(matches!(
+        res,
+        Err(bria::app::error::ApplicationError::WalletError(
+            bria::wallet::error::WalletError::NoMaturedRecoveryUtxos
+        ))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn taproot_inputs_lower_fees() -> anyhow::Result<()> {
+    let pool = helpers::init_pool().await?;
+    let profile = helpers::create_test_account(&pool).await?;
+
+    let wpkh_xpub = "tpubDDdzmt7vndmNywiVAeBPuhYLTFa7hmtfaqUxxTv5iLy7bxU93B62M9WKFSmn1BEN2vte8GDD3SUNKbupRajFW4RK8hd3i6W15pvTRQfo1fK";
+    let tr_xpub = "tpubDD4vFnWuTMEcZiaaZPgvzeGyMzWe6qHW8gALk5Md9kutDvtdDjYFwzauEFFRHgov8pAwup5jX88j5YFyiACsPf3pqn5hBjvuTLRAseaJ6b4";
+    let wpkh_keychain = KeychainWallet::new(
+        pool.clone(),
+        bitcoin::Network::Regtest,
+        KeychainId::new(),
+        KeychainConfig::wpkh(XPub::try_from((wpkh_xpub, Some("m/84'/0'/0'")))?),
+    );
+    let tr_keychain = KeychainWallet::new(
+        pool.clone(),
+        bitcoin::Network::Regtest,
+        KeychainId::new(),
+        KeychainConfig::tr(XPub::try_from((tr_xpub, Some("m/86'/0'/0'")))?),
+    );
+    let fees_client = FeesClient::new(FeesConfig::default());
+    assert!(
+        fees_to_encumber(&fees_client, tr_keychain.max_satisfaction_weight()).await?
+            < fees_to_encumber(&fees_client, wpkh_keychain.max_satisfaction_weight()).await?
+    );
+
+    let app = App::run(pool, AppConfig::default()).await?;
+    let wpkh_wallet = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    app.create_wpkh_wallet(
+        &profile,
+        wpkh_wallet.clone(),
+        wpkh_xpub.to_owned(),
+        Some("m/84'/0'/0'".to_owned()),
+        None,
+    )
+    .await?;
+    let tr_wallet = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    app.create_taproot_wallet(
+        &profile,
+        tr_wallet.clone(),
+        tr_xpub.to_owned(),
+        Some("m/86'/0'/0'".to_owned()),
+        None,
+    )
+    .await?;
+    let queue_name = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    app.create_payout_queue(&profile, queue_name.clone(), None, None)
+        .await?;
+
+    let destination = "bcrt1q208tuy5rd3kvy8xdpv6yrczg7f3mnlk3lql7ej".to_owned();
+    let sats = Satoshis::from(100_000);
+    let wpkh_fee = app
+        .estimate_payout_fee_to_address(
+            &profile,
+            wpkh_wallet,
+            queue_name.clone(),
+            destination.clone(),
+            sats,
+        )
+        .await?;
+    let tr_fee = app
+        .estimate_payout_fee_to_address(&profile, tr_wallet, queue_name, destination, sats)
+        .await?;
+    assert!(tr_fee < wpkh_fee);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn create_descriptors_wallet() -> anyhow::Result<()> {
     let pool = helpers::init_pool().await?;
@@ -59,7 +207,7 @@ async fn create_descriptors_wallet() -> anyhow::Result<()> {
     let app = App::run(pool, AppConfig::default()).await?;
     let wallet_name = "test_import_descriptor".to_owned();
     let (_, xpub_fingerprints) = app
-        .create_descriptors_wallet(&profile, wallet_name, external, internal)
+        .create_descriptors_wallet(&profile, wallet_name, external, internal, None)
         .await?;
     assert_eq!(xpub_fingerprints.len(), 1);
     assert_eq!(xpub_fingerprints[0].to_string(), "2f18f2f7");